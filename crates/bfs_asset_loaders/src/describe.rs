@@ -0,0 +1,371 @@
+use bevy::prelude::*;
+use ron::{Map, Number, Value};
+
+use bfs_color::*;
+use bfs_core::*;
+use bfs_movement::*;
+use bfs_reactions::*;
+
+/// Describes the named particle type in the same RON format [`ParticleTypesAsset`] loads, e.g.:
+///
+/// ```ron
+/// {
+///     "Water": {
+///         "density": 750,
+///         "max_velocity": 3,
+///         "liquid": 5,
+///         "colors": ["#0B80AB80"],
+///         "momentum": true,
+///     },
+/// }
+/// ```
+///
+/// Returns `None` if there is no particle type with that name. Movement priority isn't listed, as
+/// it's derived from the type's material.
+///
+/// [`ParticleTypesAsset`]: crate::ParticleTypesAsset
+pub fn describe_particle_type(world: &World, name: &str) -> Option<String> {
+    let entity = *world
+        .get_resource::<ParticleTypeMap>()?
+        .get(&name.to_string())?;
+    let mut types = Map::new();
+    types.insert(key(name), Value::Map(particle_type_config(world, entity)));
+    ron::ser::to_string_pretty(&Value::Map(types), ron::ser::PrettyConfig::default()).ok()
+}
+
+/// Collects the loader configuration for a particle type entity, keyed by component name.
+pub fn particle_type_config(world: &World, entity: Entity) -> Map {
+    let mut config = Map::new();
+    let mut insert = |name: &str, value: Value| {
+        config.insert(key(name), value);
+    };
+
+    if let Some(density) = component(world, entity, |density: &DensityBlueprint| &density.0) {
+        insert("density", int(density.0));
+    }
+    if let Some(velocity) = component(world, entity, |velocity: &VelocityBlueprint| &velocity.0) {
+        insert("max_velocity", int(velocity.max));
+    }
+    if component(world, entity, |momentum: &MomentumBlueprint| &momentum.0).is_some() {
+        insert("momentum", Value::Bool(true));
+    }
+    if let Some(passes_through) = world.get::<PassesThroughBlueprint>(entity) {
+        let mut names: Vec<&String> = passes_through.0.particle_types().iter().collect();
+        names.sort();
+        insert(
+            "passes_through",
+            Value::Seq(names.into_iter().map(|name| key(name)).collect()),
+        );
+    }
+    if let Some(erodes) = world.get::<ErodesBlueprint>(entity) {
+        insert(
+            "erodes",
+            map([
+                ("carries", key(&erodes.0.carries.name)),
+                ("threshold_velocity", int(erodes.0.threshold_velocity)),
+                ("chance", float(erodes.0.chance)),
+            ]),
+        );
+    }
+    if let Some(friction) = world.get::<FrictionBlueprint>(entity) {
+        insert("friction", float(friction.0 .0));
+    }
+    if let Some(viscosity) = world.get::<ViscosityBlueprint>(entity) {
+        insert("viscosity", int(viscosity.0 .0));
+    }
+    if let Some(allow_diagonal) = world.get::<AllowDiagonalBlueprint>(entity) {
+        insert("allow_diagonal", Value::Bool(allow_diagonal.0 .0));
+    }
+    if let Some(bounce) = world.get::<BounceBlueprint>(entity) {
+        insert("bounce", float(bounce.0.restitution));
+    }
+    if let Some(spread_budget) = world.get::<SpreadBudgetBlueprint>(entity) {
+        insert("spread_budget", int(spread_budget.0.budget));
+    }
+    if let Some(wind_resistance) = world.get::<WindResistanceBlueprint>(entity) {
+        insert("wind_resistance", float(wind_resistance.0 .0));
+    }
+    if let Some(leaves_trail) = world.get::<LeavesTrailBlueprint>(entity) {
+        insert(
+            "leaves_trail",
+            map([
+                ("particle", key(&leaves_trail.0.particle.name)),
+                ("chance", float(leaves_trail.0.chance)),
+                ("lifetime", int(leaves_trail.0.lifetime as i64)),
+            ]),
+        );
+    }
+    if let Some(infects_on_contact) = world.get::<InfectsOnContactBlueprint>(entity) {
+        let mut excludes: Vec<&String> = infects_on_contact.0.excludes.iter().collect();
+        excludes.sort();
+        insert(
+            "infects_on_contact",
+            map([
+                ("chance", float(infects_on_contact.0.chance)),
+                (
+                    "excludes",
+                    Value::Seq(excludes.into_iter().map(|name| key(name)).collect()),
+                ),
+            ]),
+        );
+    }
+
+    if component(world, entity, |wall: &WallBlueprint| &wall.0).is_some() {
+        insert("wall", Value::Bool(true));
+    }
+    if component(world, entity, |solid: &SolidBlueprint| &solid.0).is_some() {
+        insert("solid", Value::Bool(true));
+    }
+    if component(world, entity, |solid: &MovableSolidBlueprint| &solid.0).is_some() {
+        insert("movable_solid", Value::Bool(true));
+    }
+    if component(world, entity, |mover: &DiagonalMoverBlueprint| &mover.0).is_some() {
+        insert("diagonal_mover", Value::Bool(true));
+    }
+    if let Some(liquid) = component(world, entity, |liquid: &LiquidBlueprint| &liquid.0) {
+        if liquid.min_neighbors_to_spread == 0 && liquid.stack_bias == 0 {
+            insert("liquid", int(liquid.fluidity as i64));
+        } else {
+            insert(
+                "liquid",
+                map([
+                    ("fluidity", int(liquid.fluidity as i64)),
+                    (
+                        "min_neighbors_to_spread",
+                        int(liquid.min_neighbors_to_spread),
+                    ),
+                    ("stack_bias", float(f32::from(liquid.stack_bias) / 100.)),
+                ]),
+            );
+        }
+    }
+    if let Some(gas) = component(world, entity, |gas: &GasBlueprint| &gas.0) {
+        insert("gas", int(gas.fluidity as i64));
+    }
+
+    if let Some(colors) = component(world, entity, |colors: &ParticleColorBlueprint| &colors.0) {
+        insert("colors", colors_value(colors));
+    }
+    if let Some(flows_color) = component(world, entity, |flows: &FlowsColorBlueprint| &flows.0) {
+        insert("changes_colors", float(flows_color.rate));
+    }
+    if let Some(randomizes_color) =
+        component(world, entity, |randomizes: &RandomizesColorBlueprint| {
+            &randomizes.0
+        })
+    {
+        insert("randomizes_colors", float(randomizes_color.rate));
+    }
+    if let Some(positional_color) = world.get::<PositionalColorBlueprint>(entity) {
+        insert("positional_colors", float(positional_color.0.scale));
+    }
+    if world.get::<SeededColorBlueprint>(entity).is_some() {
+        insert("seeded_colors", Value::Bool(true));
+    }
+    if let Some(highlight) = world.get::<SurfaceHighlightBlueprint>(entity) {
+        insert(
+            "surface_highlight",
+            map([
+                ("color", hex(highlight.0.color)),
+                ("smoothing", float(highlight.0.smoothing)),
+            ]),
+        );
+    }
+    if let Some(gradient_color) = world.get::<GradientColorBlueprint>(entity) {
+        let value = match gradient_color.0.source {
+            ColorSource::Velocity => map([("source", key("velocity"))]),
+            ColorSource::Temperature { min, max } => map([
+                ("source", key("temperature")),
+                ("min", float(min)),
+                ("max", float(max)),
+            ]),
+        };
+        insert("gradient_color", value);
+    }
+    if let Some(color_by_velocity) = world.get::<ColorByVelocityBlueprint>(entity) {
+        insert(
+            "color_by_velocity",
+            map([
+                ("colors", colors_value(&color_by_velocity.0.gradient)),
+                ("max_speed", float(color_by_velocity.0.max_speed)),
+            ]),
+        );
+    }
+
+    if let Some(fire) = component(world, entity, |fire: &FireBlueprint| &fire.0) {
+        insert("fire", fire_value(fire));
+    }
+    if let Some(burns) = component(world, entity, |burns: &BurnsBlueprint| &burns.0) {
+        insert("burns", burns_value(burns));
+    }
+    if let Some(burning) = component(world, entity, |burning: &BurningBlueprint| &burning.0) {
+        insert(
+            "burning",
+            map([
+                ("duration", millis(burning.timer.duration())),
+                ("tick_rate", millis(burning.tick_timer.duration())),
+            ]),
+        );
+    }
+    if let Some(temperature) = world.get::<TemperatureBlueprint>(entity) {
+        insert("temperature", float(temperature.0 .0));
+    }
+    if let Some(conductivity) = world.get::<ThermalConductivityBlueprint>(entity) {
+        insert("thermal_conductivity", float(conductivity.0 .0));
+    }
+    if let Some(cooldown) = world.get::<ReactionCooldownBlueprint>(entity) {
+        insert("reaction_cooldown", int(cooldown.0.ticks));
+    }
+    if let Some(propagates) = world.get::<PropagatesBlueprint>(entity) {
+        insert(
+            "propagates",
+            map([
+                ("along", key(&propagates.0.along)),
+                ("speed_ticks", int(propagates.0.speed_ticks)),
+                ("triggers", key(&propagates.0.triggers.name)),
+            ]),
+        );
+    }
+    if let Some(evaporates) = world.get::<EvaporatesBlueprint>(entity) {
+        insert(
+            "evaporates",
+            map([
+                ("chance_per_tick", float(evaporates.0.chance_per_tick)),
+                ("produces", key(&evaporates.0.produces.name)),
+                ("requires_heat", Value::Bool(evaporates.0.requires_heat)),
+            ]),
+        );
+    }
+    if let Some(corrodes) = world.get::<CorrodesBlueprint>(entity) {
+        let targets = &corrodes.0.targets;
+        let target_names = [
+            (targets.walls, "wall"),
+            (targets.solids, "solid"),
+            (targets.movable_solids, "movable_solid"),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| key(name))
+        .collect();
+        insert(
+            "corrodes",
+            map([
+                ("chance_per_tick", float(corrodes.0.chance_per_tick)),
+                (
+                    "consumes_self_chance",
+                    float(corrodes.0.consumes_self_chance),
+                ),
+                ("targets", Value::Seq(target_names)),
+            ]),
+        );
+    }
+
+    config
+}
+
+/// Reads a component of a particle type, whether it's held in its blueprint or was inserted as is.
+fn component<'w, B: Component, T: Component>(
+    world: &'w World,
+    entity: Entity,
+    blueprint: fn(&B) -> &T,
+) -> Option<&'w T> {
+    world
+        .get::<B>(entity)
+        .map(blueprint)
+        .or_else(|| world.get::<T>(entity))
+}
+
+fn burns_value(burns: &Burns) -> Value {
+    let mut burns_map = Map::new();
+    burns_map.insert(key("duration"), millis(burns.duration));
+    burns_map.insert(key("tick_rate"), millis(burns.tick_rate));
+    if let Some(chance_destroy) = burns.chance_destroy_per_tick {
+        burns_map.insert(key("chance_destroy_per_tick"), float(chance_destroy));
+    }
+    if let Some(reaction) = &burns.reaction {
+        burns_map.insert(
+            key("reaction"),
+            map([
+                ("produces", key(&reaction.produces.name)),
+                ("chance_to_produce", float(reaction.chance_to_produce)),
+            ]),
+        );
+    }
+    if let Some(colors) = &burns.color {
+        burns_map.insert(key("colors"), colors_value(colors));
+    }
+    if let Some(fire) = &burns.spreads {
+        burns_map.insert(key("spreads"), fire_value(fire));
+    }
+    if burns.heat_output != 0. {
+        burns_map.insert(key("heat_output"), float(burns.heat_output));
+    }
+    if let Some(ignition_temperature) = burns.ignition_temperature {
+        burns_map.insert(key("ignition_temperature"), float(ignition_temperature));
+    }
+    if let Some(emits) = &burns.emits {
+        let (at, radius) = match emits.at {
+            ProductPlacement::Above => ("above", None),
+            ProductPlacement::Below => ("below", None),
+            ProductPlacement::NearestEmpty(radius) => ("nearest_empty", Some(radius)),
+        };
+        let mut emits_map = Map::new();
+        emits_map.insert(key("particle"), key(&emits.particle.name));
+        emits_map.insert(key("rate"), float(emits.rate));
+        emits_map.insert(key("at"), key(at));
+        if let Some(radius) = radius {
+            emits_map.insert(key("radius"), int(radius));
+        }
+        burns_map.insert(key("emits"), Value::Map(emits_map));
+    }
+    Value::Map(burns_map)
+}
+
+fn fire_value(fire: &Fire) -> Value {
+    let on_spread = match &fire.on_spread {
+        SpreadEffect::Keep => key("keep"),
+        SpreadEffect::Destroy => key("destroy"),
+        SpreadEffect::ConvertTo(particle) => map([("convert_to", key(&particle.name))]),
+    };
+    map([
+        ("burn_radius", float(fire.burn_radius)),
+        ("chance_to_spread", float(fire.chance_to_spread)),
+        ("on_spread", on_spread),
+    ])
+}
+
+fn colors_value(colors: &ParticleColor) -> Value {
+    Value::Seq(colors.palette.iter().map(|color| hex(*color)).collect())
+}
+
+fn map<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    let mut map = Map::new();
+    for (name, value) in entries {
+        map.insert(key(name), value);
+    }
+    Value::Map(map)
+}
+
+fn key(name: &str) -> Value {
+    Value::String(name.to_string())
+}
+
+fn int(value: impl Into<i64>) -> Value {
+    Value::Number(Number::from(value.into()))
+}
+
+/// Converts through the shortest decimal representation, so e.g. `0.1f32` is written as `0.1`
+/// rather than `0.10000000149011612`.
+fn float(value: impl Into<f64> + ToString) -> Value {
+    let value = value.to_string().parse::<f64>().unwrap_or_default();
+    Value::Number(Number::from(value))
+}
+
+fn millis(duration: std::time::Duration) -> Value {
+    int(duration.as_millis() as i64)
+}
+
+fn hex(color: Color) -> Value {
+    let [r, g, b, a] = color.to_srgba().to_u8_array();
+    Value::String(format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a))
+}
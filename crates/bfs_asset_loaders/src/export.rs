@@ -0,0 +1,204 @@
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use ron::{Map, Value};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use bfs_core::ParticleTypeMap;
+
+use crate::{particle_type_config, ParticleTypesAsset, ParticleTypesAssetLoaderError};
+
+pub(crate) struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveParticleDefinitionsEvent>().add_systems(
+            Update,
+            save_particle_definitions_system.run_if(on_event::<SaveParticleDefinitionsEvent>),
+        );
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ParticleDefinitionsError {
+    #[error("Could not access particle definitions file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not serialize particle definitions: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("Could not deserialize particle definitions: {0}")]
+    Deserialize(#[from] ron::error::SpannedError),
+    #[error("Could not load exported particle definitions: {0}")]
+    Load(#[from] ParticleTypesAssetLoaderError),
+    #[error("Exported particle definitions did not load back identically")]
+    RoundTrip,
+}
+
+/// Writes every registered particle type to a single file that [`ParticleTypesAsset`] can load,
+/// e.g. to share a palette built at runtime.
+///
+/// [`ParticleTypesAsset`]: crate::ParticleTypesAsset
+#[derive(Event, Clone, Debug)]
+pub struct SaveParticleDefinitionsEvent {
+    pub path: PathBuf,
+}
+
+impl SaveParticleDefinitionsEvent {
+    pub fn new(path: impl Into<PathBuf>) -> SaveParticleDefinitionsEvent {
+        SaveParticleDefinitionsEvent { path: path.into() }
+    }
+}
+
+/// Collects the loader configuration for every registered particle type, keyed by type name.
+pub fn particle_types_config(world: &World) -> Map {
+    let mut types = Map::new();
+    let Some(type_map) = world.get_resource::<ParticleTypeMap>() else {
+        return types;
+    };
+    let mut entries: Vec<(&String, &Entity)> = type_map.iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (name, entity) in entries {
+        types.insert(
+            Value::String(name.clone()),
+            Value::Map(particle_type_config(world, *entity)),
+        );
+    }
+    types
+}
+
+/// Writes every registered particle type to `path`.
+///
+/// Before anything is written, the exported RON is loaded into an empty world the same way a
+/// [`ParticleTypesAsset`] is, and the particle types it produces are compared against the live
+/// registry. A successful save is therefore guaranteed to load the same configuration.
+pub fn save_particle_types(world: &World, path: &Path) -> Result<(), ParticleDefinitionsError> {
+    let types = particle_types_config(world);
+    let ron_string = ron::ser::to_string_pretty(
+        &Value::Map(types.clone()),
+        ron::ser::PrettyConfig::default(),
+    )?;
+    if particle_types_config(&load_particle_types(&ron_string)?) != types {
+        return Err(ParticleDefinitionsError::RoundTrip);
+    }
+    File::create(path)?.write_all(ron_string.as_bytes())?;
+    Ok(())
+}
+
+/// Loads particle types from `ron_string` into a new, otherwise empty world.
+fn load_particle_types(ron_string: &str) -> Result<World, ParticleDefinitionsError> {
+    let asset = ParticleTypesAsset {
+        particle_types: ron::de::from_str(ron_string)?,
+    };
+    let mut world = World::new();
+    world.init_resource::<ParticleTypeMap>();
+    world
+        .run_system_once(
+            move |mut commands: Commands, mut type_map: ResMut<ParticleTypeMap>| {
+                asset.load_particle_types(&mut commands, &mut type_map)
+            },
+        )
+        .map_err(|_| ParticleDefinitionsError::RoundTrip)??;
+    Ok(world)
+}
+
+pub fn save_particle_definitions_system(
+    world: &World,
+    mut ev_save_definitions: EventReader<SaveParticleDefinitionsEvent>,
+) {
+    for ev in ev_save_definitions.read() {
+        if let Err(err) = save_particle_types(world, &ev.path) {
+            error!(
+                "Failed to save particle definitions to {:?}: {}",
+                ev.path, err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bfs_color::*;
+    use bfs_movement::*;
+    use bfs_reactions::*;
+    use std::fmt::Debug;
+
+    const PARTICLE_TYPES: &str = r##"{
+        "Water": {
+            "density": 750,
+            "max_velocity": 3,
+            "momentum": true,
+            "liquid": { "fluidity": 5, "min_neighbors_to_spread": 2, "stack_bias": 0.5 },
+            "colors": ["#0B80AB80"],
+            "changes_colors": 0.1,
+        },
+        "Sand": {
+            "density": 1250,
+            "max_velocity": 3,
+            "movable_solid": true,
+            "friction": 0.3,
+            "colors": ["#FFEB8AFF", "#F2E06BFF"],
+        },
+        "Wood": {
+            "wall": true,
+            "burns": {
+                "duration": 1000,
+                "tick_rate": 100,
+                "chance_destroy_per_tick": 0.1,
+                "spreads": { "burn_radius": 1.5, "chance_to_spread": 0.5, "on_spread": { "convert_to": "Ash" } },
+            },
+        },
+        "Steam": { "density": 4, "max_velocity": 1, "gas": 1, "evaporates": { "chance_per_tick": 0.01, "produces": "Water" } },
+    }"##;
+
+    fn get<'w, T: Component>(world: &'w World, name: &str) -> Option<&'w T> {
+        let entity = *world
+            .resource::<ParticleTypeMap>()
+            .get(&name.to_string())
+            .unwrap();
+        world.get::<T>(entity)
+    }
+
+    fn assert_same<T: Component + PartialEq + Debug>(
+        original: &World,
+        exported: &World,
+        name: &str,
+    ) {
+        assert_eq!(get::<T>(original, name), get::<T>(exported, name), "{name}");
+    }
+
+    #[test]
+    fn exported_particle_types_load_identically() {
+        let original = load_particle_types(PARTICLE_TYPES).unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "bfs_asset_loaders_{}_export.ron",
+            std::process::id()
+        ));
+        save_particle_types(&original, &path).unwrap();
+        let exported = load_particle_types(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        for name in ["Water", "Sand", "Wood", "Steam"] {
+            assert_same::<DensityBlueprint>(&original, &exported, name);
+            assert_same::<VelocityBlueprint>(&original, &exported, name);
+            assert_same::<MomentumBlueprint>(&original, &exported, name);
+            assert_same::<LiquidBlueprint>(&original, &exported, name);
+            assert_same::<MovableSolidBlueprint>(&original, &exported, name);
+            assert_same::<WallBlueprint>(&original, &exported, name);
+            assert_same::<GasBlueprint>(&original, &exported, name);
+            assert_same::<FrictionBlueprint>(&original, &exported, name);
+            assert_same::<ParticleColorBlueprint>(&original, &exported, name);
+            assert_same::<FlowsColorBlueprint>(&original, &exported, name);
+            assert_same::<BurnsBlueprint>(&original, &exported, name);
+            assert_same::<EvaporatesBlueprint>(&original, &exported, name);
+        }
+        assert!(get::<LiquidBlueprint>(&exported, "Water").is_some());
+        assert!(get::<BurnsBlueprint>(&exported, "Wood").is_some());
+        assert_eq!(
+            particle_types_config(&original),
+            particle_types_config(&exported)
+        );
+    }
+}
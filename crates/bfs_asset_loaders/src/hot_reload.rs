@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+use bfs_core::{ParticleType, ParticleTypeMap, ResetParticleEvent};
+
+use crate::ParticleTypesAsset;
+
+pub(crate) struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            hot_reload_particle_types.run_if(resource_exists::<HotReloadParticleTypes>),
+        );
+    }
+}
+
+/// Re-applies a [`ParticleTypesAsset`] whenever it changes on disk while present, so particle
+/// types can be tuned in an external editor without restarting.
+///
+/// Bevy only notices changes to asset files if file watching is enabled, e.g. through the
+/// `file_watcher` feature.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct HotReloadParticleTypes {
+    /// Whether existing particles of a reloaded type are reset, so they pick up the new
+    /// configuration right away. Otherwise only newly spawned particles use it.
+    pub reset_particles: bool,
+}
+
+/// Updates the particle types of a modified [`ParticleTypesAsset`] in place, so their entities in
+/// the [`ParticleTypeMap`] stay valid across reloads.
+pub fn hot_reload_particle_types(
+    mut commands: Commands,
+    mut type_map: ResMut<ParticleTypeMap>,
+    settings: Res<HotReloadParticleTypes>,
+    particle_types_assets: Res<Assets<ParticleTypesAsset>>,
+    parent_query: Query<&Children, With<ParticleType>>,
+    mut ev_asset: EventReader<AssetEvent<ParticleTypesAsset>>,
+) {
+    for ev in ev_asset.read() {
+        let AssetEvent::Modified { id } = ev else {
+            continue;
+        };
+        let Some(asset) = particle_types_assets.get(*id) else {
+            continue;
+        };
+        let updated = match asset.reload_particle_types(&mut commands, &mut type_map) {
+            Ok(updated) => updated,
+            Err(err) => {
+                error!("Failed to reload particle types: {}", err);
+                continue;
+            }
+        };
+        if !settings.reset_particles {
+            continue;
+        }
+        for entity in updated {
+            if let Ok(children) = parent_query.get(entity) {
+                for child in children.iter() {
+                    commands.trigger(ResetParticleEvent { entity: *child });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+
+use bfs_core::Particle;
+
+pub(crate) struct ImageImportPlugin;
+
+impl Plugin for ImageImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LoadImageAsParticlesEvent>()
+            .add_systems(Update, load_images_as_particles);
+    }
+}
+
+/// Spawns a particle for every opaque pixel of an image, e.g. to load levels drawn in an image
+/// editor.
+///
+/// Each pixel becomes the particle whose palette color is nearest to it, by squared RGB distance.
+/// If several palette colors are equally near, the first one wins. Fully transparent pixels are
+/// skipped. `origin` is the position of the image's bottom-left pixel, and the top row of the
+/// image ends up at the top.
+///
+/// If the image hasn't finished loading yet, it is spawned once it has.
+#[derive(Event, Clone, Debug)]
+pub struct LoadImageAsParticlesEvent {
+    pub handle: Handle<Image>,
+    pub origin: IVec2,
+    pub palette: Vec<(Color, Particle)>,
+}
+
+/// Returns the particle `palette` maps `color` to, or `None` if the palette is empty.
+pub fn nearest_palette_particle<'a>(
+    palette: &'a [(Color, Particle)],
+    color: Color,
+) -> Option<&'a Particle> {
+    let color = color.to_srgba();
+    let mut nearest: Option<(&Particle, f32)> = None;
+    for (palette_color, particle) in palette {
+        let palette_color = palette_color.to_srgba();
+        let distance = (color.red - palette_color.red).powi(2)
+            + (color.green - palette_color.green).powi(2)
+            + (color.blue - palette_color.blue).powi(2);
+        if nearest.is_none_or(|(_, nearest)| distance < nearest) {
+            nearest = Some((particle, distance));
+        }
+    }
+    nearest.map(|(particle, _)| particle)
+}
+
+/// The particles an image maps to through `palette`, with their positions. See
+/// [`LoadImageAsParticlesEvent`].
+pub fn image_to_particles(
+    image: &Image,
+    origin: IVec2,
+    palette: &[(Color, Particle)],
+) -> Vec<(IVec2, Particle)> {
+    let size = image.size();
+    let mut particles = Vec::new();
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let Ok(color) = image.get_color_at(x, y) else {
+                continue;
+            };
+            if color.alpha() == 0. {
+                continue;
+            }
+            if let Some(particle) = nearest_palette_particle(palette, color) {
+                let offset = IVec2::new(x as i32, (size.y - 1 - y) as i32);
+                particles.push((origin + offset, particle.clone()));
+            }
+        }
+    }
+    particles
+}
+
+pub fn load_images_as_particles(
+    mut commands: Commands,
+    images: Res<Assets<Image>>,
+    mut pending: Local<Vec<LoadImageAsParticlesEvent>>,
+    mut ev_load_image: EventReader<LoadImageAsParticlesEvent>,
+) {
+    pending.extend(ev_load_image.read().cloned());
+    pending.retain(|ev| {
+        let Some(image) = images.get(&ev.handle) else {
+            return true;
+        };
+        for (coordinates, particle) in image_to_particles(image, ev.origin, &ev.palette) {
+            commands.spawn((
+                particle,
+                Transform::from_xyz(coordinates.x as f32, coordinates.y as f32, 0.),
+            ));
+        }
+        false
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+
+    #[test]
+    fn image_pixels_spawn_their_nearest_palette_particles() {
+        // Rows run from the top of the image down. Magenta is as near to red as it is to blue,
+        // so it goes to red, which comes first in the palette.
+        let pixels = [
+            [RED, CLEAR, BLUE],
+            [[255, 0, 255, 255], [230, 25, 0, 255], BLUE],
+            [BLUE, BLUE, RED],
+        ];
+        let image = Image::new(
+            Extent3d {
+                width: 3,
+                height: 3,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            pixels.concat().concat(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+
+        let mut world = World::new();
+        world.init_resource::<Events<LoadImageAsParticlesEvent>>();
+        let handle = world.get_resource_or_init::<Assets<Image>>().add(image);
+        world.send_event(LoadImageAsParticlesEvent {
+            handle,
+            origin: IVec2::new(10, 20),
+            palette: vec![
+                (Color::srgb(1., 0., 0.), Particle::new("Sand")),
+                (Color::srgb(0., 0., 1.), Particle::new("Water")),
+            ],
+        });
+        world.run_system_once(load_images_as_particles).unwrap();
+
+        let mut query = world.query::<(&Particle, &Transform)>();
+        let mut spawned: Vec<(IVec2, String)> = query
+            .iter(&world)
+            .map(|(particle, transform)| {
+                (
+                    transform.translation.truncate().as_ivec2(),
+                    particle.name.to_string(),
+                )
+            })
+            .collect();
+        spawned.sort_by_key(|(position, _)| (-position.y, position.x));
+        let expected = [
+            ((10, 22), "Sand"),
+            ((12, 22), "Water"),
+            ((10, 21), "Sand"),
+            ((11, 21), "Sand"),
+            ((12, 21), "Water"),
+            ((10, 20), "Water"),
+            ((11, 20), "Water"),
+            ((12, 20), "Sand"),
+        ]
+        .map(|((x, y), name)| (IVec2::new(x, y), name.to_string()));
+        assert_eq!(spawned, expected);
+    }
+}
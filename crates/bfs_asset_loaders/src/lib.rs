@@ -1,3 +1,8 @@
+mod describe;
+mod export;
+mod hot_reload;
+mod image_import;
+
 use bevy::{
     asset::{io::Reader, AssetLoader, LoadContext},
     prelude::*,
@@ -5,6 +10,8 @@ use bevy::{
     utils::Duration,
 };
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use thiserror::Error;
 
 use bfs_color::*;
@@ -12,12 +19,20 @@ use bfs_core::*;
 use bfs_movement::*;
 use bfs_reactions::*;
 
+pub use describe::*;
+pub use export::*;
+pub use hot_reload::*;
+pub use image_import::*;
+
 pub struct FallingSandAssetLoadersPlugin;
 
 impl bevy::prelude::Plugin for FallingSandAssetLoadersPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_plugins((ExportPlugin, HotReloadPlugin, ImageImportPlugin));
         app.init_asset::<ParticleTypesAsset>()
-            .init_asset_loader::<ParticleTypesAssetLoader>();
+            .init_asset_loader::<ParticleTypesAssetLoader>()
+            .init_asset::<ParticleStampsAsset>()
+            .init_asset_loader::<ParticleStampsAssetLoader>();
     }
 }
 
@@ -26,46 +41,270 @@ pub struct ParticleTypesAsset {
     pub particle_types: ron::Map,
 }
 
+/// The top-level key holding per-category default components in a particle types file.
+pub const DEFAULTS_KEY: &str = "defaults";
+
+/// The material category keys that `defaults` may define, in the order they are checked.
+pub const MATERIAL_CATEGORIES: [&str; 6] = [
+    "wall",
+    "solid",
+    "movable_solid",
+    "liquid",
+    "gas",
+    "diagonal_mover",
+];
+
+/// Every component [`ParticleTypesAsset`] may insert on a particle type entity, along with the
+/// movement priority derived from its material. Removed before a type is reloaded, so components
+/// dropped from the asset don't linger.
+type LoadedComponents = (
+    (
+        WallBlueprint,
+        DensityBlueprint,
+        VelocityBlueprint,
+        MomentumBlueprint,
+        PassesThroughBlueprint,
+        AllowDiagonalBlueprint,
+        FrictionBlueprint,
+        ViscosityBlueprint,
+        BounceBlueprint,
+        SpreadBudgetBlueprint,
+        WindResistanceBlueprint,
+        LeavesTrailBlueprint,
+        InfectsOnContactBlueprint,
+        ErodesBlueprint,
+    ),
+    (
+        LiquidBlueprint,
+        MovableSolidBlueprint,
+        SolidBlueprint,
+        GasBlueprint,
+        DiagonalMoverBlueprint,
+        MovementPriorityBlueprint,
+    ),
+    (
+        ParticleColorBlueprint,
+        FlowsColorBlueprint,
+        RandomizesColorBlueprint,
+        PositionalColorBlueprint,
+        SeededColorBlueprint,
+        SurfaceHighlightBlueprint,
+        GradientColorBlueprint,
+        ColorByVelocityBlueprint,
+    ),
+    (
+        BurnsBlueprint,
+        FireBlueprint,
+        BurningBlueprint,
+        ReactionCooldownBlueprint,
+        PropagatesBlueprint,
+        EvaporatesBlueprint,
+        CorrodesBlueprint,
+        TemperatureBlueprint,
+        ThermalConductivityBlueprint,
+    ),
+);
+
 impl ParticleTypesAsset {
+    /// Spawns a particle type entity for every particle defined in the asset.
+    ///
+    /// An optional top-level `"defaults"` map may define default components per material category
+    /// (`"wall"`, `"solid"`, `"movable_solid"`, `"liquid"`, `"gas"` and `"diagonal_mover"`). A particle's category is
+    /// given by which of those keys it defines, and components from the category defaults are
+    /// applied unless the particle defines them itself.
+    ///
+    /// A particle with a malformed component is skipped with a warning, and the rest are still
+    /// loaded. In that case the first error encountered is returned once loading is done. A
+    /// malformed `"defaults"` map fails the whole load, as no particle could be loaded correctly.
     pub fn load_particle_types(
         &self,
         commands: &mut Commands,
         type_map: &mut ResMut<ParticleTypeMap>,
-    ) {
+    ) -> Result<(), ParticleTypesAssetLoaderError> {
+        let defaults = self.category_defaults()?;
+
+        let mut first_error = None;
         for (key, map) in self.particle_types.iter() {
-            let particle_name = key
-                .clone()
-                .into_rust::<String>()
-                .expect("Invalid particle name format");
-            let entity = commands.spawn(Name::new(particle_name.clone())).id();
+            if let Err(err) =
+                self.load_particle_type(commands, type_map, key, map, &defaults, false)
+            {
+                warn!("Skipping particle type: {}", err);
+                first_error.get_or_insert(err);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
 
-            type_map.insert(particle_name.clone(), entity);
-            commands.entity(entity).insert((
-                ParticleType {
-                    name: particle_name.clone(),
-                },
-                Transform::default(),
-            ));
+    /// Loads every particle in the asset like [`ParticleTypesAsset::load_particle_types`], but
+    /// updates particle types that are already loaded instead of leaving them be.
+    ///
+    /// An already loaded type keeps its entity. The components this loader manages are removed from
+    /// it and inserted again from the asset, and the updated type entities are returned so the
+    /// caller can reset their particles. A particle that fails to load leaves its existing type
+    /// untouched, and types missing from the asset are kept as they are.
+    pub fn reload_particle_types(
+        &self,
+        commands: &mut Commands,
+        type_map: &mut ResMut<ParticleTypeMap>,
+    ) -> Result<Vec<Entity>, ParticleTypesAssetLoaderError> {
+        let defaults = self.category_defaults()?;
+
+        let mut updated = vec![];
+        for (key, map) in self.particle_types.iter() {
+            match self.load_particle_type(commands, type_map, key, map, &defaults, true) {
+                Ok(Some(entity)) => updated.push(entity),
+                Ok(None) => {}
+                Err(err) => warn!("Skipping particle type: {}", err),
+            }
+        }
+        Ok(updated)
+    }
+
+    fn load_particle_type(
+        &self,
+        commands: &mut Commands,
+        type_map: &mut ResMut<ParticleTypeMap>,
+        key: &ron::Value,
+        map: &ron::Value,
+        defaults: &[(String, ron::Map)],
+        update: bool,
+    ) -> Result<Option<Entity>, ParticleTypesAssetLoaderError> {
+        let particle_name = key.clone().into_rust::<String>().map_err(|err| {
+            ParticleTypesAssetLoaderError::Component {
+                particle: format!("{:?}", key),
+                component: String::from("name"),
+                reason: config_error("Expected string for particle name", err),
+            }
+        })?;
+        if particle_name == DEFAULTS_KEY {
+            return Ok(None);
+        }
+        let mut particle_data = map.clone().into_rust::<ron::Map>().map_err(|err| {
+            ParticleTypesAssetLoaderError::Component {
+                particle: particle_name.clone(),
+                component: String::new(),
+                reason: config_error("Expected map of particle data", err),
+            }
+        })?;
+        self.apply_category_defaults(&particle_name, &mut particle_data, defaults);
+
+        if let Some(entity) = type_map.get(&particle_name).copied().filter(|_| update) {
+            // Check the new configuration on a throwaway entity first, so a malformed one leaves
+            // the type as it was.
+            let scratch = commands.spawn_empty().id();
+            let checked = self.insert_components(commands, scratch, &particle_name, &particle_data);
+            commands.entity(scratch).despawn();
+            checked?;
+
+            commands.entity(entity).remove::<LoadedComponents>();
+            self.insert_components(commands, entity, &particle_name, &particle_data)?;
+            return Ok(Some(entity));
+        }
+
+        let entity = commands.spawn(Name::new(particle_name.clone())).id();
+        commands.entity(entity).insert((
+            ParticleType {
+                name: particle_name.clone(),
+            },
+            Transform::default(),
+        ));
+        if let Err(err) = self.insert_components(commands, entity, &particle_name, &particle_data) {
+            commands.entity(entity).despawn();
+            return Err(err);
+        }
+        type_map.insert(particle_name, entity);
+        Ok(None)
+    }
+
+    /// Deserializes each component for the particle entity, stopping at the first malformed one.
+    fn insert_components(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        particle_name: &str,
+        particle_data: &ron::Map,
+    ) -> Result<(), ParticleTypesAssetLoaderError> {
+        for (component_str, component_data) in particle_data.iter() {
+            let Ok(component_str) = component_str.clone().into_rust::<String>() else {
+                continue;
+            };
+            self.handle_component(
+                commands,
+                entity,
+                particle_name,
+                &component_str,
+                component_data.clone(),
+            )?;
+        }
+        Ok(())
+    }
 
-            let particle_data = map
+    fn category_defaults(&self) -> Result<Vec<(String, ron::Map)>, ParticleTypesAssetLoaderError> {
+        let defaults_key = ron::Value::String(DEFAULTS_KEY.to_string());
+        let Some((_, defaults)) = self
+            .particle_types
+            .iter()
+            .find(|(key, _)| **key == defaults_key)
+        else {
+            return Ok(vec![]);
+        };
+        let defaults_error =
+            |category: &str, reason: String| ParticleTypesAssetLoaderError::Component {
+                particle: DEFAULTS_KEY.to_string(),
+                component: category.to_string(),
+                reason,
+            };
+        let mut category_defaults = vec![];
+        for (category, components) in defaults
+            .clone()
+            .into_rust::<ron::Map>()
+            .map_err(|err| defaults_error("", config_error("Expected map of categories", err)))?
+            .iter()
+        {
+            let category = category
                 .clone()
-                .into_rust::<ron::Map>()
-                .expect("Config error: Expected map of particle data");
-
-            // Deserialize each component for the particle entity
-            particle_data
-                .iter()
-                .for_each(|(component_str, component_data)| {
-                    if let Ok(component_str) = component_str.clone().into_rust::<String>() {
-                        self.handle_component(
-                            commands,
-                            entity,
-                            &particle_name,
-                            &component_str,
-                            component_data.clone(),
-                        );
-                    }
-                });
+                .into_rust::<String>()
+                .map_err(|err| defaults_error("", config_error("Expected category name", err)))?;
+            if !MATERIAL_CATEGORIES.contains(&category.as_str()) {
+                warn!(
+                    "Ignoring defaults for unknown material category '{}'",
+                    category
+                );
+                continue;
+            }
+            let components = components.clone().into_rust::<ron::Map>().map_err(|err| {
+                defaults_error(&category, config_error("Expected map of components", err))
+            })?;
+            category_defaults.push((category, components));
+        }
+        Ok(category_defaults)
+    }
+
+    fn apply_category_defaults(
+        &self,
+        particle_name: &str,
+        particle_data: &mut ron::Map,
+        defaults: &[(String, ron::Map)],
+    ) {
+        let categories: Vec<&(String, ron::Map)> = defaults
+            .iter()
+            .filter(|(category, _)| {
+                let category = ron::Value::String(category.clone());
+                particle_data.keys().any(|key| *key == category)
+            })
+            .collect();
+        if categories.len() > 1 {
+            warn!(
+                "Particle '{}' matches multiple material categories; applying defaults for '{}' only",
+                particle_name, categories[0].0
+            );
+        }
+        if let Some((_, components)) = categories.first() {
+            for (component, value) in components.iter() {
+                if !particle_data.keys().any(|key| key == component) {
+                    particle_data.insert(component.clone(), value.clone());
+                }
+            }
         }
     }
 }
@@ -77,35 +316,79 @@ impl ParticleTypesAsset {
         particle_name: &str,
         component_str: &str,
         component_data: ron::Value,
-    ) {
-        match component_str {
+    ) -> Result<(), ParticleTypesAssetLoaderError> {
+        let result = match component_str {
             "density" => self.insert_density(commands, entity, component_data),
             "max_velocity" => self.insert_max_velocity(commands, entity, component_data),
             "momentum" => self.insert_momentum(commands, entity, component_data),
+            "passes_through" => self.insert_passes_through(commands, entity, component_data),
+            "erodes" => self.insert_erodes(commands, entity, component_data),
+            "friction" => self.insert_friction(commands, entity, component_data),
+            "viscosity" => self.insert_viscosity(commands, entity, component_data),
+            "allow_diagonal" => self.insert_allow_diagonal(commands, entity, component_data),
+            "bounce" => self.insert_bounce(commands, entity, component_data),
+            "spread_budget" => self.insert_spread_budget(commands, entity, component_data),
+            "wind_resistance" => self.insert_wind_resistance(commands, entity, component_data),
+            "leaves_trail" => self.insert_leaves_trail(commands, entity, component_data),
+            "infects_on_contact" => {
+                self.insert_infects_on_contact(commands, entity, component_data)
+            }
             "colors" => self.insert_colors(commands, entity, component_data),
             "changes_colors" => self.insert_flowing_colors(commands, entity, component_data),
             "randomizes_colors" => self.insert_random_colors(commands, entity, component_data),
+            "positional_colors" => self.insert_positional_colors(commands, entity, component_data),
+            "seeded_colors" => self.insert_seeded_colors(commands, entity, component_data),
+            "surface_highlight" => self.insert_surface_highlight(commands, entity, component_data),
+            "gradient_color" => self.insert_gradient_color(commands, entity, component_data),
+            "color_by_velocity" => self.insert_color_by_velocity(commands, entity, component_data),
             "liquid" => self.insert_liquid(commands, entity, component_data),
             "movable_solid" => self.insert_movable_solid(commands, entity),
             "solid" => self.insert_solid(commands, entity),
             "gas" => self.insert_gas(commands, entity, component_data),
+            "diagonal_mover" => self.insert_diagonal_mover(commands, entity, component_data),
             "wall" => {
-                commands.entity(entity).insert(Wall);
+                commands.entity(entity).insert(WallBlueprint(Wall::new()));
+                Ok(())
             }
             "burns" => self.insert_burns(commands, entity, component_data),
             "fire" => self.insert_fire(commands, entity, component_data),
             "burning" => self.insert_burning(commands, entity, component_data),
-            _ => warn!(
-                "Erroneous config option found for particle '{}': {}",
-                particle_name, component_str
-            ),
-        }
+            "temperature" => self.insert_temperature(commands, entity, component_data),
+            "thermal_conductivity" => {
+                self.insert_thermal_conductivity(commands, entity, component_data)
+            }
+            "reaction_cooldown" => self.insert_reaction_cooldown(commands, entity, component_data),
+            "propagates" => self.insert_propagates(commands, entity, component_data),
+            "evaporates" => self.insert_evaporates(commands, entity, component_data),
+            "corrodes" => self.insert_corrodes(commands, entity, component_data),
+            _ => {
+                warn!(
+                    "Erroneous config option found for particle '{}': {}",
+                    particle_name, component_str
+                );
+                Ok(())
+            }
+        };
+        result.map_err(|reason| ParticleTypesAssetLoaderError::Component {
+            particle: particle_name.to_string(),
+            component: component_str.to_string(),
+            reason,
+        })
     }
-    fn insert_density(&self, commands: &mut Commands, entity: Entity, component_data: ron::Value) {
+
+    fn insert_density(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
         let density = component_data
             .into_rust::<u32>()
-            .expect("Config error: Expected u32 for 'density'");
-        commands.entity(entity).insert(Density(density));
+            .map_err(|err| config_error("Expected u32 for 'density'", err))?;
+        commands
+            .entity(entity)
+            .insert(DensityBlueprint(Density(density)));
+        Ok(())
     }
 
     fn insert_max_velocity(
@@ -113,117 +396,882 @@ impl ParticleTypesAsset {
         commands: &mut Commands,
         entity: Entity,
         component_data: ron::Value,
-    ) {
+    ) -> Result<(), String> {
         let max_velocity = component_data
             .into_rust::<u8>()
-            .expect("Config error: Expected u8 for 'max_velocity'");
+            .map_err(|err| config_error("Expected u8 for 'max_velocity'", err))?;
         commands
             .entity(entity)
-            .insert(Velocity::new(1, max_velocity));
+            .insert(VelocityBlueprint(Velocity::new(1, max_velocity)));
+        Ok(())
     }
 
-    fn insert_momentum(&self, commands: &mut Commands, entity: Entity, component_data: ron::Value) {
+    fn insert_momentum(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
         component_data
             .into_rust::<bool>()
-            .expect("Config error: Expected 'true' or 'false' for 'momentum'");
-        commands.entity(entity).insert(Momentum(IVec2::ZERO));
+            .map_err(|err| config_error("Expected 'true' or 'false' for 'momentum'", err))?;
+        commands
+            .entity(entity)
+            .insert(MomentumBlueprint(Momentum(IVec2::ZERO)));
+        Ok(())
     }
 
-    fn insert_colors(&self, commands: &mut Commands, entity: Entity, component_data: ron::Value) {
-        let colors: Vec<Color> = component_data
-            .into_rust::<Vec<String>>()
-            .expect("Expected array of 4 tuples holding f32 values")
-            .iter()
-            .map(|hex_str| {
-                let srgba = Srgba::hex(hex_str).unwrap();
-                Color::Srgba(srgba)
-            })
-            .collect();
+    fn insert_passes_through(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let particle_types = component_data
+            .into_rust::<HashSet<String>>()
+            .map_err(|err| config_error("Expected array of strings for 'passes_through'", err))?;
+        commands
+            .entity(entity)
+            .insert(PassesThroughBlueprint(PassesThrough::new(particle_types)));
+        Ok(())
+    }
+
+    fn insert_allow_diagonal(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let allow_diagonal = component_data
+            .into_rust::<bool>()
+            .map_err(|err| config_error("Expected 'true' or 'false' for 'allow_diagonal'", err))?;
+        commands
+            .entity(entity)
+            .insert(AllowDiagonalBlueprint(AllowDiagonal(allow_diagonal)));
+        Ok(())
+    }
+
+    fn insert_friction(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let friction = component_data
+            .into_rust::<f32>()
+            .map_err(|err| config_error("Expected f32 for 'friction'", err))?;
+        commands
+            .entity(entity)
+            .insert(FrictionBlueprint(Friction::new(friction)));
+        Ok(())
+    }
+
+    fn insert_viscosity(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let viscosity = component_data
+            .into_rust::<u8>()
+            .map_err(|err| config_error("Expected u8 for 'viscosity'", err))?;
+        commands
+            .entity(entity)
+            .insert(ViscosityBlueprint(Viscosity(viscosity)));
+        Ok(())
+    }
+
+    fn insert_bounce(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let restitution = component_data
+            .into_rust::<f32>()
+            .map_err(|err| config_error("Expected f32 for 'bounce'", err))?;
+        commands
+            .entity(entity)
+            .insert(BounceBlueprint(Bounce::new(restitution)));
+        Ok(())
+    }
+
+    fn insert_spread_budget(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let budget = component_data
+            .into_rust::<u16>()
+            .map_err(|err| config_error("Expected u16 for 'spread_budget'", err))?;
+        commands
+            .entity(entity)
+            .insert(SpreadBudgetBlueprint(SpreadBudget::new(budget)));
+        Ok(())
+    }
+
+    fn insert_wind_resistance(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let resistance = component_data
+            .into_rust::<f32>()
+            .map_err(|err| config_error("Expected f32 for 'wind_resistance'", err))?;
+        commands
+            .entity(entity)
+            .insert(WindResistanceBlueprint(WindResistance::new(resistance)));
+        Ok(())
+    }
+
+    fn insert_leaves_trail(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let trail_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'leaves_trail' component", err))?;
+
+        let mut particle = String::new();
+        let mut chance: f64 = 0.0;
+        let mut lifetime: u64 = 0;
+
+        for (trail_key, trail_value) in trail_map.iter() {
+            let trail_str = trail_key
+                .clone()
+                .into_rust::<String>()
+                .map_err(|err| config_error("Expected valid mapping for 'leaves_trail'", err))?;
+            match trail_str.as_str() {
+                "particle" => {
+                    particle = trail_value
+                        .clone()
+                        .into_rust::<String>()
+                        .map_err(|err| config_error("Expected String for 'particle'", err))?;
+                }
+                "chance" => {
+                    chance = trail_value
+                        .clone()
+                        .into_rust::<f64>()
+                        .map_err(|err| config_error("Expected f64 for 'chance'", err))?;
+                }
+                "lifetime" => {
+                    lifetime = trail_value
+                        .clone()
+                        .into_rust::<u64>()
+                        .map_err(|err| config_error("Expected u64 for 'lifetime'", err))?;
+                }
+                _ => {}
+            }
+        }
+
+        commands
+            .entity(entity)
+            .insert(LeavesTrailBlueprint(LeavesTrail::new(
+                Particle::new(&particle),
+                chance,
+                lifetime,
+            )));
+        Ok(())
+    }
+
+    fn insert_infects_on_contact(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let infects_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'infects_on_contact' component", err))?;
+
+        let mut chance: f64 = 0.0;
+        let mut excludes: HashSet<String> = HashSet::new();
+
+        for (infects_key, infects_value) in infects_map.iter() {
+            let infects_str = infects_key.clone().into_rust::<String>().map_err(|err| {
+                config_error("Expected valid mapping for 'infects_on_contact'", err)
+            })?;
+            match infects_str.as_str() {
+                "chance" => {
+                    chance = infects_value
+                        .clone()
+                        .into_rust::<f64>()
+                        .map_err(|err| config_error("Expected f64 for 'chance'", err))?;
+                }
+                "excludes" => {
+                    excludes = infects_value
+                        .clone()
+                        .into_rust::<HashSet<String>>()
+                        .map_err(|err| {
+                            config_error("Expected array of strings for 'excludes'", err)
+                        })?;
+                }
+                _ => {}
+            }
+        }
+
+        commands
+            .entity(entity)
+            .insert(InfectsOnContactBlueprint(InfectsOnContact::new(
+                chance, excludes,
+            )));
+        Ok(())
+    }
+
+    fn insert_erodes(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let erodes = self.parse_erodes(component_data)?;
+        commands.entity(entity).insert(ErodesBlueprint(erodes));
+        Ok(())
+    }
+
+    fn insert_surface_highlight(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let highlight_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'surface_highlight' component", err))?;
+
+        let mut color = Color::WHITE;
+        let mut smoothing: f32 = 0.;
+
+        for (highlight_key, highlight_value) in highlight_map.iter() {
+            let highlight_str = highlight_key.clone().into_rust::<String>().map_err(|err| {
+                config_error("Expected valid mapping for 'surface_highlight'", err)
+            })?;
+            match highlight_str.as_str() {
+                "color" => {
+                    let hex_str = highlight_value
+                        .clone()
+                        .into_rust::<String>()
+                        .map_err(|err| config_error("Expected hex string for 'color'", err))?;
+                    color = Color::Srgba(
+                        Srgba::hex(hex_str)
+                            .map_err(|err| config_error("Invalid hex string for 'color'", err))?,
+                    );
+                }
+                "smoothing" => {
+                    smoothing = highlight_value
+                        .clone()
+                        .into_rust::<f32>()
+                        .map_err(|err| config_error("Expected f32 for 'smoothing'", err))?;
+                }
+                _ => {}
+            }
+        }
+
+        commands
+            .entity(entity)
+            .insert(SurfaceHighlightBlueprint(SurfaceHighlight::new(
+                color, smoothing,
+            )));
+        Ok(())
+    }
+
+    fn insert_gradient_color(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let gradient_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'gradient_color' component", err))?;
+
+        let mut source: Option<String> = None;
+        let mut min: f32 = 0.;
+        let mut max: f32 = 1.;
+
+        for (gradient_key, gradient_value) in gradient_map.iter() {
+            let gradient_str = gradient_key
+                .clone()
+                .into_rust::<String>()
+                .map_err(|err| config_error("Expected valid mapping for 'gradient_color'", err))?;
+            match gradient_str.as_str() {
+                "source" => {
+                    source = Some(
+                        gradient_value
+                            .clone()
+                            .into_rust::<String>()
+                            .map_err(|err| config_error("Expected string for 'source'", err))?,
+                    );
+                }
+                "min" => {
+                    min = gradient_value
+                        .clone()
+                        .into_rust::<f32>()
+                        .map_err(|err| config_error("Expected f32 for 'min'", err))?;
+                }
+                "max" => {
+                    max = gradient_value
+                        .clone()
+                        .into_rust::<f32>()
+                        .map_err(|err| config_error("Expected f32 for 'max'", err))?;
+                }
+                _ => {}
+            }
+        }
+
+        let source = match source.as_deref() {
+            Some("velocity") => ColorSource::Velocity,
+            Some("temperature") => ColorSource::Temperature { min, max },
+            Some(other) => {
+                return Err(format!(
+                    "Unknown gradient source '{}', expected 'velocity' or 'temperature'",
+                    other
+                ))
+            }
+            None => return Err("Missing 'source' for 'gradient_color'".to_string()),
+        };
+        commands
+            .entity(entity)
+            .insert(GradientColorBlueprint(GradientColor::new(source)));
+        Ok(())
+    }
+
+    fn insert_color_by_velocity(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let velocity_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'color_by_velocity' component", err))?;
+
+        let mut gradient: Option<ParticleColor> = None;
+        let mut max_speed: f32 = 1.;
+
+        for (velocity_key, velocity_value) in velocity_map.iter() {
+            let velocity_str = velocity_key.clone().into_rust::<String>().map_err(|err| {
+                config_error("Expected valid mapping for 'color_by_velocity'", err)
+            })?;
+            match velocity_str.as_str() {
+                "colors" => gradient = Some(self.parse_colors(velocity_value.clone())?),
+                "max_speed" => {
+                    max_speed = velocity_value
+                        .clone()
+                        .into_rust::<f32>()
+                        .map_err(|err| config_error("Expected f32 for 'max_speed'", err))?;
+                }
+                _ => {}
+            }
+        }
+
+        let gradient =
+            gradient.ok_or_else(|| "Missing 'colors' for 'color_by_velocity'".to_string())?;
+        commands
+            .entity(entity)
+            .insert(ColorByVelocityBlueprint(ColorByVelocity::new(
+                gradient, max_speed,
+            )));
+        Ok(())
+    }
+
+    fn insert_colors(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let colors = self.parse_colors(component_data)?;
+        commands
+            .entity(entity)
+            .insert(ParticleColorBlueprint(colors));
+        Ok(())
+    }
+
+    fn insert_liquid(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        // Either a bare fluidity, or a map with 'fluidity', 'min_neighbors_to_spread' and
+        // 'stack_bias'.
+        if let Ok(fluidity) = component_data.clone().into_rust::<usize>() {
+            commands
+                .entity(entity)
+                .insert(LiquidBlueprint(Liquid::new(fluidity)));
+            return Ok(());
+        }
+
+        let liquid_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected usize or map for 'liquid'", err))?;
+
+        let mut liquid = Liquid::default();
+        for (liquid_key, liquid_value) in liquid_map.iter() {
+            let liquid_str = liquid_key
+                .clone()
+                .into_rust::<String>()
+                .map_err(|err| config_error("Expected valid mapping for 'liquid'", err))?;
+            match liquid_str.as_str() {
+                "fluidity" => {
+                    liquid.fluidity = liquid_value
+                        .clone()
+                        .into_rust::<usize>()
+                        .map_err(|err| config_error("Expected usize for 'fluidity'", err))?;
+                }
+                "min_neighbors_to_spread" => {
+                    let min_neighbors_to_spread =
+                        liquid_value.clone().into_rust::<u8>().map_err(|err| {
+                            config_error("Expected u8 for 'min_neighbors_to_spread'", err)
+                        })?;
+                    liquid = liquid.with_min_neighbors_to_spread(min_neighbors_to_spread);
+                }
+                "stack_bias" => {
+                    let stack_bias = liquid_value
+                        .clone()
+                        .into_rust::<f32>()
+                        .map_err(|err| config_error("Expected f32 for 'stack_bias'", err))?;
+                    liquid = liquid.with_stack_bias(stack_bias);
+                }
+                _ => {}
+            }
+        }
+
+        commands.entity(entity).insert(LiquidBlueprint(liquid));
+        Ok(())
+    }
+
+    fn insert_movable_solid(&self, commands: &mut Commands, entity: Entity) -> Result<(), String> {
+        commands
+            .entity(entity)
+            .insert(MovableSolidBlueprint(MovableSolid::new()));
+        Ok(())
+    }
+
+    fn insert_solid(&self, commands: &mut Commands, entity: Entity) -> Result<(), String> {
+        commands.entity(entity).insert(SolidBlueprint(Solid::new()));
+        Ok(())
+    }
+
+    fn insert_diagonal_mover(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        if component_data
+            .into_rust::<bool>()
+            .map_err(|err| config_error("Expected 'true' or 'false' for 'diagonal_mover'", err))?
+        {
+            commands
+                .entity(entity)
+                .insert(DiagonalMoverBlueprint(DiagonalMover::new()));
+        }
+        Ok(())
+    }
+
+    fn insert_gas(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let fluidity = component_data
+            .into_rust::<usize>()
+            .map_err(|err| config_error("Expected usize for 'gas'", err))?;
+        commands
+            .entity(entity)
+            .insert(GasBlueprint(Gas::new(fluidity)));
+        Ok(())
+    }
+
+    fn insert_burns(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let (
+            duration,
+            tick_rate,
+            chance_destroy_per_tick,
+            reaction,
+            burning_colors,
+            spreads,
+            heat_output,
+            emits,
+            ignition_temperature,
+        ) = self.parse_burns(component_data)?;
+        let burns = Burns::new(
+            duration,
+            tick_rate,
+            chance_destroy_per_tick,
+            reaction,
+            burning_colors,
+            spreads,
+        )
+        .with_heat_output(heat_output);
+        let burns = match emits {
+            Some(emits) => burns.with_emits(emits),
+            None => burns,
+        };
+        let burns = match ignition_temperature {
+            Some(ignition_temperature) => burns.with_ignition_temperature(ignition_temperature),
+            None => burns,
+        };
+        commands.entity(entity).insert(BurnsBlueprint(burns));
+        Ok(())
+    }
+
+    fn insert_reaction_cooldown(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let ticks = component_data
+            .into_rust::<u32>()
+            .map_err(|err| config_error("Expected u32 for 'reaction_cooldown'", err))?;
+        commands
+            .entity(entity)
+            .insert(ReactionCooldownBlueprint(ReactionCooldown::new(ticks)));
+        Ok(())
+    }
+
+    fn insert_propagates(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let propagates_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'propagates'", err))?;
+
+        let mut along: Option<String> = None;
+        let mut speed_ticks: u32 = 0;
+        let mut triggers: Option<String> = None;
+
+        for (propagates_key, propagates_value) in propagates_map.iter() {
+            let propagates_str = propagates_key
+                .clone()
+                .into_rust::<String>()
+                .map_err(|err| config_error("Expected valid mapping for 'propagates'", err))?;
+            match propagates_str.as_str() {
+                "along" => {
+                    along = Some(
+                        propagates_value
+                            .clone()
+                            .into_rust::<String>()
+                            .map_err(|err| config_error("Expected String for 'along'", err))?,
+                    );
+                }
+                "speed_ticks" => {
+                    speed_ticks = propagates_value
+                        .clone()
+                        .into_rust::<u32>()
+                        .map_err(|err| config_error("Expected u32 for 'speed_ticks'", err))?;
+                }
+                "triggers" => {
+                    triggers = Some(
+                        propagates_value
+                            .clone()
+                            .into_rust::<String>()
+                            .map_err(|err| config_error("Expected String for 'triggers'", err))?,
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        let along = along.ok_or_else(|| "'propagates' requires 'along'".to_string())?;
+        let triggers = triggers.ok_or_else(|| "'propagates' requires 'triggers'".to_string())?;
+        let propagates = Propagates::new(&along, speed_ticks, Particle::new(&triggers));
+        commands
+            .entity(entity)
+            .insert(PropagatesBlueprint(propagates));
+        Ok(())
+    }
+
+    fn insert_evaporates(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let evaporates_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'evaporates'", err))?;
+
+        let mut chance_per_tick: f64 = 0.;
+        let mut produces: Option<String> = None;
+        let mut requires_heat = false;
+
+        for (evaporates_key, evaporates_value) in evaporates_map.iter() {
+            let evaporates_str = evaporates_key
+                .clone()
+                .into_rust::<String>()
+                .map_err(|err| config_error("Expected valid mapping for 'evaporates'", err))?;
+            match evaporates_str.as_str() {
+                "chance_per_tick" => {
+                    chance_per_tick = evaporates_value
+                        .clone()
+                        .into_rust::<f64>()
+                        .map_err(|err| config_error("Expected f64 for 'chance_per_tick'", err))?;
+                }
+                "produces" => {
+                    produces = Some(
+                        evaporates_value
+                            .clone()
+                            .into_rust::<String>()
+                            .map_err(|err| config_error("Expected String for 'produces'", err))?,
+                    );
+                }
+                "requires_heat" => {
+                    requires_heat = evaporates_value
+                        .clone()
+                        .into_rust::<bool>()
+                        .map_err(|err| config_error("Expected bool for 'requires_heat'", err))?;
+                }
+                _ => {}
+            }
+        }
+
+        let produces = produces.ok_or_else(|| "'evaporates' requires 'produces'".to_string())?;
+        commands
+            .entity(entity)
+            .insert(EvaporatesBlueprint(Evaporates::new(
+                chance_per_tick,
+                Particle::new(&produces),
+                requires_heat,
+            )));
+        Ok(())
+    }
+
+    fn insert_corrodes(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let corrodes_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'corrodes'", err))?;
+
+        let mut chance_per_tick: f64 = 0.;
+        let mut consumes_self_chance: f64 = 0.;
+        let mut targets = CorrosionMask::default();
+
+        for (corrodes_key, corrodes_value) in corrodes_map.iter() {
+            let corrodes_str = corrodes_key
+                .clone()
+                .into_rust::<String>()
+                .map_err(|err| config_error("Expected valid mapping for 'corrodes'", err))?;
+            match corrodes_str.as_str() {
+                "chance_per_tick" => {
+                    chance_per_tick = corrodes_value
+                        .clone()
+                        .into_rust::<f64>()
+                        .map_err(|err| config_error("Expected f64 for 'chance_per_tick'", err))?;
+                }
+                "consumes_self_chance" => {
+                    consumes_self_chance =
+                        corrodes_value.clone().into_rust::<f64>().map_err(|err| {
+                            config_error("Expected f64 for 'consumes_self_chance'", err)
+                        })?;
+                }
+                "targets" => {
+                    let target_names = corrodes_value
+                        .clone()
+                        .into_rust::<Vec<String>>()
+                        .map_err(|err| config_error("Expected list for 'targets'", err))?;
+                    for target in target_names {
+                        match target.as_str() {
+                            "wall" => targets.walls = true,
+                            "solid" => targets.solids = true,
+                            "movable_solid" => targets.movable_solids = true,
+                            _ => {
+                                return Err(format!(
+                                    "Unknown corrosion target '{}', expected 'wall', 'solid' or \
+                                     'movable_solid'",
+                                    target
+                                ));
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
         commands
             .entity(entity)
-            .insert(ParticleColor::new(*colors.get(0).unwrap(), colors));
-    }
-
-    fn insert_liquid(&self, commands: &mut Commands, entity: Entity, component_data: ron::Value) {
-        let fluidity = component_data
-            .into_rust::<usize>()
-            .expect("Config error: Expected usize for 'liquid'");
-        commands.entity(entity).insert(Liquid::new(fluidity));
+            .insert(CorrodesBlueprint(Corrodes::new(
+                chance_per_tick,
+                consumes_self_chance,
+                targets,
+            )));
+        Ok(())
     }
 
-    fn insert_movable_solid(&self, commands: &mut Commands, entity: Entity) {
-        commands.entity(entity).insert(MovableSolid::new());
+    fn insert_temperature(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let temperature = component_data
+            .into_rust::<f32>()
+            .map_err(|err| config_error("Expected f32 for 'temperature'", err))?;
+        commands
+            .entity(entity)
+            .insert(TemperatureBlueprint(Temperature(temperature)));
+        Ok(())
     }
 
-    fn insert_solid(&self, commands: &mut Commands, entity: Entity) {
-        commands.entity(entity).insert(Solid::new());
+    fn insert_thermal_conductivity(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let conductivity = component_data
+            .into_rust::<f32>()
+            .map_err(|err| config_error("Expected f32 for 'thermal_conductivity'", err))?;
+        commands
+            .entity(entity)
+            .insert(ThermalConductivityBlueprint(ThermalConductivity(
+                conductivity,
+            )));
+        Ok(())
     }
 
-    fn insert_gas(&self, commands: &mut Commands, entity: Entity, component_data: ron::Value) {
-        let fluidity = component_data
-            .into_rust::<usize>()
-            .expect("Config error: Expected usize for 'gas'");
-        commands.entity(entity).insert(Gas::new(fluidity));
+    fn insert_fire(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let fire = self.parse_fire(component_data)?;
+        commands.entity(entity).insert(FireBlueprint(fire));
+        Ok(())
     }
 
-    fn insert_burns(&self, commands: &mut Commands, entity: Entity, component_data: ron::Value) {
-        let (duration, tick_rate, chance_destroy_per_tick, reaction, burning_colors, spreads) =
-            self.parse_burns(component_data);
-        let burns = Burns::new(
-            duration,
-            tick_rate,
-            chance_destroy_per_tick,
-            reaction,
-            burning_colors,
-            spreads,
-        );
-        commands.entity(entity).insert(burns);
+    fn insert_burning(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let burning = self.parse_burning(component_data)?;
+        commands.entity(entity).insert(BurningBlueprint(burning));
+        Ok(())
     }
 
-    fn insert_fire(&self, commands: &mut Commands, entity: Entity, component_data: ron::Value) {
-        let fire = self.parse_fire(component_data);
-        commands.entity(entity).insert(fire);
+    fn insert_flowing_colors(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let chance = component_data
+            .into_rust::<f64>()
+            .map_err(|err| config_error("Expected f64 for 'changes_colors'", err))?;
+        commands
+            .entity(entity)
+            .insert(FlowsColorBlueprint(FlowsColor::new(chance)));
+        Ok(())
     }
 
-    fn insert_burning(&self, commands: &mut Commands, entity: Entity, component_data: ron::Value) {
-        let burning = self.parse_burning(component_data);
-        commands.entity(entity).insert(burning);
+    fn insert_random_colors(
+        &self,
+        commands: &mut Commands,
+        entity: Entity,
+        component_data: ron::Value,
+    ) -> Result<(), String> {
+        let chance = component_data
+            .into_rust::<f64>()
+            .map_err(|err| config_error("Expected f64 for 'randomizes_colors'", err))?;
+        commands
+            .entity(entity)
+            .insert(RandomizesColorBlueprint(RandomizesColor::new(chance)));
+        Ok(())
     }
 
-    fn insert_flowing_colors(
+    fn insert_positional_colors(
         &self,
         commands: &mut Commands,
         entity: Entity,
         component_data: ron::Value,
-    ) {
-        let chance = component_data.into_rust::<f64>().unwrap();
-        commands.entity(entity).insert(FlowsColor::new(chance));
+    ) -> Result<(), String> {
+        let scale = component_data
+            .into_rust::<f32>()
+            .map_err(|err| config_error("Expected f32 for 'positional_colors'", err))?;
+        commands
+            .entity(entity)
+            .insert(PositionalColorBlueprint(PositionalColor::new(scale)));
+        Ok(())
     }
 
-    fn insert_random_colors(
+    fn insert_seeded_colors(
         &self,
         commands: &mut Commands,
         entity: Entity,
         component_data: ron::Value,
-    ) {
-        let chance = component_data.into_rust::<f64>().unwrap();
-        commands.entity(entity).insert(RandomizesColor::new(chance));
+    ) -> Result<(), String> {
+        if component_data
+            .into_rust::<bool>()
+            .map_err(|err| config_error("Expected 'true' or 'false' for 'seeded_colors'", err))?
+        {
+            commands
+                .entity(entity)
+                .insert(SeededColorBlueprint(SeededColor));
+        }
+        Ok(())
+    }
+
+    fn parse_colors(&self, component_data: ron::Value) -> Result<ParticleColor, String> {
+        let colors = component_data
+            .into_rust::<Vec<String>>()
+            .map_err(|err| config_error("Expected array of hex color strings", err))?
+            .iter()
+            .map(|hex_str| {
+                Srgba::hex(hex_str)
+                    .map(Color::Srgba)
+                    .map_err(|err| config_error("Invalid hex string for color", err))
+            })
+            .collect::<Result<Vec<Color>, String>>()?;
+        let first = *colors
+            .first()
+            .ok_or_else(|| "Expected at least one color".to_string())?;
+        Ok(ParticleColor::new(first, colors))
     }
 
     fn parse_burns(
         &self,
         component_data: ron::Value,
-    ) -> (
-        Duration,
-        Duration,
-        Option<f64>,
-        Option<Reacting>,
-        Option<ParticleColor>,
-        Option<Fire>,
-    ) {
+    ) -> Result<
+        (
+            Duration,
+            Duration,
+            Option<f64>,
+            Option<Reacting>,
+            Option<ParticleColor>,
+            Option<Fire>,
+            f32,
+            Option<EmitsWhileBurning>,
+            Option<f32>,
+        ),
+        String,
+    > {
         let burn_map = component_data
             .into_rust::<ron::Map>()
-            .expect("Config error: Expected map for 'burns' component");
+            .map_err(|err| config_error("Expected map for 'burns' component", err))?;
 
         let mut duration: Duration = Duration::from_millis(0);
         let mut tick_rate: Duration = Duration::from_millis(0);
@@ -231,19 +1279,22 @@ impl ParticleTypesAsset {
         let mut reaction: Option<Reacting> = None;
         let mut burning_colors: Option<ParticleColor> = None;
         let mut spreads: Option<Fire> = None;
+        let mut heat_output: f32 = 0.;
+        let mut emits: Option<EmitsWhileBurning> = None;
+        let mut ignition_temperature: Option<f32> = None;
 
         for (burn_key, burn_value) in burn_map.iter() {
             let burn_str = burn_key
                 .clone()
                 .into_rust::<String>()
-                .expect("Config error: Expected valid mapping for 'burns'");
+                .map_err(|err| config_error("Expected valid mapping for 'burns'", err))?;
             match burn_str.as_str() {
                 "duration" => {
                     duration = Duration::from_millis(
                         burn_value
                             .clone()
                             .into_rust::<u64>()
-                            .expect("Config error: Expected u64 for 'duration'"),
+                            .map_err(|err| config_error("Expected u64 for 'duration'", err))?,
                     );
                 }
                 "tick_rate" => {
@@ -251,54 +1302,119 @@ impl ParticleTypesAsset {
                         burn_value
                             .clone()
                             .into_rust::<u64>()
-                            .expect("Config error: Expected u64 for 'tick_rate'"),
+                            .map_err(|err| config_error("Expected u64 for 'tick_rate'", err))?,
                     );
                 }
                 "chance_destroy_per_tick" => {
-                    chance_destroy_per_tick = Some(
-                        burn_value
-                            .clone()
-                            .into_rust::<f64>()
-                            .expect("Config error: Expected f64 for 'chance_destroy_per_tick'"),
-                    );
+                    chance_destroy_per_tick =
+                        Some(burn_value.clone().into_rust::<f64>().map_err(|err| {
+                            config_error("Expected f64 for 'chance_destroy_per_tick'", err)
+                        })?);
                 }
                 "reaction" => {
-                    reaction = Some(self.parse_reaction(burn_value.clone()));
+                    reaction = Some(self.parse_reaction(burn_value.clone())?);
                 }
                 "colors" => {
-                    let colors: Vec<Color> = burn_value
-                        .clone()
-                        .into_rust::<Vec<String>>()
-                        .expect("Expected array of 4 tuples holding f32 values")
-                        .iter()
-                        .map(|hex_str| {
-                            let srgba = Srgba::hex(hex_str).unwrap();
-                            Color::Srgba(srgba)
-                        })
-                        .collect();
-                    burning_colors = Some(ParticleColor::new(*colors.get(0).unwrap(), colors));
+                    burning_colors = Some(self.parse_colors(burn_value.clone())?);
                 }
                 "spreads" => {
-                    spreads = Some(self.parse_fire(burn_value.clone()));
+                    spreads = Some(self.parse_fire(burn_value.clone())?);
+                }
+                "heat_output" => {
+                    heat_output = burn_value
+                        .clone()
+                        .into_rust::<f32>()
+                        .map_err(|err| config_error("Expected f32 for 'heat_output'", err))?;
+                }
+                "emits" => {
+                    emits = Some(self.parse_emits(burn_value.clone())?);
+                }
+                "ignition_temperature" => {
+                    ignition_temperature =
+                        Some(burn_value.clone().into_rust::<f32>().map_err(|err| {
+                            config_error("Expected f32 for 'ignition_temperature'", err)
+                        })?);
                 }
                 _ => {}
             }
         }
 
-        (
+        Ok((
             duration,
             tick_rate,
             chance_destroy_per_tick,
             reaction,
             burning_colors,
             spreads,
-        )
+            heat_output,
+            emits,
+            ignition_temperature,
+        ))
+    }
+
+    fn parse_emits(&self, component_data: ron::Value) -> Result<EmitsWhileBurning, String> {
+        let emits_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'emits'", err))?;
+
+        let mut particle = String::new();
+        let mut rate: f32 = 0.;
+        let mut at = String::from("above");
+        let mut radius: i32 = 1;
+
+        for (emits_key, emits_value) in emits_map.iter() {
+            let emits_str = emits_key
+                .clone()
+                .into_rust::<String>()
+                .map_err(|err| config_error("Expected valid mapping for 'emits'", err))?;
+            match emits_str.as_str() {
+                "particle" => {
+                    particle = emits_value
+                        .clone()
+                        .into_rust::<String>()
+                        .map_err(|err| config_error("Expected String for 'particle'", err))?;
+                }
+                "rate" => {
+                    rate = emits_value
+                        .clone()
+                        .into_rust::<f32>()
+                        .map_err(|err| config_error("Expected f32 for 'rate'", err))?;
+                }
+                "at" => {
+                    at = emits_value
+                        .clone()
+                        .into_rust::<String>()
+                        .map_err(|err| config_error("Expected String for 'at'", err))?;
+                }
+                "radius" => {
+                    radius = emits_value
+                        .clone()
+                        .into_rust::<i32>()
+                        .map_err(|err| config_error("Expected i32 for 'radius'", err))?;
+                }
+                _ => {}
+            }
+        }
+
+        let at = match at.as_str() {
+            "above" => ProductPlacement::Above,
+            "below" => ProductPlacement::Below,
+            "nearest_empty" => ProductPlacement::NearestEmpty(radius),
+            _ => {
+                return Err(format!(
+                    "Expected 'above', 'below' or 'nearest_empty' for 'at', found '{}'",
+                    at
+                ))
+            }
+        };
+
+        Ok(EmitsWhileBurning::new(Particle::new(&particle), rate, at))
     }
 
-    fn parse_reaction(&self, reaction_value: ron::Value) -> Reacting {
+    fn parse_reaction(&self, reaction_value: ron::Value) -> Result<Reacting, String> {
         let reaction_map = reaction_value
             .into_rust::<ron::Map>()
-            .expect("Config error: Expected map for 'reaction' component");
+            .map_err(|err| config_error("Expected map for 'reaction' component", err))?;
 
         let mut produces = String::new();
         let mut chance_to_produce: f64 = 0.0;
@@ -307,75 +1423,162 @@ impl ParticleTypesAsset {
             let reaction_str = reaction_key
                 .clone()
                 .into_rust::<String>()
-                .expect("Config error: Expected valid mapping for 'reaction'");
+                .map_err(|err| config_error("Expected valid mapping for 'reaction'", err))?;
             match reaction_str.as_str() {
                 "produces" => {
                     produces = reaction_value
                         .clone()
                         .into_rust::<String>()
-                        .expect("Config error: Expected String for 'produces'");
+                        .map_err(|err| config_error("Expected String for 'produces'", err))?;
                 }
                 "chance_to_produce" => {
                     chance_to_produce = reaction_value
                         .clone()
                         .into_rust::<f64>()
-                        .expect("Config error: Expected f64 for 'chance_to_produce'");
+                        .map_err(|err| config_error("Expected f64 for 'chance_to_produce'", err))?;
                 }
                 _ => {}
             }
         }
 
-        Reacting::new(Particle::new(&produces), chance_to_produce)
+        Ok(Reacting::new(Particle::new(&produces), chance_to_produce))
     }
 
-    fn parse_fire(&self, component_data: ron::Value) -> Fire {
+    fn parse_fire(&self, component_data: ron::Value) -> Result<Fire, String> {
         let fire_map = component_data
             .into_rust::<ron::Map>()
-            .expect("Config error: Expected map for 'fire' component");
+            .map_err(|err| config_error("Expected map for 'fire' component", err))?;
 
         let mut burn_radius: f32 = 0.0;
         let mut chance_to_spread: f64 = 0.0;
-        let mut destroys_on_spread = false;
+        let mut on_spread = SpreadEffect::Keep;
 
         for (fire_key, fire_value) in fire_map.iter() {
             let fire_str = fire_key
                 .clone()
                 .into_rust::<String>()
-                .expect("Config error: Expected valid mapping for 'fire'");
+                .map_err(|err| config_error("Expected valid mapping for 'fire'", err))?;
             match fire_str.as_str() {
                 "burn_radius" => {
                     burn_radius = fire_value
                         .clone()
                         .into_rust::<f32>()
-                        .expect("Config error: Expected f32 for 'burn_radius'");
+                        .map_err(|err| config_error("Expected f32 for 'burn_radius'", err))?;
                 }
                 "chance_to_spread" => {
                     chance_to_spread = fire_value
                         .clone()
                         .into_rust::<f64>()
-                        .expect("Config error: Expected f64 for 'chance_to_spread'");
+                        .map_err(|err| config_error("Expected f64 for 'chance_to_spread'", err))?;
+                }
+                "on_spread" => {
+                    on_spread = self.parse_spread_effect(fire_value.clone())?;
                 }
                 "destroys_on_spread" => {
-                    destroys_on_spread = fire_value
-                        .clone()
-                        .into_rust::<bool>()
-                        .expect("Config error: Expected bool for 'destroys_on_spread'");
+                    warn!(
+                        "Config option 'destroys_on_spread' is deprecated, use 'on_spread' instead"
+                    );
+                    on_spread =
+                        SpreadEffect::from(fire_value.clone().into_rust::<bool>().map_err(
+                            |err| config_error("Expected bool for 'destroys_on_spread'", err),
+                        )?);
                 }
                 _ => {}
             }
         }
 
-        Fire {
+        Ok(Fire {
             burn_radius,
             chance_to_spread,
-            destroys_on_spread,
+            on_spread,
+        })
+    }
+
+    /// Parses `"keep"`, `"destroy"` or `{ "convert_to": "<particle>" }`.
+    fn parse_spread_effect(&self, component_data: ron::Value) -> Result<SpreadEffect, String> {
+        const EXPECTED: &str =
+            "Expected 'keep', 'destroy' or a map with 'convert_to' for 'on_spread'";
+
+        if let Ok(effect) = component_data.clone().into_rust::<String>() {
+            return match effect.as_str() {
+                "keep" => Ok(SpreadEffect::Keep),
+                "destroy" => Ok(SpreadEffect::Destroy),
+                other => Err(format!("{EXPECTED}, got '{other}'")),
+            };
+        }
+
+        let effect_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error(EXPECTED, err))?;
+        let mut convert_to = None;
+        for (effect_key, effect_value) in effect_map.iter() {
+            let effect_str = effect_key
+                .clone()
+                .into_rust::<String>()
+                .map_err(|err| config_error(EXPECTED, err))?;
+            match effect_str.as_str() {
+                "convert_to" => {
+                    convert_to =
+                        Some(effect_value.clone().into_rust::<String>().map_err(|err| {
+                            config_error("Expected String for 'convert_to'", err)
+                        })?);
+                }
+                other => return Err(format!("{EXPECTED}, got key '{other}'")),
+            }
+        }
+        convert_to
+            .map(|particle| SpreadEffect::ConvertTo(Particle::new(&particle)))
+            .ok_or_else(|| EXPECTED.to_string())
+    }
+
+    fn parse_erodes(&self, component_data: ron::Value) -> Result<Erodes, String> {
+        let erodes_map = component_data
+            .into_rust::<ron::Map>()
+            .map_err(|err| config_error("Expected map for 'erodes' component", err))?;
+
+        let mut carries = String::new();
+        let mut threshold_velocity: u8 = 1;
+        let mut chance: f64 = 0.0;
+
+        for (erodes_key, erodes_value) in erodes_map.iter() {
+            let erodes_str = erodes_key
+                .clone()
+                .into_rust::<String>()
+                .map_err(|err| config_error("Expected valid mapping for 'erodes'", err))?;
+            match erodes_str.as_str() {
+                "carries" => {
+                    carries = erodes_value
+                        .clone()
+                        .into_rust::<String>()
+                        .map_err(|err| config_error("Expected String for 'carries'", err))?;
+                }
+                "threshold_velocity" => {
+                    threshold_velocity = erodes_value
+                        .clone()
+                        .into_rust::<u8>()
+                        .map_err(|err| config_error("Expected u8 for 'threshold_velocity'", err))?;
+                }
+                "chance" => {
+                    chance = erodes_value
+                        .clone()
+                        .into_rust::<f64>()
+                        .map_err(|err| config_error("Expected f64 for 'chance'", err))?;
+                }
+                _ => {}
+            }
         }
+
+        Ok(Erodes::new(
+            Particle::new(&carries),
+            threshold_velocity,
+            chance,
+        ))
     }
 
-    fn parse_burning(&self, component_data: ron::Value) -> Burning {
+    fn parse_burning(&self, component_data: ron::Value) -> Result<Burning, String> {
         let burning_map = component_data
             .into_rust::<ron::Map>()
-            .expect("Config error: Expected map for 'burning' component");
+            .map_err(|err| config_error("Expected map for 'burning' component", err))?;
 
         let mut duration: Duration = Duration::from_millis(0);
         let mut tick_rate: Duration = Duration::from_millis(0);
@@ -384,14 +1587,14 @@ impl ParticleTypesAsset {
             let burn_str = burn_key
                 .clone()
                 .into_rust::<String>()
-                .expect("Config error: Expected valid mapping for 'burning'");
+                .map_err(|err| config_error("Expected valid mapping for 'burning'", err))?;
             match burn_str.as_str() {
                 "duration" => {
                     duration = Duration::from_millis(
                         burn_value
                             .clone()
                             .into_rust::<u64>()
-                            .expect("Config error: Expected u64 for 'duration'"),
+                            .map_err(|err| config_error("Expected u64 for 'duration'", err))?,
                     );
                 }
                 "tick_rate" => {
@@ -399,14 +1602,14 @@ impl ParticleTypesAsset {
                         burn_value
                             .clone()
                             .into_rust::<u64>()
-                            .expect("Config error: Expected u64 for 'tick_rate'"),
+                            .map_err(|err| config_error("Expected u64 for 'tick_rate'", err))?,
                     );
                 }
                 _ => {}
             }
         }
 
-        Burning::new(duration, tick_rate)
+        Ok(Burning::new(duration, tick_rate))
     }
 }
 
@@ -420,6 +1623,17 @@ pub enum ParticleTypesAssetLoaderError {
     Io(#[from] std::io::Error),
     #[error("Could not parse RON: {0}")]
     RonSpannedError(#[from] ron::error::SpannedError),
+    #[error("Invalid '{component}' for particle '{particle}': {reason}")]
+    Component {
+        particle: String,
+        component: String,
+        reason: String,
+    },
+}
+
+/// Describes a value in a particle types file that couldn't be read as `expected`.
+fn config_error(expected: &str, err: impl fmt::Display) -> String {
+    format!("{} ({})", expected, err)
 }
 
 impl AssetLoader for ParticleTypesAssetLoader {
@@ -444,3 +1658,485 @@ impl AssetLoader for ParticleTypesAssetLoader {
         &["custom"]
     }
 }
+
+/// A set of named [`ParticleStamp`]s, e.g.:
+///
+/// ```ron
+/// {
+///     "Boulder": (cells: [((0, 0), (name: "Rock")), ((1, 0), (name: "Rock"))]),
+/// }
+/// ```
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct ParticleStampsAsset {
+    pub stamps: HashMap<String, ParticleStamp>,
+}
+
+impl ParticleStampsAsset {
+    pub fn load_stamps(&self, stamps: &mut ParticleStamps) {
+        for (name, stamp) in self.stamps.iter() {
+            stamps.insert(name.clone(), stamp.clone());
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ParticleStampsAssetLoader;
+
+impl AssetLoader for ParticleStampsAssetLoader {
+    type Asset = ParticleStampsAsset;
+    type Settings = ();
+    type Error = ParticleTypesAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let stamps = ron::de::from_bytes::<HashMap<String, ParticleStamp>>(&bytes)?;
+        Ok(ParticleStampsAsset { stamps })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["stamps"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy_turborand::{GlobalRng, RngPlugin};
+    use bfs_core::testing::{positions, spawn, step};
+    use bfs_spatial::FallingSandSpatialPlugin;
+
+    fn load(source: &str) -> World {
+        let (world, result) = try_load(source);
+        result.unwrap();
+        world
+    }
+
+    fn try_load(source: &str) -> (World, Result<(), ParticleTypesAssetLoaderError>) {
+        let mut world = World::new();
+        world.init_resource::<ParticleTypeMap>();
+        let asset = ParticleTypesAsset {
+            particle_types: ron::from_str(source).unwrap(),
+        };
+        let result = world
+            .run_system_once(
+                move |mut commands: Commands, mut type_map: ResMut<ParticleTypeMap>| {
+                    asset.load_particle_types(&mut commands, &mut type_map)
+                },
+            )
+            .unwrap();
+        (world, result)
+    }
+
+    fn particle_type(world: &World, name: &str) -> Entity {
+        *world
+            .resource::<ParticleTypeMap>()
+            .get(&name.to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn diagonal_mover_inserts_blueprint() {
+        let world = load(r#"{ "Slider": { "density": 1000, "diagonal_mover": true } }"#);
+        let entity = particle_type(&world, "Slider");
+
+        assert!(world.get::<DiagonalMoverBlueprint>(entity).is_some());
+        assert!(world.get::<DiagonalMover>(entity).is_none());
+    }
+
+    #[test]
+    fn liquid_stack_bias_is_parsed() {
+        let world = load(
+            r#"{
+                "Water": { "density": 750, "liquid": 5 },
+                "Syrup": { "density": 750, "liquid": { "fluidity": 3, "stack_bias": 0.25 } },
+                "Tar": { "density": 750, "liquid": { "fluidity": 1, "stack_bias": 4.0 } },
+            }"#,
+        );
+        let liquid = |name| world.get::<LiquidBlueprint>(particle_type(&world, name));
+
+        assert_eq!(liquid("Water"), Some(&LiquidBlueprint(Liquid::new(5))));
+        assert_eq!(
+            liquid("Syrup"),
+            Some(&LiquidBlueprint(Liquid::new(3).with_stack_bias(0.25)))
+        );
+        assert_eq!(liquid("Syrup").unwrap().0.stack_bias, 25);
+        assert_eq!(liquid("Tar").unwrap().0.stack_bias, 100);
+    }
+
+    #[test]
+    fn liquid_min_neighbors_to_spread_is_parsed() {
+        let world = load(
+            r#"{ "Mercury": { "density": 1350, "liquid": { "fluidity": 2, "min_neighbors_to_spread": 3 } } }"#,
+        );
+        let entity = particle_type(&world, "Mercury");
+
+        assert_eq!(
+            world.get::<LiquidBlueprint>(entity),
+            Some(&LiquidBlueprint(
+                Liquid::new(2).with_min_neighbors_to_spread(3)
+            ))
+        );
+    }
+
+    #[test]
+    fn fire_on_spread_effects_are_parsed() {
+        let world = load(
+            r#"{
+                "Spark": { "fire": { "burn_radius": 1.5, "chance_to_spread": 0.5, "on_spread": "keep" } },
+                "Flash": { "fire": { "burn_radius": 1.5, "chance_to_spread": 0.5, "on_spread": "destroy" } },
+                "Ember": { "fire": { "burn_radius": 1.5, "chance_to_spread": 0.5, "on_spread": { "convert_to": "Ash" } } },
+                "Flame": { "fire": { "burn_radius": 1.5, "chance_to_spread": 0.5, "destroys_on_spread": true } },
+            }"#,
+        );
+        let on_spread = |name| {
+            world
+                .get::<FireBlueprint>(particle_type(&world, name))
+                .unwrap()
+                .0
+                .on_spread
+                .clone()
+        };
+
+        assert_eq!(on_spread("Spark"), SpreadEffect::Keep);
+        assert_eq!(on_spread("Flash"), SpreadEffect::Destroy);
+        assert_eq!(
+            on_spread("Ember"),
+            SpreadEffect::ConvertTo(Particle::new("Ash"))
+        );
+        assert_eq!(on_spread("Flame"), SpreadEffect::Destroy);
+    }
+
+    #[test]
+    fn malformed_particle_is_skipped() {
+        let (world, result) = try_load(
+            r#"{
+                "Sand": { "density": 1250 },
+                "Mud": { "density": "high" },
+            }"#,
+        );
+
+        assert!(
+            matches!(
+                result,
+                Err(ParticleTypesAssetLoaderError::Component { ref particle, ref component, .. })
+                    if particle == "Mud" && component == "density"
+            ),
+            "{result:?}"
+        );
+        let type_map = world.resource::<ParticleTypeMap>();
+        assert!(type_map.get(&"Sand".to_string()).is_some());
+        assert!(type_map.get(&"Mud".to_string()).is_none());
+    }
+
+    #[test]
+    fn unknown_on_spread_effect_is_rejected() {
+        for on_spread in [r#""Ash""#, r#"{ "turn_into": "Ash" }"#] {
+            let (_, result) = try_load(&format!(
+                r#"{{ "Ember": {{ "fire": {{ "burn_radius": 1.5, "chance_to_spread": 0.5, "on_spread": {on_spread} }} }} }}"#
+            ));
+            assert!(
+                matches!(result, Err(ParticleTypesAssetLoaderError::Component { ref component, .. }) if component == "fire"),
+                "{on_spread}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn describe_lists_the_loaded_keys() {
+        let source = r##"{
+            "Water": {
+                "density": 750,
+                "max_velocity": 3,
+                "momentum": true,
+                "liquid": 5,
+                "colors": ["#0B80AB80"],
+                "changes_colors": 0.1,
+            },
+            "Sand": { "density": 1250, "max_velocity": 3, "movable_solid": true, "randomizes_colors": 0.2 },
+            "Rock": { "density": 1500, "solid": true },
+            "Wall": { "wall": true },
+            "Steam": { "density": 4, "gas": 1 },
+            "Slider": { "density": 1000, "diagonal_mover": true },
+            "Wood": {
+                "wall": true,
+                "burns": { "duration": 1000, "tick_rate": 100 },
+            },
+            "Flame": {
+                "fire": { "burn_radius": 1.5, "chance_to_spread": 0.5, "on_spread": "destroy" },
+                "burning": { "duration": 1000, "tick_rate": 100 },
+            },
+        }"##;
+        let loaded: ron::Map = ron::from_str(source).unwrap();
+        let world = load(source);
+
+        for (name, components) in loaded.iter() {
+            let name = name.clone().into_rust::<String>().unwrap();
+            let mut described: HashMap<String, ron::Map> =
+                ron::from_str(&describe_particle_type(&world, &name).unwrap()).unwrap();
+            let described = described.remove(&name).unwrap();
+
+            let keys = |map: &ron::Map| {
+                let mut keys: Vec<String> = map
+                    .keys()
+                    .map(|key| key.clone().into_rust::<String>().unwrap())
+                    .collect();
+                keys.sort();
+                keys
+            };
+            let components = components.clone().into_rust::<ron::Map>().unwrap();
+            assert_eq!(keys(&described), keys(&components), "{name}");
+        }
+    }
+
+    fn reload(world: &mut World, source: &str) -> Vec<Entity> {
+        let asset = ParticleTypesAsset {
+            particle_types: ron::from_str(source).unwrap(),
+        };
+        world
+            .run_system_once(
+                move |mut commands: Commands, mut type_map: ResMut<ParticleTypeMap>| {
+                    asset.reload_particle_types(&mut commands, &mut type_map)
+                },
+            )
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn reloading_updates_types_in_place() {
+        let mut world =
+            load(r#"{ "Water": { "density": 750, "liquid": 5, "temperature": 20.0 } }"#);
+        let water = particle_type(&world, "Water");
+
+        let updated = reload(
+            &mut world,
+            r#"{ "Water": { "density": 1000, "liquid": 3 } }"#,
+        );
+
+        assert_eq!(updated, vec![water]);
+        assert_eq!(particle_type(&world, "Water"), water);
+        assert_eq!(
+            world.get::<DensityBlueprint>(water),
+            Some(&DensityBlueprint(Density(1000)))
+        );
+        assert_eq!(
+            world.get::<LiquidBlueprint>(water),
+            Some(&LiquidBlueprint(Liquid::new(3)))
+        );
+        assert!(world.get::<TemperatureBlueprint>(water).is_none());
+    }
+
+    #[test]
+    fn malformed_reload_keeps_the_previous_type() {
+        let mut world = load(r#"{ "Water": { "density": 750, "liquid": 5 } }"#);
+        let water = particle_type(&world, "Water");
+
+        let updated = reload(
+            &mut world,
+            r#"{ "Water": { "density": "heavy", "liquid": 3 } }"#,
+        );
+
+        assert!(updated.is_empty());
+        assert_eq!(
+            world.get::<DensityBlueprint>(water),
+            Some(&DensityBlueprint(Density(750)))
+        );
+        assert_eq!(
+            world.get::<LiquidBlueprint>(water),
+            Some(&LiquidBlueprint(Liquid::new(5)))
+        );
+        assert_eq!(world.query::<&DensityBlueprint>().iter(&world).count(), 1);
+    }
+
+    /// Runs the simulation with the particle types of `source` loaded.
+    fn simulation(source: &str) -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            RngPlugin::default(),
+            FallingSandCorePlugin::default(),
+            FallingSandMovementPlugin,
+            FallingSandSpatialPlugin,
+            FallingSandReactionsPlugin,
+        ))
+        .add_event::<ResetParticleColorEvent>();
+        let asset = ParticleTypesAsset {
+            particle_types: ron::from_str(source).unwrap(),
+        };
+        app.world_mut()
+            .run_system_once(
+                move |mut commands: Commands, mut type_map: ResMut<ParticleTypeMap>| {
+                    asset.load_particle_types(&mut commands, &mut type_map)
+                },
+            )
+            .unwrap()
+            .unwrap();
+        app.update();
+        app
+    }
+
+    #[test]
+    fn loaded_particles_carry_their_material() {
+        let mut app = simulation(
+            r#"{
+                "Sand": { "density": 1250, "max_velocity": 3, "movable_solid": true },
+                "Rock": { "density": 1250, "max_velocity": 3, "solid": true },
+                "Steam": { "density": 4, "max_velocity": 1, "gas": 1 },
+                "Stone": { "wall": true },
+            }"#,
+        );
+        for (x, name) in ["Sand", "Rock", "Steam", "Stone"].into_iter().enumerate() {
+            spawn(&mut app, name, [IVec2::new(x as i32 * 4, 0)]);
+        }
+        step(&mut app, 2);
+
+        let mut materials: Vec<_> = app
+            .world_mut()
+            .query::<(
+                &Particle,
+                Has<MovableSolid>,
+                Has<Solid>,
+                Has<Gas>,
+                Has<Wall>,
+                Option<&Density>,
+            )>()
+            .iter(app.world())
+            .map(|(particle, movable_solid, solid, gas, wall, density)| {
+                (
+                    particle.name.clone(),
+                    movable_solid,
+                    solid,
+                    gas,
+                    wall,
+                    density.copied(),
+                )
+            })
+            .collect();
+        materials.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            materials,
+            vec![
+                (
+                    "Rock".to_string(),
+                    false,
+                    true,
+                    false,
+                    false,
+                    Some(Density(1250))
+                ),
+                (
+                    "Sand".to_string(),
+                    true,
+                    false,
+                    false,
+                    false,
+                    Some(Density(1250))
+                ),
+                (
+                    "Steam".to_string(),
+                    false,
+                    false,
+                    true,
+                    false,
+                    Some(Density(4))
+                ),
+                ("Stone".to_string(), false, false, false, true, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn loaded_light_solids_float_up_through_loaded_liquids() {
+        let mut app = simulation(
+            r#"{
+                "Stone": { "wall": true },
+                "Water": { "density": 1000, "max_velocity": 3, "liquid": 3 },
+                "Wood": { "density": 500, "max_velocity": 3, "movable_solid": true },
+            }"#,
+        );
+        // A sealed, one cell wide shaft with the wood at the bottom of a column of water.
+        spawn(
+            &mut app,
+            "Stone",
+            (-1..=4)
+                .flat_map(|y| [IVec2::new(-1, y), IVec2::new(1, y)])
+                .chain([IVec2::new(0, -1), IVec2::new(0, 4)]),
+        );
+        spawn(&mut app, "Wood", [IVec2::ZERO]);
+        spawn(&mut app, "Water", (1..=3).map(|y| IVec2::new(0, y)));
+        step(&mut app, 20);
+
+        assert_eq!(positions(&mut app, "Wood"), vec![IVec2::new(0, 3)]);
+        assert_eq!(positions(&mut app, "Water").len(), 3);
+    }
+
+    #[test]
+    fn local_force_fields_push_loaded_gases() {
+        let mut app = simulation(
+            r#"{
+                "Stone": { "wall": true },
+                "Steam": { "density": 200, "max_velocity": 1, "gas": 1 },
+            }"#,
+        );
+        app.insert_resource(GlobalRng::with_seed(7));
+
+        // A sealed corridor, with end walls thick enough that gas can't jump over them, and a fan
+        // blowing along it.
+        spawn(
+            &mut app,
+            "Stone",
+            (-32..=32).flat_map(|x| [IVec2::new(x, -1), IVec2::new(x, 3)]),
+        );
+        spawn(
+            &mut app,
+            "Stone",
+            (0..3).flat_map(|y| [-32, -31, -30, 30, 31, 32].map(|x| IVec2::new(x, y))),
+        );
+        spawn(
+            &mut app,
+            "Steam",
+            (0..3).flat_map(|y| (-1..=1).map(move |x| IVec2::new(x, y))),
+        );
+        app.world_mut().spawn(LocalForceField::new(
+            IRect::new(-30, 0, 30, 2),
+            ForceField::new(Vec2::X, 0.5),
+        ));
+        step(&mut app, 40);
+
+        let steam = positions(&mut app, "Steam");
+        assert_eq!(steam.len(), 9);
+        let drift: i32 = steam.iter().map(|position| position.x).sum();
+        assert!(drift > 9 * 5, "{steam:?}");
+    }
+
+    #[test]
+    fn loaded_acid_dissolves_loaded_walls() {
+        let mut app = simulation(
+            r#"{
+                "Stone": { "wall": true },
+                "Acid": {
+                    "density": 1100,
+                    "max_velocity": 1,
+                    "liquid": 1,
+                    "corrodes": { "chance_per_tick": 1.0, "targets": ["wall"] },
+                },
+            }"#,
+        );
+        spawn(
+            &mut app,
+            "Stone",
+            [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y],
+        );
+        spawn(&mut app, "Acid", [IVec2::ZERO]);
+        step(&mut app, 20);
+
+        assert!(positions(&mut app, "Stone").len() < 4);
+    }
+}
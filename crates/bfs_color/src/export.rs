@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy::render::{
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bfs_core::ChunkMap;
+use image::{ImageError, Rgba, RgbaImage};
+use std::path::Path;
+use thiserror::Error;
+
+use crate::ParticleColor;
+
+#[derive(Debug, Error)]
+pub enum FrameExportError {
+    #[error("Could not export frame: the ChunkMap resource does not exist")]
+    MissingChunkMap,
+    #[error("Could not export frame: the region is empty")]
+    EmptyRegion,
+    #[error("Could not write frame: {0}")]
+    Image(#[from] ImageError),
+}
+
+/// Rasterizes the currently displayed particle colors into an image, one pixel per cell.
+///
+/// The image covers `region` (inclusive), or the whole map if `None`, with +y pointing up. Cells
+/// without a particle are filled with `background`, or left transparent if `None`. This reads
+/// particle sprites directly and does not need a window or the GPU.
+///
+/// A cell holding a bubble as well as its own particle (see `bfs_core::Submerged`) is drawn with
+/// the two colors blended evenly.
+pub fn render_frame(
+    world: &World,
+    region: Option<IRect>,
+    background: Option<Color>,
+) -> Result<RgbaImage, FrameExportError> {
+    let map = world
+        .get_resource::<ChunkMap>()
+        .ok_or(FrameExportError::MissingChunkMap)?;
+    render_map(map, region, background, |entity| {
+        world.get::<Sprite>(entity).map(|sprite| sprite.color)
+    })
+}
+
+/// Rasterizes the particles in `map` using `color_of` to look up each particle's color. See
+/// [`render_frame`]; this is the same rasterization for callers that don't have the `World`,
+/// such as regular systems that query `Sprite`s themselves.
+pub fn render_map(
+    map: &ChunkMap,
+    region: Option<IRect>,
+    background: Option<Color>,
+    color_of: impl Fn(Entity) -> Option<Color>,
+) -> Result<RgbaImage, FrameExportError> {
+    let region = region.unwrap_or_else(|| {
+        map.iter_chunks()
+            .map(|chunk| chunk.region())
+            .reduce(|a, b| a.union(b))
+            .unwrap_or_default()
+    });
+    if region.max.x < region.min.x || region.max.y < region.min.y {
+        return Err(FrameExportError::EmptyRegion);
+    }
+
+    Ok(rasterize(map, region, background, color_of))
+}
+
+/// Renders the particles between the corners `min` and `max` (inclusive) into an `Image` using
+/// each particle's selected [`ParticleColor`], e.g. for thumbnails of a selection.
+///
+/// The top row of the image is the top of the region. Empty cells are left transparent.
+pub fn render_region_to_image(
+    map: &ChunkMap,
+    colors: &Query<&ParticleColor>,
+    min: IVec2,
+    max: IVec2,
+) -> Image {
+    let image = rasterize(map, IRect::from_corners(min, max), None, |entity| {
+        colors.get(entity).ok().map(|color| color.selected)
+    });
+    Image::new(
+        Extent3d {
+            width: image.width(),
+            height: image.height(),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        image.into_raw(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+fn rasterize(
+    map: &ChunkMap,
+    region: IRect,
+    background: Option<Color>,
+    color_of: impl Fn(Entity) -> Option<Color>,
+) -> RgbaImage {
+    let size = region.size() + IVec2::ONE;
+    let fill = background.map_or(Rgba([0, 0, 0, 0]), to_rgba);
+    let mut image = RgbaImage::from_pixel(size.x as u32, size.y as u32, fill);
+
+    map.iter_region(region.min, region.max)
+        .for_each(|(coordinates, entity)| {
+            if let Some(color) = color_of(entity) {
+                let x = (coordinates.x - region.min.x) as u32;
+                let y = (region.max.y - coordinates.y) as u32;
+                image.put_pixel(x, y, to_rgba(color));
+            }
+        });
+    // Bubbles sharing a cell are blended evenly with the particle they share it with.
+    map.iter_secondary()
+        .filter(|(coordinates, _)| region.contains(*coordinates))
+        .for_each(|(coordinates, entity)| {
+            let Some(color) = color_of(entity) else {
+                return;
+            };
+            let color = match map
+                .entity(&coordinates)
+                .and_then(|entity| color_of(*entity))
+            {
+                Some(primary) => Srgba::from(primary).mix(&Srgba::from(color), 0.5).into(),
+                None => color,
+            };
+            let x = (coordinates.x - region.min.x) as u32;
+            let y = (region.max.y - coordinates.y) as u32;
+            image.put_pixel(x, y, to_rgba(color));
+        });
+
+    image
+}
+
+/// Writes the current frame to a PNG at `path`, leaving empty cells transparent. See
+/// [`render_frame`].
+pub fn export_frame(
+    world: &World,
+    path: impl AsRef<Path>,
+    region: Option<IRect>,
+) -> Result<(), FrameExportError> {
+    export_frame_with_background(world, path, region, None)
+}
+
+/// Writes the current frame to a PNG at `path`, filling empty cells with `background`.
+pub fn export_frame_with_background(
+    world: &World,
+    path: impl AsRef<Path>,
+    region: Option<IRect>,
+    background: Option<Color>,
+) -> Result<(), FrameExportError> {
+    render_frame(world, region, background)?.save_with_format(path, image::ImageFormat::Png)?;
+    Ok(())
+}
+
+fn to_rgba(color: Color) -> Rgba<u8> {
+    Rgba(color.to_srgba().to_u8_array())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    const RED: Color = Color::srgb(1., 0., 0.);
+
+    fn world_with(particles: &[IVec2]) -> World {
+        let mut world = World::new();
+        let mut map = ChunkMap::new(128);
+        for coordinates in particles {
+            let entity = world.spawn(ParticleColor::new(RED, vec![RED])).id();
+            map.insert_overwrite(*coordinates, entity);
+        }
+        world.insert_resource(map);
+        world
+    }
+
+    fn region_image(world: &mut World, min: IVec2, max: IVec2) -> Image {
+        world
+            .run_system_once(move |map: Res<ChunkMap>, colors: Query<&ParticleColor>| {
+                render_region_to_image(&map, &colors, min, max)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn single_row_and_column_regions_are_rendered() {
+        // y = 0 is the top row of its chunks, x = 0 the first column of its chunks.
+        let row: Vec<IVec2> = (-3..=3).map(|x| IVec2::new(x, 0)).collect();
+        let column: Vec<IVec2> = (1..=4).map(|y| IVec2::new(0, y)).collect();
+        let mut world = world_with(&[row.as_slice(), column.as_slice()].concat());
+        let opaque_red = [255, 0, 0, 255];
+
+        let image = region_image(&mut world, IVec2::new(-3, 0), IVec2::new(3, 0));
+        assert_eq!((image.width(), image.height()), (7, 1));
+        assert!(image.data.chunks(4).all(|pixel| pixel == opaque_red));
+
+        let image = region_image(&mut world, IVec2::new(0, 0), IVec2::new(0, 4));
+        assert_eq!((image.width(), image.height()), (1, 5));
+        assert!(image.data.chunks(4).all(|pixel| pixel == opaque_red));
+    }
+
+    fn with_sprites(world: &mut World) {
+        let particles: Vec<Entity> = world
+            .query_filtered::<Entity, With<ParticleColor>>()
+            .iter(world)
+            .collect();
+        for entity in particles {
+            world
+                .entity_mut(entity)
+                .insert(Sprite::from_color(RED, Vec2::ONE));
+        }
+    }
+
+    #[test]
+    fn whole_frame_includes_map_edges() {
+        let bounds = ChunkMap::new(128).world_bounds_i();
+        let corners = [
+            bounds.min,
+            bounds.max,
+            IVec2::new(bounds.min.x, bounds.max.y),
+            IVec2::new(bounds.max.x, bounds.min.y),
+        ];
+        let mut world = world_with(&corners);
+        with_sprites(&mut world);
+
+        let image = render_frame(&world, None, Some(Color::BLACK)).unwrap();
+        assert_eq!((image.width(), image.height()), (128, 128));
+        let red = Rgba([255, 0, 0, 255]);
+        for (x, y) in [(0, 0), (127, 0), (0, 127), (127, 127)] {
+            assert_eq!(*image.get_pixel(x, y), red);
+        }
+        let filled = image.pixels().filter(|pixel| **pixel == red).count();
+        assert_eq!(filled, corners.len());
+    }
+
+    #[test]
+    fn exported_frame_keeps_chunk_edge_rows() {
+        // y = 0 and y = 1 belong to different chunks.
+        let cells: Vec<IVec2> = (-2..=2)
+            .flat_map(|x| [IVec2::new(x, 0), IVec2::new(x, 1)])
+            .collect();
+        let mut world = world_with(&cells);
+        with_sprites(&mut world);
+
+        let path = std::env::temp_dir().join(format!("bfs_color_{}_frame.png", std::process::id()));
+        let region = IRect::from_corners(IVec2::new(-2, 0), IVec2::new(2, 1));
+        export_frame(&world, &path, Some(region)).unwrap();
+        let image = image::open(&path).unwrap().to_rgba8();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((image.width(), image.height()), (5, 2));
+        assert!(image.pixels().all(|pixel| *pixel == Rgba([255, 0, 0, 255])));
+    }
+
+    #[test]
+    fn bubbles_are_blended_with_their_cell() {
+        let mut world = world_with(&[IVec2::ZERO]);
+        with_sprites(&mut world);
+        let blue = Color::srgb(0., 0., 1.);
+        for coordinates in [IVec2::ZERO, IVec2::X] {
+            let bubble = world.spawn(Sprite::from_color(blue, Vec2::ONE)).id();
+            world
+                .resource_mut::<ChunkMap>()
+                .insert_secondary(coordinates, bubble);
+        }
+
+        let image = render_frame(&world, Some(IRect::new(0, 0, 1, 0)), None).unwrap();
+        // sRGB channels are averaged, rounding to the nearest byte.
+        assert_eq!(*image.get_pixel(0, 0), Rgba([128, 0, 128, 255]));
+        // Bubbles in empty cells are drawn as they are.
+        assert_eq!(*image.get_pixel(1, 0), Rgba([0, 0, 255, 255]));
+    }
+}
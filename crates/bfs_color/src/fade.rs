@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use bfs_core::{ChunkMap, Particle};
+
+pub struct DespawnFadePlugin;
+
+impl Plugin for DespawnFadePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DespawnFade>()
+            .register_type::<DespawnGhost>()
+            .add_systems(Update, fade_despawn_ghosts)
+            .add_observer(on_despawn_particle);
+    }
+}
+
+/// Fades particles out over `frames` frames when they are despawned, instead of removing them
+/// instantly. Without this resource, despawned particles vanish immediately.
+///
+/// The particle itself is despawned as usual and its cell is free straight away. What fades is a
+/// plain sprite left in its place, which never takes part in the simulation.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct DespawnFade {
+    pub frames: u8,
+}
+
+impl Default for DespawnFade {
+    fn default() -> DespawnFade {
+        DespawnFade { frames: 8 }
+    }
+}
+
+/// The fading sprite left behind by a despawned particle.
+#[derive(Component, Copy, Clone, PartialEq, Debug, Reflect)]
+#[reflect(Component)]
+pub struct DespawnGhost {
+    pub coordinates: IVec2,
+    pub remaining: u8,
+    pub frames: u8,
+    pub alpha: f32,
+}
+
+pub fn on_despawn_particle(
+    trigger: Trigger<OnRemove, Particle>,
+    mut commands: Commands,
+    fade: Option<Res<DespawnFade>>,
+    particle_query: Query<(&Sprite, &Transform)>,
+) {
+    let Some(fade) = fade else {
+        return;
+    };
+    if fade.frames == 0 {
+        return;
+    }
+    let Ok((sprite, transform)) = particle_query.get(trigger.entity()) else {
+        return;
+    };
+    commands.spawn((
+        sprite.clone(),
+        *transform,
+        DespawnGhost {
+            coordinates: transform.translation.truncate().as_ivec2(),
+            remaining: fade.frames,
+            frames: fade.frames,
+            alpha: sprite.color.alpha(),
+        },
+    ));
+}
+
+pub fn fade_despawn_ghosts(
+    mut commands: Commands,
+    map: Res<ChunkMap>,
+    mut ghost_query: Query<(Entity, &mut DespawnGhost, &mut Sprite)>,
+) {
+    ghost_query
+        .iter_mut()
+        .for_each(|(entity, mut ghost, mut sprite)| {
+            // A particle moving into the ghost's cell replaces it outright.
+            if ghost.remaining == 0 || map.is_occupied(ghost.coordinates) {
+                commands.entity(entity).despawn();
+                return;
+            }
+            ghost.remaining -= 1;
+            let progress = f32::from(ghost.remaining) / f32::from(ghost.frames);
+            sprite.color.set_alpha(ghost.alpha * progress);
+        });
+}
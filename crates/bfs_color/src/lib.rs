@@ -1,17 +1,29 @@
+mod export;
+mod fade;
 mod particle_definitions;
+mod render;
 mod rng;
 mod systems;
 
 use bevy::prelude::*;
 
+pub use export::*;
+pub use fade::*;
 pub use particle_definitions::*;
+pub use render::*;
 pub use rng::*;
+pub use systems::ParticleColorSet;
 use systems::*;
 
 pub struct FallingSandColorPlugin;
 
 impl Plugin for FallingSandColorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((ParticleDefinitionsPlugin, SystemsPlugin));
+        app.add_plugins((
+            ParticleDefinitionsPlugin,
+            SystemsPlugin,
+            ParticleRenderPlugin,
+            DespawnFadePlugin,
+        ));
     }
 }
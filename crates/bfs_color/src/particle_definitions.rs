@@ -1,20 +1,30 @@
 use bevy::prelude::*;
-use bevy_turborand::{DelegatedRng, GlobalRng, TurboRand};
+use bevy_turborand::{DelegatedRng, GlobalRng, RngComponent, TurboRand};
 use serde::{Deserialize, Serialize};
 
 use super::ColorRng;
-use bfs_core::{Particle, ParticleRegistrationEvent, ParticleType};
+use bfs_core::{handle_new_particles, Particle, ParticleRegistrationEvent, ParticleType};
 
 pub struct ParticleDefinitionsPlugin;
 
 impl Plugin for ParticleDefinitionsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, handle_particle_registration);
+        app.add_systems(
+            PreUpdate,
+            handle_particle_registration.after(handle_new_particles),
+        );
         app.add_event::<ResetParticleColorEvent>();
         app.register_type::<ColorRng>()
             .register_type::<ParticleColor>()
             .register_type::<FlowsColor>()
-            .register_type::<RandomizesColor>();
+            .register_type::<RandomizesColor>()
+            .register_type::<PositionalColor>()
+            .register_type::<SeededColor>()
+            .register_type::<ColorSeed>()
+            .register_type::<SurfaceHighlight>()
+            .register_type::<SurfaceExposure>()
+            .register_type::<GradientColor>()
+            .register_type::<ColorByVelocity>();
     }
 }
 
@@ -49,6 +59,11 @@ impl ParticleColor {
         self.selected = *self.palette.get(self.color_index).unwrap();
     }
 
+    pub fn select(&mut self, index: usize) {
+        self.color_index = index % self.palette.len();
+        self.selected = *self.palette.get(self.color_index).unwrap();
+    }
+
     pub fn set_next(&mut self) {
         if self.palette.len() - 1 == self.color_index {
             self.color_index = 0;
@@ -57,6 +72,20 @@ impl ParticleColor {
         }
         self.selected = *self.palette.get(self.color_index).unwrap();
     }
+
+    /// Samples the palette as a gradient, from its first color at `t = 0.` to its last at
+    /// `t = 1.`, blending linearly between neighboring colors. `t` is clamped to that range.
+    ///
+    /// Returns the selected color if the palette is empty.
+    pub fn sample(&self, t: f32) -> Color {
+        let Some(last) = self.palette.len().checked_sub(1) else {
+            return self.selected;
+        };
+        let position = t.clamp(0., 1.) * last as f32;
+        let index = (position.floor() as usize).min(last);
+        let next = (index + 1).min(last);
+        self.palette[index].mix(&self.palette[next], position - index as f32)
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Default, Component, Reflect, Serialize, Deserialize)]
@@ -91,6 +120,175 @@ impl FlowsColor {
     }
 }
 
+/// Selects a particle's color from its palette by hashing its position, so a given cell always
+/// renders the same color. Cells are grouped into `scale`-sized blocks before hashing.
+#[derive(Copy, Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct PositionalColor {
+    pub scale: f32,
+}
+
+impl PositionalColor {
+    pub fn new(scale: f32) -> PositionalColor {
+        PositionalColor { scale }
+    }
+
+    pub fn index(&self, coordinates: IVec2, palette_len: usize) -> usize {
+        if palette_len == 0 {
+            return 0;
+        }
+        let cell = (coordinates.as_vec2() / self.scale.max(1.)).floor().as_ivec2();
+        hash_index((cell.x as u32 as u64) << 32 | cell.y as u32 as u64, palette_len)
+    }
+}
+
+/// Gives each particle one color from its palette for its whole lifetime, chosen from the
+/// particle's [`ColorSeed`] rather than rolled at random.
+///
+/// This sits between a single solid color and [`RandomizesColor`]: a field of sand gets varied
+/// grains that never flicker, and a particle keeps its color when its type is re-registered.
+/// Components that change colors over time, such as [`FlowsColor`], still apply on top of it.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct SeededColor;
+
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct SeededColorBlueprint(pub SeededColor);
+
+/// The per-instance seed used by [`SeededColor`]. It's taken from the particle's entity when the
+/// particle is first registered and kept from then on.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct ColorSeed(pub u64);
+
+impl ColorSeed {
+    pub fn index(&self, palette_len: usize) -> usize {
+        hash_index(self.0, palette_len)
+    }
+}
+
+/// Mixes `value` into a well-distributed index into a palette of `palette_len` colors.
+fn hash_index(value: u64, palette_len: usize) -> usize {
+    if palette_len == 0 {
+        return 0;
+    }
+    let mut hash = value;
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^= hash >> 33;
+    (hash % palette_len as u64) as usize
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct PositionalColorBlueprint(pub PositionalColor);
+
+/// Tints particles with an empty cell directly above them, e.g. to give liquids a visible
+/// surface line.
+///
+/// `smoothing` (0.0 to 1.0) is the share of the previous tint kept each tick, so churning
+/// surfaces fade in and out instead of flickering. Use 0.0 for an immediate tint.
+#[derive(Copy, Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct SurfaceHighlight {
+    pub color: Color,
+    pub smoothing: f32,
+}
+
+impl SurfaceHighlight {
+    pub fn new(color: Color, smoothing: f32) -> SurfaceHighlight {
+        SurfaceHighlight {
+            color,
+            smoothing: smoothing.clamp(0., 1.),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct SurfaceHighlightBlueprint(pub SurfaceHighlight);
+
+/// How strongly a particle's `SurfaceHighlight` is currently applied, from 0.0 to 1.0.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct SurfaceExposure(pub f32);
+
+/// The value a [`GradientColor`] maps across its particle's palette.
+#[derive(Copy, Clone, PartialEq, Debug, Reflect, Serialize, Deserialize)]
+pub enum ColorSource {
+    /// The particle's current `Velocity` as a fraction of its maximum, so resting particles
+    /// take the first color and particles at full speed take the last.
+    Velocity,
+    /// The particle's `Temperature`, from the first color at `min` to the last at `max`. This is
+    /// only updated when `bfs_reactions` is in use.
+    Temperature { min: f32, max: f32 },
+}
+
+/// Renders a particle by sampling its [`ParticleColor`] palette as a gradient (see
+/// [`ParticleColor::sample`]), driven by a value read from `source` each tick.
+///
+/// The palette's selected color isn't shown while this is present, so components that change
+/// it, such as [`FlowsColor`] or [`SurfaceHighlight`], have no visible effect.
+#[derive(Copy, Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct GradientColor {
+    pub source: ColorSource,
+}
+
+impl GradientColor {
+    pub fn new(source: ColorSource) -> GradientColor {
+        GradientColor { source }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct GradientColorBlueprint(pub GradientColor);
+
+/// Renders a particle by its speed, sampling `gradient` from its first color at rest to its last
+/// at `max_speed` or above (see [`ParticleColor::sample`]). Useful as a heat map to debug flow.
+///
+/// Speed is how many cells per tick the particle's `Velocity` is above resting (1), so resting
+/// particles get the first color. Particles without a velocity, or whose `Momentum` is zero, are
+/// treated as resting. This takes precedence over the particle's own [`ParticleColor`] and
+/// [`GradientColor`], which keep updating but aren't shown.
+#[derive(Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct ColorByVelocity {
+    pub gradient: ParticleColor,
+    pub max_speed: f32,
+}
+
+impl ColorByVelocity {
+    pub fn new(gradient: ParticleColor, max_speed: f32) -> ColorByVelocity {
+        ColorByVelocity {
+            gradient,
+            max_speed,
+        }
+    }
+
+    /// The color for a particle moving at `speed`.
+    pub fn color(&self, speed: f32) -> Color {
+        if self.max_speed <= 0. {
+            return self.gradient.sample(1.);
+        }
+        self.gradient.sample(speed / self.max_speed)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct ColorByVelocityBlueprint(pub ColorByVelocity);
+
 #[derive(
     Clone, Hash, Debug, Default, Eq, PartialEq, PartialOrd, Event, Reflect, Serialize, Deserialize,
 )]
@@ -110,33 +308,61 @@ fn handle_particle_components(
             Option<&ParticleColorBlueprint>,
             Option<&FlowsColorBlueprint>,
             Option<&RandomizesColorBlueprint>,
+            Option<&PositionalColorBlueprint>,
+            Option<&SurfaceHighlightBlueprint>,
+            Option<&SeededColorBlueprint>,
+            Option<&GradientColorBlueprint>,
+            Option<&ColorByVelocityBlueprint>,
         ),
         With<ParticleType>,
     >,
-    particle_query: &Query<&Parent, With<Particle>>,
+    particle_query: &Query<(&Parent, Option<&ColorSeed>), With<Particle>>,
     entities: &Vec<Entity>,
 ) {
     entities.iter().for_each(|entity| {
-        if let Ok(parent) = particle_query.get(*entity) {
-            commands.entity(*entity).insert(ColorRng::default());
-            if let Ok((particle_color, flows_color, randomizes_color)) =
-                parent_query.get(parent.get())
+        if let Ok((parent, color_seed)) = particle_query.get(*entity) {
+            commands
+                .entity(*entity)
+                .insert(ColorRng(RngComponent::from(&mut **rng)));
+            if let Ok((
+                particle_color,
+                flows_color,
+                randomizes_color,
+                positional_color,
+                surface_highlight,
+                seeded_color,
+                gradient_color,
+                color_by_velocity,
+            )) = parent_query.get(parent.get())
             {
-                commands.entity(*entity).insert((
-                    Sprite {
-                        color: Color::srgba(0., 0., 0., 0.),
-                        ..default()
-                    },
-                    ColorRng::default(),
-                ));
+                commands.entity(*entity).insert(Sprite {
+                    color: Color::srgba(0., 0., 0., 0.),
+                    ..default()
+                });
                 if let Some(particle_color) = particle_color {
-                    let rng = rng.get_mut();
-                    commands
-                        .entity(*entity)
-                        .insert(particle_color.0.new_with_random(rng));
+                    if let Some(seeded_color) = seeded_color {
+                        let color_seed = color_seed
+                            .copied()
+                            .unwrap_or(ColorSeed(entity.to_bits()));
+                        let mut particle_color = particle_color.0.clone();
+                        particle_color.select(color_seed.index(particle_color.palette.len()));
+                        commands.entity(*entity).insert((
+                            particle_color,
+                            seeded_color.0,
+                            color_seed,
+                        ));
+                    } else {
+                        let rng = rng.get_mut();
+                        commands
+                            .entity(*entity)
+                            .insert(particle_color.0.new_with_random(rng));
+                    }
                 } else {
                     commands.entity(*entity).remove::<ParticleColor>();
                 }
+                if seeded_color.is_none() || particle_color.is_none() {
+                    commands.entity(*entity).remove::<SeededColor>();
+                }
                 if let Some(flows_color) = flows_color {
                     commands.entity(*entity).insert(flows_color.0.clone());
                 } else {
@@ -147,6 +373,30 @@ fn handle_particle_components(
                 } else {
                     commands.entity(*entity).remove::<RandomizesColor>();
                 }
+                if let Some(positional_color) = positional_color {
+                    commands.entity(*entity).insert(positional_color.0);
+                } else {
+                    commands.entity(*entity).remove::<PositionalColor>();
+                }
+                if let Some(surface_highlight) = surface_highlight {
+                    commands
+                        .entity(*entity)
+                        .insert((surface_highlight.0, SurfaceExposure::default()));
+                } else {
+                    commands
+                        .entity(*entity)
+                        .remove::<(SurfaceHighlight, SurfaceExposure)>();
+                }
+                if let Some(gradient_color) = gradient_color {
+                    commands.entity(*entity).insert(gradient_color.0);
+                } else {
+                    commands.entity(*entity).remove::<GradientColor>();
+                }
+                if let Some(color_by_velocity) = color_by_velocity {
+                    commands.entity(*entity).insert(color_by_velocity.0.clone());
+                } else {
+                    commands.entity(*entity).remove::<ColorByVelocity>();
+                }
             }
         }
     });
@@ -160,10 +410,15 @@ fn handle_particle_registration(
             Option<&ParticleColorBlueprint>,
             Option<&FlowsColorBlueprint>,
             Option<&RandomizesColorBlueprint>,
+            Option<&PositionalColorBlueprint>,
+            Option<&SurfaceHighlightBlueprint>,
+            Option<&SeededColorBlueprint>,
+            Option<&GradientColorBlueprint>,
+            Option<&ColorByVelocityBlueprint>,
         ),
         With<ParticleType>,
     >,
-    particle_query: Query<&Parent, With<Particle>>,
+    particle_query: Query<(&Parent, Option<&ColorSeed>), With<Particle>>,
     mut ev_particle_registered: EventReader<ParticleRegistrationEvent>,
     mut ev_reset_particle_color: EventReader<ResetParticleColorEvent>,
 ) {
@@ -186,3 +441,51 @@ fn handle_particle_registration(
         );
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(palette: Vec<Color>) -> ParticleColor {
+        ParticleColor::new(Color::srgb(0., 0., 1.), palette)
+    }
+
+    #[test]
+    fn sample_blends_between_palette_colors() {
+        let (black, red, white) = (
+            Color::srgb(0., 0., 0.),
+            Color::srgb(1., 0., 0.),
+            Color::srgb(1., 1., 1.),
+        );
+        let colors = gradient(vec![black, red, white]);
+        assert_eq!(colors.sample(0.), black);
+        assert_eq!(colors.sample(0.25), Color::srgb(0.5, 0., 0.));
+        assert_eq!(colors.sample(0.5), red);
+        assert_eq!(colors.sample(1.), white);
+
+        let two = gradient(vec![black, white]);
+        assert_eq!(two.sample(0.5), Color::srgb(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn sample_clamps_outside_of_the_gradient() {
+        let (black, white) = (Color::srgb(0., 0., 0.), Color::srgb(1., 1., 1.));
+        let colors = gradient(vec![black, white]);
+        assert_eq!(colors.sample(-0.5), black);
+        assert_eq!(colors.sample(1.5), white);
+        assert_eq!(colors.sample(f32::NEG_INFINITY), black);
+        assert_eq!(colors.sample(f32::INFINITY), white);
+    }
+
+    #[test]
+    fn sample_falls_back_for_short_palettes() {
+        let selected = Color::srgb(0., 0., 1.);
+        assert_eq!(gradient(vec![]).sample(0.5), selected);
+
+        let red = Color::srgb(1., 0., 0.);
+        let single = gradient(vec![red]);
+        for t in [-1., 0., 0.5, 1., 2.] {
+            assert_eq!(single.sample(t), red);
+        }
+    }
+}
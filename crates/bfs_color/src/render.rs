@@ -0,0 +1,106 @@
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::{
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bfs_core::Particle;
+
+/// Side length (in texels) of the soft particle texture.
+const SOFT_PARTICLE_TEXTURE_SIZE: u32 = 16;
+
+pub struct ParticleRenderPlugin;
+
+impl Plugin for ParticleRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleRenderMode>()
+            .register_type::<ParticleRenderMode>()
+            .add_systems(Startup, setup_soft_particle_texture)
+            .add_systems(PostUpdate, apply_particle_render_mode);
+    }
+}
+
+/// Controls how particle sprites are drawn.
+///
+/// `Crisp` draws every particle as a single, hard-edged world unit. `Smooth` draws each particle
+/// as a slightly larger, bilinear-filtered sprite with a soft falloff, which looks much less
+/// aliased when the camera is zoomed out. Smoothing only takes effect while the camera's
+/// orthographic scale is above 1.0; zoomed-in views are always rendered crisp.
+#[derive(Resource, Copy, Clone, PartialEq, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub enum ParticleRenderMode {
+    #[default]
+    Crisp,
+    Smooth {
+        /// How far (in world units) each particle bleeds into its neighbors.
+        spread: f32,
+    },
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct SoftParticleTexture(pub Handle<Image>);
+
+fn setup_soft_particle_texture(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = SOFT_PARTICLE_TEXTURE_SIZE;
+    let center = (size as f32 - 1.) / 2.;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let distance = Vec2::new(x as f32 - center, y as f32 - center).length() / (center + 1.);
+            let alpha = (1. - distance.powi(2)).clamp(0., 1.);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.) as u8]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::linear();
+
+    commands.insert_resource(SoftParticleTexture(images.add(image)));
+}
+
+pub fn apply_particle_render_mode(
+    mode: Res<ParticleRenderMode>,
+    texture: Option<Res<SoftParticleTexture>>,
+    projection_query: Query<Ref<OrthographicProjection>, With<Camera>>,
+    mut particle_query: Query<&mut Sprite, With<Particle>>,
+) {
+    let Some(texture) = texture else {
+        return;
+    };
+
+    let zoomed_out = projection_query
+        .iter()
+        .any(|projection| projection.scale > 1.);
+    let projection_changed = projection_query
+        .iter()
+        .any(|projection| projection.is_changed());
+
+    let (image, custom_size) = match *mode {
+        ParticleRenderMode::Smooth { spread } if zoomed_out => (
+            texture.0.clone(),
+            Some(Vec2::splat(1. + spread.max(0.) * 2.)),
+        ),
+        _ => (Handle::default(), None),
+    };
+
+    let update_all = mode.is_changed() || projection_changed;
+    particle_query.iter_mut().for_each(|mut sprite| {
+        if !update_all && !sprite.is_changed() {
+            return;
+        }
+        if sprite.image != image || sprite.custom_size != custom_size {
+            sprite.image = image.clone();
+            sprite.custom_size = custom_size;
+        }
+    });
+}
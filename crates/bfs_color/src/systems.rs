@@ -1,31 +1,65 @@
 use bevy::prelude::*;
-use bfs_core::{Particle, ParticleSimulationSet};
+use bfs_core::{ChunkMap, Coordinates, Particle, ParticleSimulation, ParticleSimulationSet};
+use bfs_movement::{Momentum, Velocity};
 
-use super::{FlowsColor, ParticleColor, ColorRng};
+use super::{
+    ColorByVelocity, ColorRng, ColorSource, FlowsColor, GradientColor, ParticleColor,
+    PositionalColor, SurfaceExposure, SurfaceHighlight,
+};
 
 pub struct SystemsPlugin;
 
 impl Plugin for SystemsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
+            ParticleSimulation,
             (
+                color_positional_particles.before(color_particles),
                 color_particles,
                 color_flowing_particles,
+                highlight_surfaces.after(color_particles),
+                color_velocity_gradients.after(color_flowing_particles),
+                color_by_velocity,
             )
-                .in_set(ParticleSimulationSet),
+                .in_set(ParticleSimulationSet)
+                .in_set(ParticleColorSet),
         );
     }
 }
 
+/// The systems that recolor particles each tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParticleColorSet;
+
 pub fn color_particles(
-    mut particle_query: Query<(&mut Sprite, &ParticleColor), Changed<ParticleColor>>,
+    mut particle_query: Query<
+        (&mut Sprite, &ParticleColor),
+        (
+            Changed<ParticleColor>,
+            Without<GradientColor>,
+            Without<ColorByVelocity>,
+        ),
+    >,
 ) {
     particle_query.iter_mut().for_each(|(mut sprite, color)| {
         sprite.color = color.selected;
     });
 }
 
+pub fn color_positional_particles(
+    mut particle_query: Query<
+        (&mut ParticleColor, &PositionalColor, &Coordinates),
+        Or<(Changed<Coordinates>, Changed<ParticleColor>)>,
+    >,
+) {
+    particle_query
+        .iter_mut()
+        .for_each(|(mut particle_color, positional_color, coordinates)| {
+            let index = positional_color.index(coordinates.0, particle_color.palette.len());
+            particle_color.select(index);
+        });
+}
+
 pub fn color_flowing_particles(
     mut particles_query: Query<(&mut ParticleColor, &mut ColorRng, &FlowsColor), With<Particle>>,
 ) {
@@ -37,3 +71,152 @@ pub fn color_flowing_particles(
             }
         })
 }
+
+pub fn highlight_surfaces(
+    map: Res<ChunkMap>,
+    mut particle_query: Query<
+        (
+            &mut Sprite,
+            Ref<ParticleColor>,
+            &SurfaceHighlight,
+            &mut SurfaceExposure,
+            &Coordinates,
+        ),
+        (Without<GradientColor>, Without<ColorByVelocity>),
+    >,
+) {
+    particle_query.iter_mut().for_each(
+        |(mut sprite, particle_color, surface_highlight, mut exposure, coordinates)| {
+            // Exposure can only change in active chunks, but a fade in progress still has to
+            // finish in a chunk that just went to sleep.
+            let settled = exposure.0 == 0. || exposure.0 == 1.;
+            let hibernating = map
+                .chunk(&coordinates.0)
+                .is_none_or(|chunk| chunk.hibernating());
+            if settled && hibernating && !particle_color.is_changed() {
+                return;
+            }
+
+            let target = if map.is_occupied(coordinates.0 + IVec2::Y) {
+                0.
+            } else {
+                1.
+            };
+            let mut next = exposure.0 * surface_highlight.smoothing
+                + target * (1. - surface_highlight.smoothing);
+            if (next - target).abs() < 0.01 {
+                next = target;
+            }
+            if next == exposure.0 && !particle_color.is_changed() {
+                return;
+            }
+
+            exposure.0 = next;
+            sprite.color = particle_color
+                .selected
+                .mix(&surface_highlight.color, exposure.0);
+        },
+    );
+}
+
+pub fn color_velocity_gradients(
+    mut particle_query: Query<
+        (&mut Sprite, &ParticleColor, &GradientColor, &Velocity),
+        (
+            Or<(
+                Changed<Velocity>,
+                Changed<ParticleColor>,
+                Changed<GradientColor>,
+            )>,
+            Without<ColorByVelocity>,
+        ),
+    >,
+) {
+    particle_query
+        .iter_mut()
+        .filter(|(_, _, gradient_color, _)| gradient_color.source == ColorSource::Velocity)
+        .for_each(|(mut sprite, particle_color, _, velocity)| {
+            sprite.color = particle_color.sample(velocity_fraction(velocity));
+        });
+}
+
+pub fn color_by_velocity(
+    mut particle_query: Query<
+        (
+            &mut Sprite,
+            &ColorByVelocity,
+            Option<&Velocity>,
+            Option<&Momentum>,
+        ),
+        Or<(
+            Changed<Velocity>,
+            Changed<Momentum>,
+            Changed<ColorByVelocity>,
+        )>,
+    >,
+) {
+    particle_query
+        .iter_mut()
+        .for_each(|(mut sprite, color_by_velocity, velocity, momentum)| {
+            let speed = velocity.map_or(0., |velocity| speed(velocity, momentum));
+            sprite.color = color_by_velocity.color(speed);
+        });
+}
+
+/// How many cells per tick `velocity` is above resting. A particle whose `momentum` is zero is
+/// blocked, so it's resting whatever its velocity.
+fn speed(velocity: &Velocity, momentum: Option<&Momentum>) -> f32 {
+    if momentum.is_some_and(|momentum| *momentum == Momentum::ZERO) {
+        return 0.;
+    }
+    let max = velocity.max.saturating_sub(1);
+    f32::from(velocity.val.saturating_sub(1).min(max))
+}
+
+/// How far `velocity` is between resting and its maximum, from 0.0 to 1.0.
+fn velocity_fraction(velocity: &Velocity) -> f32 {
+    let max = velocity.max.saturating_sub(1);
+    if max == 0 {
+        return 0.;
+    }
+    speed(velocity, None) / f32::from(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn color_by_velocity_samples_speed_above_resting() {
+        let mut world = World::new();
+        let gradient = ParticleColor::new(Color::BLACK, vec![Color::BLACK, Color::WHITE]);
+        let heat_map = ColorByVelocity::new(gradient, 4.);
+        let mut spawn = |velocity: Velocity, momentum: Option<Momentum>| {
+            let mut particle = world.spawn((
+                Sprite::from_color(Color::NONE, Vec2::ONE),
+                heat_map.clone(),
+                velocity,
+            ));
+            if let Some(momentum) = momentum {
+                particle.insert(momentum);
+            }
+            particle.id()
+        };
+        let resting = spawn(Velocity::new(1, 5), None);
+        let halfway = spawn(Velocity::new(3, 5), None);
+        let fastest = spawn(Velocity::new(5, 5), Some(Momentum(IVec2::NEG_Y)));
+        let blocked = spawn(Velocity::new(5, 5), Some(Momentum::ZERO));
+        let unmoving = world
+            .spawn((Sprite::from_color(Color::NONE, Vec2::ONE), heat_map.clone()))
+            .id();
+        world.run_system_once(color_by_velocity).unwrap();
+
+        let color = |entity: Entity| world.get::<Sprite>(entity).unwrap().color;
+        assert_eq!(color(resting), Color::BLACK);
+        assert_eq!(color(halfway), Color::BLACK.mix(&Color::WHITE, 0.5));
+        assert_eq!(color(fastest), Color::WHITE);
+        assert_eq!(color(blocked), Color::BLACK);
+        assert_eq!(color(unmoving), Color::BLACK);
+    }
+}
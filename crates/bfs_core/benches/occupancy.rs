@@ -0,0 +1,65 @@
+//! Compares the occupancy bitset behind `ChunkMap::is_occupied` with hashmap lookups through
+//! `ChunkMap::entity`, over movement ticks of a densely packed field of falling particles.
+
+use bevy::prelude::*;
+use bfs_core::ChunkMap;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// The cells a particle tries to move into, in order: straight down, then either diagonal.
+const CANDIDATES: [IVec2; 3] = [IVec2::NEG_Y, IVec2::NEG_ONE, IVec2::new(1, -1)];
+
+/// A 128x128 block with one cell in ten left empty, resting on a floor that spans the map.
+fn dense_field() -> (ChunkMap, Vec<IVec2>) {
+    let mut map = ChunkMap::new(256);
+    for x in -128..128 {
+        map.insert_overwrite(IVec2::new(x, -64), Entity::PLACEHOLDER);
+    }
+    let particles: Vec<IVec2> = (-63..=64)
+        .flat_map(|y| (-64..64).map(move |x| IVec2::new(x, y)))
+        .enumerate()
+        .filter(|(i, _)| i % 10 != 0)
+        .map(|(_, cell)| cell)
+        .collect();
+    for (i, cell) in particles.iter().enumerate() {
+        map.insert_overwrite(*cell, Entity::from_raw(i as u32));
+    }
+    (map, particles)
+}
+
+/// Moves every particle into the first empty candidate cell, if any.
+fn tick(map: &mut ChunkMap, particles: &mut [IVec2], is_empty: impl Fn(&ChunkMap, IVec2) -> bool) {
+    for particle in particles.iter_mut() {
+        let target = CANDIDATES
+            .iter()
+            .map(|offset| *particle + *offset)
+            .find(|target| map.contains(*target) && is_empty(map, *target));
+        if let Some(target) = target {
+            map.swap(*particle, target);
+            *particle = target;
+        }
+    }
+    map.reset_chunks(1);
+}
+
+fn dense_movement_tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dense_movement_tick");
+
+    group.bench_function("bitset", |b| {
+        let (mut map, mut particles) = dense_field();
+        b.iter(|| tick(&mut map, &mut particles, |map, cell| !map.is_occupied(cell)));
+    });
+
+    group.bench_function("hashmap", |b| {
+        let (mut map, mut particles) = dense_field();
+        b.iter(|| {
+            tick(&mut map, &mut particles, |map, cell| {
+                map.entity(&cell).is_none()
+            })
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, dense_movement_tick);
+criterion_main!(benches);
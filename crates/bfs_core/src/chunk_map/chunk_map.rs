@@ -2,10 +2,12 @@ use ahash::{HashMap, HashMapExt};
 use bevy::prelude::*;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::prelude::*;
+use std::marker::PhantomData;
+use thiserror::Error;
 
 use crate::{
-    Coordinates, Particle, ParticleSimulationSet, ParticleType, ParticleTypeMap,
-    RemoveParticleEvent, SimulationRun,
+    clear_neighbor_cache, Coordinates, NeighborCache, Particle, ParticleSimulation,
+    ParticleSimulationSet, ParticleType, ParticleTypeMap, RemoveParticleEvent, Submerged,
 };
 
 pub struct ChunkMapPlugin;
@@ -13,12 +15,19 @@ pub struct ChunkMapPlugin;
 impl Plugin for ChunkMapPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
-            reset_chunks
+            ParticleSimulation,
+            reset_chunks.after(ParticleSimulationSet),
+        )
+        .add_systems(
+            ParticleSimulation,
+            clear_neighbor_cache
                 .after(ParticleSimulationSet)
-                .run_if(resource_exists::<SimulationRun>),
+                .run_if(resource_exists::<NeighborCache>),
         )
         .add_event::<ClearMapEvent>()
+        .add_event::<ChunkWokeEvent>()
+        .init_resource::<HibernationThreshold>()
+        .register_type::<HibernationThreshold>()
         .add_event::<ClearParticleTypeChildrenEvent>()
         .init_resource::<ChunkMap>()
         .add_observer(on_remove_particle)
@@ -27,58 +36,254 @@ impl Plugin for ChunkMapPlugin {
     }
 }
 
+/// Offsets of the 8 cells surrounding a position, in clockwise order starting at the top-left.
+pub const NEIGHBOR_OFFSETS: [IVec2; 8] = [
+    IVec2::new(-1, 1),
+    IVec2::new(0, 1),
+    IVec2::new(1, 1),
+    IVec2::new(1, 0),
+    IVec2::new(1, -1),
+    IVec2::new(0, -1),
+    IVec2::new(-1, -1),
+    IVec2::new(-1, 0),
+];
+
+/// Side length (in cells) of a chunk. Chunks track occupancy in one `u32` per row, so this is
+/// fixed.
+const CHUNK_SIZE: i32 = 32;
+const CHUNK_SHIFT: i32 = CHUNK_SIZE.trailing_zeros() as i32;
+
+/// The index of the chunk holding `coord` in a map `map_size` cells wide, or `None` if it lies
+/// outside of the map.
+fn chunk_index(map_size: usize, coord: &IVec2) -> Option<usize> {
+    let offset = map_size as i32 / 2;
+    let grid_width = map_size as i32 >> CHUNK_SHIFT;
+
+    let col = coord.x.saturating_add(offset) >> CHUNK_SHIFT;
+    let row = offset.saturating_sub(coord.y) >> CHUNK_SHIFT;
+
+    // Checking both axes keeps cells past one edge from wrapping around into another row.
+    ((0..grid_width).contains(&col) && (0..grid_width).contains(&row))
+        .then(|| (row * grid_width + col) as usize)
+}
+
+/// Why [`ChunkMap::try_swap`] refused to swap two positions.
+#[derive(Debug, Error, Copy, Clone, PartialEq, Eq)]
+pub enum SwapError {
+    #[error("Cannot swap particles: position {0} is outside of the map")]
+    FirstOutOfBounds(IVec2),
+    #[error("Cannot swap particles: position {0} is outside of the map")]
+    SecondOutOfBounds(IVec2),
+    #[error("Cannot swap particles: neither {0} nor {1} is occupied")]
+    BothEmpty(IVec2, IVec2),
+}
+
+/// How many consecutive simulation steps a chunk must go without changes before it hibernates.
+///
+/// Particles in hibernating chunks aren't simulated until something changes in or next to the
+/// chunk. Higher values keep settling regions awake a little longer, lower values save work
+/// sooner. Values below 1 behave like 1.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct HibernationThreshold(pub u32);
+
+impl Default for HibernationThreshold {
+    fn default() -> HibernationThreshold {
+        HibernationThreshold(1)
+    }
+}
+
+/// Sent when a hibernating chunk wakes up because something changed in or next to it. Holds the
+/// chunk's index into [`ChunkMap::chunks`].
+#[derive(Event, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ChunkWokeEvent(pub usize);
+
 #[derive(Resource, Debug, Clone)]
 pub struct ChunkMap {
     pub chunks: Vec<Chunk>,
+    generation: u64,
+    map_size: usize,
 }
 
 impl Default for ChunkMap {
     fn default() -> ChunkMap {
-        let chunks: Vec<Chunk> = (0..32_i32.pow(2))
+        ChunkMap::new(1024)
+    }
+}
+
+impl ChunkMap {
+    /// Creates an empty map `map_size` cells wide and tall, roughly centered on the origin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `map_size` isn't a power of two at least as large as a chunk (32 cells).
+    pub fn new(map_size: usize) -> ChunkMap {
+        ChunkMap {
+            chunks: ChunkMap::build_chunks(map_size),
+            generation: 0,
+            map_size,
+        }
+    }
+
+    fn build_chunks(map_size: usize) -> Vec<Chunk> {
+        assert!(
+            map_size.is_power_of_two() && map_size >= CHUNK_SIZE as usize,
+            "map size must be a power of two of at least {CHUNK_SIZE}, got {map_size}"
+        );
+        let half = map_size as i32 / 2;
+        let grid_width = map_size as i32 >> CHUNK_SHIFT;
+        (0..grid_width.pow(2))
             .map(|i| {
-                let x = (i % 32) * 32 - 512;
-                let y = 512 - (i / 32) * 32;
-                let upper_left = IVec2::new(x, y - 31);
-                let lower_right = IVec2::new(x + 31, y);
+                let x = (i % grid_width) * CHUNK_SIZE - half;
+                let y = half - (i / grid_width) * CHUNK_SIZE;
+                let upper_left = IVec2::new(x, y - (CHUNK_SIZE - 1));
+                let lower_right = IVec2::new(x + (CHUNK_SIZE - 1), y);
                 Chunk::new(upper_left, lower_right)
             })
-            .collect();
+            .collect()
+    }
 
-        ChunkMap { chunks }
+    /// The width and height of the map, in cells.
+    pub fn map_size(&self) -> usize {
+        self.map_size
     }
-}
 
-impl ChunkMap {
-    fn chunk_index(&self, coord: &IVec2) -> usize {
-        const OFFSET: i32 = 512;
-        const GRID_WIDTH: usize = 32;
+    /// Rebuilds the map to be `new_map_size` cells wide and tall, keeping every particle that
+    /// still fits, including secondary occupants. Returns the entities of particles that fell
+    /// outside the new bounds, which are no longer in the map and should be despawned by the
+    /// caller.
+    ///
+    /// Chunks start out awake with no dirty rects, as after a fresh insert.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_map_size` isn't a power of two at least as large as a chunk (32 cells).
+    pub fn resize(&mut self, new_map_size: usize) -> Vec<Entity> {
+        let chunks = ChunkMap::build_chunks(new_map_size);
+        let particles: Vec<(IVec2, Entity)> = self.iter_particles().collect();
+        let secondary: Vec<(IVec2, Entity)> = self.iter_secondary().collect();
+        self.chunks = chunks;
+        self.map_size = new_map_size;
+        self.generation = self.generation.wrapping_add(1);
+
+        let mut overflow = Vec::new();
+        for (coordinates, entity) in particles {
+            if self.contains(coordinates) {
+                self.insert_overwrite(coordinates, entity);
+            } else {
+                overflow.push(entity);
+            }
+        }
+        for (coordinates, entity) in secondary {
+            if self.contains(coordinates) {
+                self.insert_secondary(coordinates, entity);
+            } else {
+                overflow.push(entity);
+            }
+        }
+        overflow
+    }
+
+    /// Whether `coords` lies within the map.
+    pub fn contains(&self, coords: IVec2) -> bool {
+        self.chunk(&coords)
+            .is_some_and(|chunk| chunk.region().contains(coords))
+    }
 
-        let col = ((coord.x + OFFSET) >> 5) as usize;
-        let row = ((OFFSET - coord.y) >> 5) as usize;
+    fn grid_width(&self) -> usize {
+        self.map_size >> CHUNK_SHIFT
+    }
 
-        row * GRID_WIDTH + col
+    /// The index into [`ChunkMap::chunks`] of the chunk holding `coord`, or `None` if it lies
+    /// outside of the map.
+    pub fn chunk_index(&self, coord: &IVec2) -> Option<usize> {
+        chunk_index(self.map_size, coord)
     }
 
     pub fn chunk(&self, coord: &IVec2) -> Option<&Chunk> {
-        let index = self.chunk_index(coord);
-        self.chunks.get(index)
+        self.chunk_index(coord).map(|index| &self.chunks[index])
     }
 
     fn chunk_mut(&mut self, coord: &IVec2) -> Option<&mut Chunk> {
-        let index = self.chunk_index(coord);
-        self.chunks.get_mut(index)
+        self.chunk_index(coord).map(|index| &mut self.chunks[index])
+    }
+
+    /// A counter that changes every time the whole map is cleared or rebuilt. Changes to
+    /// individual cells are tracked per chunk by [`Chunk::generation`].
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The inclusive range of cell coordinates covered by the map.
+    ///
+    /// The map is roughly centered on the origin, with x increasing to the right and y increasing
+    /// upward. The default 1024 cell map spans `-512..=511` horizontally and `-511..=512`
+    /// vertically.
+    pub fn world_bounds_i(&self) -> IRect {
+        self.chunks
+            .iter()
+            .map(|chunk| chunk.region())
+            .reduce(|bounds, region| bounds.union(region))
+            .unwrap_or_default()
+    }
+
+    /// The world-space area covered by the map. Each cell is one unit wide and centered on its
+    /// coordinates, so this extends half a unit past [`ChunkMap::world_bounds_i`] on every side.
+    pub fn world_bounds(&self) -> Rect {
+        let bounds = self.world_bounds_i();
+        Rect::from_corners(
+            bounds.min.as_vec2() - Vec2::splat(0.5),
+            bounds.max.as_vec2() + Vec2::splat(0.5),
+        )
     }
 }
 
 impl ChunkMap {
     pub fn clear(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
         for map in &mut self.chunks {
             map.clear();
         }
     }
 
+    /// Removes the particle at `coords` from the map, returning its entity. Cells outside of the
+    /// map are empty, so this returns `None` for them, e.g. for particles dropped by
+    /// [`ChunkMap::resize`].
     pub fn remove(&mut self, coords: &IVec2) -> Option<Entity> {
-        self.chunk_mut(&coords).unwrap().remove(coords)
+        self.chunk_mut(coords)?.remove(coords)
+    }
+}
+
+impl ChunkMap {
+    /// The secondary occupant of `coords`, if any.
+    ///
+    /// A cell can hold a second particle alongside its occupant, such as a gas bubble inside a
+    /// liquid. Secondary occupants aren't part of the cell's occupancy: `entity`, `is_occupied`,
+    /// `neighbors` and the iterators over particles only report the occupant. See [`Submerged`].
+    pub fn secondary(&self, coords: &IVec2) -> Option<&Entity> {
+        self.chunk(coords).and_then(|chunk| chunk.secondary(coords))
+    }
+
+    /// Stores `entity` as the secondary occupant of `coords`, returning the one it replaced. Does
+    /// nothing and returns `None` if `coords` lies outside of the map.
+    pub fn insert_secondary(&mut self, coords: IVec2, entity: Entity) -> Option<Entity> {
+        self.chunk_mut(&coords)?.insert_secondary(coords, entity)
+    }
+
+    /// Removes the secondary occupant of `coords`, returning its entity.
+    pub fn remove_secondary(&mut self, coords: &IVec2) -> Option<Entity> {
+        self.chunk_mut(coords)?.remove_secondary(coords)
+    }
+
+    /// Lazily iterates over every secondary occupant in the map, yielding its coordinates and
+    /// entity.
+    pub fn iter_secondary(&self) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        self.chunks.iter().flat_map(|chunk| {
+            chunk
+                .secondary
+                .iter()
+                .map(|(coordinates, entity)| (*coordinates, *entity))
+        })
     }
 }
 
@@ -90,59 +295,220 @@ impl ChunkMap {
     pub fn iter_chunks_mut(&mut self) -> impl Iterator<Item = &mut Chunk> {
         self.chunks.iter_mut()
     }
+
+    /// Splits the map into four checkerboard phases, calling `f` once per phase with a
+    /// [`ChunkView`] of every chunk in it.
+    ///
+    /// Chunks of the same phase are never next to each other, so their views can be used
+    /// concurrently, e.g. on the `ComputeTaskPool`. Phases are visited in the same order every
+    /// time, and each chunk's view comes after the views of the chunks before it in
+    /// [`ChunkMap::chunks`].
+    pub fn for_each_checkerboard_phase(&mut self, mut f: impl FnMut(Vec<ChunkView<'_>>)) {
+        let grid_width = self.grid_width();
+        let chunk_count = self.chunks.len();
+        let chunks = self.chunks.as_mut_ptr();
+        for phase in 0..4 {
+            let views = (0..chunk_count)
+                .filter(|index| index % grid_width % 2 + index / grid_width % 2 * 2 == phase)
+                .map(|index| ChunkView {
+                    chunks,
+                    chunk_count,
+                    map_size: self.map_size,
+                    index,
+                    _map: PhantomData,
+                })
+                .collect();
+            f(views);
+        }
+    }
+}
+
+/// One chunk of a [`ChunkMap`], split off by [`ChunkMap::for_each_checkerboard_phase`] so it can
+/// be changed alongside the other chunks of its phase.
+///
+/// A view reads cells of its own chunk and of the 8 chunks around it, which no other view of the
+/// phase changes. It only changes cells of its own chunk that aren't on the chunk's edge, so that
+/// nothing it does wakes a neighboring chunk. Reads or changes outside of those cells panic.
+pub struct ChunkView<'a> {
+    chunks: *mut Chunk,
+    chunk_count: usize,
+    map_size: usize,
+    index: usize,
+    _map: PhantomData<&'a mut ChunkMap>,
+}
+
+// SAFETY: Views of the same phase only write to their own chunk, and only read chunks that none
+// of the others write to. `ChunkView::chunk` and `ChunkView::chunk_mut` enforce both.
+unsafe impl Send for ChunkView<'_> {}
+
+impl ChunkView<'_> {
+    /// The index into [`ChunkMap::chunks`] of the chunk this view changes.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The cells this view can change: its chunk, less the cells on the chunk's edge.
+    pub fn writable_region(&self) -> IRect {
+        let region = self.own_chunk().region();
+        IRect::from_corners(region.min + IVec2::ONE, region.max - IVec2::ONE)
+    }
+
+    /// The chunk holding `coord`, or `None` if it lies outside of the map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chunk is neither this view's chunk nor one next to it.
+    pub fn chunk(&self, coord: &IVec2) -> Option<&Chunk> {
+        let index = chunk_index(self.map_size, coord)?;
+        let grid_width = self.map_size >> CHUNK_SHIFT;
+        let (col, row) = (index % grid_width, index / grid_width);
+        let (own_col, own_row) = (self.index % grid_width, self.index / grid_width);
+        assert!(
+            col.abs_diff(own_col) <= 1 && row.abs_diff(own_row) <= 1,
+            "{coord} lies outside of the chunks readable from chunk {}",
+            self.index
+        );
+        debug_assert!(index < self.chunk_count);
+        // SAFETY: See `ChunkView`. Only the views' own chunks are written to during a phase.
+        Some(unsafe { &*self.chunks.add(index) })
+    }
+
+    fn own_chunk(&self) -> &Chunk {
+        // SAFETY: See `ChunkView`.
+        unsafe { &*self.chunks.add(self.index) }
+    }
+
+    /// This view's chunk, after checking that every one of `coords` is writable.
+    fn chunk_mut(&mut self, coords: &[IVec2]) -> &mut Chunk {
+        let writable = self.writable_region();
+        for coord in coords {
+            assert!(
+                writable.contains(*coord),
+                "{coord} lies outside of the cells writable from chunk {}",
+                self.index
+            );
+        }
+        // SAFETY: See `ChunkView`. `&mut self` keeps this the only reference into the chunk.
+        unsafe { &mut *self.chunks.add(self.index) }
+    }
+
+    /// Whether `coords` lies within the map.
+    pub fn contains(&self, coords: IVec2) -> bool {
+        self.chunk(&coords)
+            .is_some_and(|chunk| chunk.region().contains(coords))
+    }
+
+    /// The entity occupying `coords`, if any. Cells outside of the map are reported as empty.
+    pub fn entity(&self, coords: &IVec2) -> Option<&Entity> {
+        self.chunk(coords).and_then(|chunk| chunk.get(coords))
+    }
+
+    /// Whether a particle occupies `coords`. Cells outside of the map are reported as unoccupied.
+    pub fn is_occupied(&self, coords: IVec2) -> bool {
+        self.chunk(&coords)
+            .is_some_and(|chunk| chunk.is_occupied(coords))
+    }
+
+    /// Returns the entities occupying the 8 cells around `coords`, ordered as `NEIGHBOR_OFFSETS`.
+    pub fn neighbors(&self, coords: IVec2) -> [Option<Entity>; 8] {
+        NEIGHBOR_OFFSETS.map(|offset| self.entity(&(coords + offset)).copied())
+    }
+
+    /// Swaps the particle at `first` with whatever occupies `second`, like [`ChunkMap::swap`].
+    pub fn swap(&mut self, first: IVec2, second: IVec2) {
+        let chunk = self.chunk_mut(&[first, second]);
+        let entity_first = chunk.remove(&first).unwrap();
+        if let Some(entity_second) = chunk.remove(&second) {
+            chunk.insert_overwrite(first, entity_second);
+        }
+        chunk.insert_overwrite(second, entity_first);
+    }
+
+    /// Extends the dirty rect of this view's chunk to include `coords`, like
+    /// [`ChunkMap::mark_dirty`].
+    pub fn mark_dirty(&mut self, coords: IVec2) {
+        self.chunk_mut(&[coords]).extend_dirty_rect(coords);
+    }
 }
 
 impl ChunkMap {
-    pub fn reset_chunks(&mut self) {
-        for chunk in &mut self.chunks {
+    /// Rolls every chunk's dirty rect over to the next frame, putting chunks to sleep once they
+    /// have gone `hibernation_threshold` steps without changes. Returns the indices of the chunks
+    /// that woke up.
+    pub fn reset_chunks(&mut self, hibernation_threshold: u32) -> Vec<usize> {
+        let mut woken = Vec::new();
+        for (idx, chunk) in self.chunks.iter_mut().enumerate() {
             chunk.prev_dirty_rect = chunk.dirty_rect;
             chunk.dirty_rect = None;
+            chunk.changed_rect = None;
 
-            match (chunk.should_process_next_frame, chunk.hibernating) {
-                (true, true) => {
+            if chunk.should_process_next_frame {
+                chunk.idle_steps = 0;
+                if chunk.hibernating {
                     chunk.hibernating = false;
+                    woken.push(idx);
                 }
-                (false, false) => {
+            } else {
+                chunk.idle_steps = chunk.idle_steps.saturating_add(1);
+                if chunk.idle_steps >= hibernation_threshold {
                     chunk.hibernating = true;
                 }
-                _ => {}
             }
 
             chunk.should_process_next_frame = false;
         }
+        woken
     }
 
     fn activate_neighbor_chunks(&mut self, coord: &IVec2, chunk_idx: usize) {
         let chunk = &self.chunks[chunk_idx];
+        let grid_width = self.grid_width();
+        // Chunks on the edge of the map have no neighbor on that side.
+        let col = chunk_idx % grid_width;
+        let (has_left, has_right) = (col > 0, col + 1 < grid_width);
+        let left = chunk_idx.wrapping_sub(1);
+        let top = chunk_idx.wrapping_sub(grid_width);
         let neighbors = [
-            (coord.x == chunk.min().x, chunk_idx - 1),  // Left neighbor
-            (coord.x == chunk.max().x, chunk_idx + 1),  // Right neighbor
-            (coord.y == chunk.min().y, chunk_idx + 32), // Bottom neighbor
-            (coord.y == chunk.max().y, chunk_idx - 32), // Top neighbor
+            (coord.x == chunk.min().x && has_left, left), // Left neighbor
+            (coord.x == chunk.max().x && has_right, chunk_idx + 1), // Right neighbor
+            (coord.y == chunk.min().y, chunk_idx + grid_width), // Bottom neighbor
+            (coord.y == chunk.max().y, top),              // Top neighbor
         ];
 
         for (condition, neighbor_idx) in neighbors.iter() {
             if *condition {
-                self.chunks[*neighbor_idx].should_process_next_frame = true;
+                if let Some(neighbor) = self.chunks.get_mut(*neighbor_idx) {
+                    neighbor.should_process_next_frame = true;
+                }
             }
         }
     }
 }
 
 impl ChunkMap {
-    pub fn insert_no_overwrite(&mut self, coords: IVec2, entity: Entity) -> &mut Entity {
-        let chunk = self.chunk_mut(&coords).unwrap();
-        chunk.insert_no_overwrite(coords, entity)
+    /// Inserts `entity` at `coords` unless the cell is already occupied, and returns the entity
+    /// that ends up occupying it. Returns `None` without inserting if `coords` lies outside of the
+    /// map.
+    pub fn insert_no_overwrite(&mut self, coords: IVec2, entity: Entity) -> Option<&mut Entity> {
+        let chunk = self.chunk_mut(&coords)?;
+        Some(chunk.insert_no_overwrite(coords, entity))
     }
 
+    /// Inserts `entity` at `coords`, returning the entity it replaced. Does nothing and returns
+    /// `None` if `coords` lies outside of the map.
     pub fn insert_overwrite(&mut self, coords: IVec2, entity: Entity) -> Option<Entity> {
-        let chunk = self.chunk_mut(&coords).unwrap();
+        let chunk = self.chunk_mut(&coords)?;
         chunk.insert_overwrite(coords, entity)
     }
 
+    /// Swaps the particle at `first` with whatever occupies `second`. Does nothing if either
+    /// position lies outside of the map.
     pub fn swap(&mut self, first: IVec2, second: IVec2) {
-        let first_chunk_idx = self.chunk_index(&first);
-        let second_chunk_idx = self.chunk_index(&second);
+        let (Some(first_chunk_idx), Some(second_chunk_idx)) =
+            (self.chunk_index(&first), self.chunk_index(&second))
+        else {
+            return;
+        };
 
         // Short-circuit if both positions are in the same chunk to save ourselves a hashmap lookup.
         if first_chunk_idx == second_chunk_idx {
@@ -169,8 +535,93 @@ impl ChunkMap {
         self.activate_neighbor_chunks(&second, second_chunk_idx);
     }
 
+    /// Swaps whatever occupies `first` and `second`, moving a particle into an empty cell if only
+    /// one of them is occupied.
+    ///
+    /// Unlike `swap`, this checks that both positions are within the map and that at least one of
+    /// them is occupied rather than panicking. Both positions end up in their chunks' dirty rects.
+    pub fn try_swap(&mut self, first: IVec2, second: IVec2) -> Result<(), SwapError> {
+        if !self.contains(first) {
+            return Err(SwapError::FirstOutOfBounds(first));
+        }
+        if !self.contains(second) {
+            return Err(SwapError::SecondOutOfBounds(second));
+        }
+        // `swap` expects `first` to be occupied.
+        match (self.is_occupied(first), self.is_occupied(second)) {
+            (true, _) => self.swap(first, second),
+            (false, true) => self.swap(second, first),
+            (false, false) => return Err(SwapError::BothEmpty(first, second)),
+        }
+        // An emptied cell isn't otherwise added to its chunk's dirty rect.
+        for coords in [first, second] {
+            self.chunk_mut(&coords).unwrap().extend_dirty_rect(coords);
+        }
+        Ok(())
+    }
+
+    /// The entity occupying `coords`, if any. Cells outside of the map are reported as empty.
     pub fn entity(&self, coords: &IVec2) -> Option<&Entity> {
-        self.chunk(&coords).unwrap().get(coords)
+        self.chunk(coords).and_then(|chunk| chunk.get(coords))
+    }
+
+    /// Whether a particle occupies `coords`. This is a bit test rather than a hash lookup, so
+    /// prefer it over `entity` when the entity itself isn't needed. Cells outside of the map are
+    /// reported as unoccupied.
+    pub fn is_occupied(&self, coords: IVec2) -> bool {
+        self.chunk(&coords)
+            .is_some_and(|chunk| chunk.is_occupied(coords))
+    }
+
+    /// Returns the entities occupying the 8 cells around `coords`, ordered as `NEIGHBOR_OFFSETS`.
+    /// Cells outside of the map are reported as empty.
+    pub fn neighbors(&self, coords: IVec2) -> [Option<Entity>; 8] {
+        NEIGHBOR_OFFSETS.map(|offset| {
+            let neighbor = coords + offset;
+            self.chunk(&neighbor)
+                .and_then(|chunk| chunk.get(&neighbor))
+                .copied()
+        })
+    }
+
+    /// Returns the empty cell closest to `center` by Euclidean distance, or `None` if every cell
+    /// within `max_radius` is occupied or outside the map.
+    ///
+    /// The search covers the full square of cells up to `max_radius` away on either axis, so a
+    /// corner cell beyond the Euclidean radius, such as `center + IVec2::splat(max_radius)`, can be
+    /// returned when everything closer is occupied. Among cells at the same distance, the one
+    /// fewer cells away on its farthest axis wins, then the one with the lowest y, then the lowest
+    /// x.
+    pub fn nearest_empty(&self, center: IVec2, max_radius: i32) -> Option<IVec2> {
+        let is_empty = |coords: IVec2| {
+            self.chunk(&coords).is_some_and(|chunk| {
+                chunk.region().contains(coords) && !chunk.is_occupied(coords)
+            })
+        };
+
+        let mut nearest: Option<(IVec2, i32)> = None;
+        for radius in 0..=max_radius.max(0) {
+            // Every cell in this ring is at least `radius` away, so nothing further out can beat
+            // what we've already found.
+            if nearest.is_some_and(|(_, distance)| radius * radius > distance) {
+                break;
+            }
+            for y in -radius..=radius {
+                // Interior rows only contribute their two edge cells.
+                let step = if y.abs() == radius { 1 } else { (2 * radius).max(1) };
+                for x in (-radius..=radius).step_by(step as usize) {
+                    let offset = IVec2::new(x, y);
+                    let distance = offset.length_squared();
+                    if nearest.is_some_and(|(_, nearest)| distance >= nearest) {
+                        continue;
+                    }
+                    if is_empty(center + offset) {
+                        nearest = Some((center + offset, distance));
+                    }
+                }
+            }
+        }
+        nearest.map(|(coords, _)| coords)
     }
 
     #[allow(unused)]
@@ -178,10 +629,77 @@ impl ChunkMap {
         self.chunks.iter().flat_map(|chunk| chunk.iter())
     }
 
+    /// Lazily iterates over every occupied cell in the map, yielding its coordinates and entity.
+    ///
+    /// Particles are visited chunk by chunk; the order of cells within a chunk is unspecified.
+    pub fn iter_particles(&self) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        self.iter().map(|(coordinates, entity)| (*coordinates, *entity))
+    }
+
+    /// Lazily iterates over every occupied cell between the corners `min` and `max` (inclusive),
+    /// yielding its coordinates and entity.
+    ///
+    /// Only chunks overlapping the rectangle are visited. Parts of the rectangle outside of the
+    /// map are ignored.
+    pub fn iter_region(
+        &self,
+        min: IVec2,
+        max: IVec2,
+    ) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        let region = IRect::from_corners(min, max);
+        self.chunks_in_region(region).flat_map(move |chunk| {
+            let contained = region.union(chunk.region()) == region;
+            chunk
+                .iter()
+                .filter(move |(coordinates, _)| contained || region.contains(**coordinates))
+                .map(|(coordinates, entity)| (*coordinates, *entity))
+        })
+    }
+
+    /// The number of occupied cells between the corners `min` and `max` (inclusive).
+    pub fn count_region(&self, min: IVec2, max: IVec2) -> usize {
+        let region = IRect::from_corners(min, max);
+        self.chunks_in_region(region)
+            .map(|chunk| chunk.count_occupied(region))
+            .sum()
+    }
+
+    fn chunks_in_region(&self, region: IRect) -> impl Iterator<Item = &Chunk> {
+        self.chunks
+            .iter()
+            .filter(move |chunk| chunk.intersects(region))
+    }
+
     pub fn par_iter(&self) -> impl IntoParallelIterator<Item = (&IVec2, &Entity)> {
         self.chunks.par_iter().flat_map(|chunk| chunk.par_iter())
     }
 
+    /// The smallest rectangle containing every chunk's dirty rect, or `None` if nothing is dirty.
+    pub fn combined_dirty_bounds(&self) -> Option<IRect> {
+        self.iter_chunks()
+            .filter_map(|chunk| chunk.dirty_rect())
+            .reduce(|bounds, dirty_rect| bounds.union(dirty_rect))
+    }
+
+    /// Whether `coords` lies within its chunk's dirty rect for this frame. Cells outside of the map
+    /// are never dirty.
+    pub fn is_dirty(&self, coords: IVec2) -> bool {
+        self.chunk(&coords)
+            .and_then(|chunk| chunk.dirty_rect())
+            .is_some_and(|dirty_rect| dirty_rect.contains(coords))
+    }
+
+    /// Extends the dirty rect of the chunk owning `coords` to include it, so the particle there is
+    /// simulated next frame. Neighboring chunks are woken if `coords` is on a chunk edge. Use this
+    /// after changing a particle outside of the simulation. Cells outside of the map are ignored.
+    pub fn mark_dirty(&mut self, coords: IVec2) {
+        let Some(chunk_idx) = self.chunk_index(&coords) else {
+            return;
+        };
+        self.chunks[chunk_idx].extend_dirty_rect(coords);
+        self.activate_neighbor_chunks(&coords, chunk_idx);
+    }
+
     pub fn should_process_this_frame(&self, coords: &IVec2) -> bool {
         if let Some(chunk) = self.chunk(coords) {
             if chunk.hibernating() == true {
@@ -197,22 +715,38 @@ impl ChunkMap {
 #[derive(Debug, Clone)]
 pub struct Chunk {
     chunk: HashMap<IVec2, Entity>,
+    /// One row of occupancy bits per y, mirroring the keys of `chunk`.
+    occupancy: [u32; 32],
     region: IRect,
     dirty_rect: Option<IRect>,
     prev_dirty_rect: Option<IRect>,
     should_process_next_frame: bool,
     hibernating: bool,
+    /// Consecutive steps without changes, for [`HibernationThreshold`].
+    idle_steps: u32,
+    /// Advanced every time a cell of this chunk is inserted, removed or cleared.
+    generation: u64,
+    /// Every cell inserted or removed this step. Unlike the dirty rect, this includes cells that
+    /// were emptied.
+    changed_rect: Option<IRect>,
+    /// Particles sharing a cell with its occupant. See [`ChunkMap::secondary`].
+    secondary: HashMap<IVec2, Entity>,
 }
 
 impl Chunk {
     pub fn new(upper_left: IVec2, lower_right: IVec2) -> Chunk {
         Chunk {
             chunk: HashMap::with_capacity(1024),
+            occupancy: [0; 32],
             region: IRect::from_corners(upper_left, lower_right),
             dirty_rect: None,
             prev_dirty_rect: None,
             should_process_next_frame: false,
             hibernating: false,
+            idle_steps: 0,
+            generation: 0,
+            changed_rect: None,
+            secondary: HashMap::new(),
         }
     }
 }
@@ -241,6 +775,49 @@ impl Chunk {
     pub fn get(&self, coords: &IVec2) -> Option<&Entity> {
         self.chunk.get(coords)
     }
+
+    /// Whether a particle occupies `coords`. Coordinates outside of this chunk are reported as
+    /// unoccupied.
+    pub fn is_occupied(&self, coords: IVec2) -> bool {
+        self.occupancy_bit(coords)
+            .is_some_and(|(row, bit)| self.occupancy[row] & bit != 0)
+    }
+
+    fn occupancy_bit(&self, coords: IVec2) -> Option<(usize, u32)> {
+        if !self.region.contains(coords) {
+            return None;
+        }
+        let local = coords - self.region.min;
+        Some((local.y as usize, 1 << local.x))
+    }
+
+    /// The number of occupied cells of this chunk that lie within `region`.
+    fn count_occupied(&self, region: IRect) -> usize {
+        // `IRect::intersect(..).is_empty()` would treat 1-wide overlaps as empty, but both
+        // rectangles are inclusive here.
+        if !self.intersects(region) {
+            return 0;
+        }
+        let local = IRect::from_corners(
+            self.region.min.max(region.min) - self.region.min,
+            self.region.max.min(region.max) - self.region.min,
+        );
+        let mask = (u32::MAX >> (31 - local.max.x)) & (u32::MAX << local.min.x);
+        self.occupancy[local.min.y as usize..=local.max.y as usize]
+            .iter()
+            .map(|row| (row & mask).count_ones() as usize)
+            .sum()
+    }
+
+    fn set_occupied(&mut self, coords: IVec2, occupied: bool) {
+        if let Some((row, bit)) = self.occupancy_bit(coords) {
+            if occupied {
+                self.occupancy[row] |= bit;
+            } else {
+                self.occupancy[row] &= !bit;
+            }
+        }
+    }
 }
 
 impl Chunk {
@@ -259,36 +836,63 @@ impl Chunk {
 
 impl Chunk {
     pub fn clear(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        self.changed_rect = Some(self.region);
         self.chunk.clear();
+        self.occupancy = [0; 32];
+        self.secondary.clear();
     }
 
     pub fn remove(&mut self, coords: &IVec2) -> Option<Entity> {
         self.should_process_next_frame = true;
+        self.record_change(*coords);
+        self.set_occupied(*coords, false);
         self.chunk.remove(coords)
     }
 
     pub fn insert_no_overwrite(&mut self, coords: IVec2, entity: Entity) -> &mut Entity {
         // Extend the dirty rect to include the newly added particle
-        self.should_process_next_frame = true;
-        if let Some(dirty_rect) = self.dirty_rect {
-            self.dirty_rect = Some(dirty_rect.union_point(coords));
-        } else {
-            self.dirty_rect = Some(IRect::from_center_size(coords, IVec2::ONE));
-        }
-
+        self.extend_dirty_rect(coords);
+        self.record_change(coords);
+        self.set_occupied(coords, true);
         self.chunk.entry(coords).or_insert(entity)
     }
 
     pub fn insert_overwrite(&mut self, coords: IVec2, entity: Entity) -> Option<Entity> {
-        self.should_process_next_frame = true;
         // Extend the dirty rect to include the newly added particle
+        self.extend_dirty_rect(coords);
+        self.record_change(coords);
+        self.set_occupied(coords, true);
+        self.chunk.insert(coords, entity)
+    }
+
+    pub fn secondary(&self, coords: &IVec2) -> Option<&Entity> {
+        self.secondary.get(coords)
+    }
+
+    pub fn insert_secondary(&mut self, coords: IVec2, entity: Entity) -> Option<Entity> {
+        self.secondary.insert(coords, entity)
+    }
+
+    pub fn remove_secondary(&mut self, coords: &IVec2) -> Option<Entity> {
+        self.secondary.remove(coords)
+    }
+
+    fn record_change(&mut self, coords: IVec2) {
+        self.generation = self.generation.wrapping_add(1);
+        self.changed_rect = Some(match self.changed_rect {
+            Some(changed_rect) => changed_rect.union_point(coords),
+            None => IRect::from_center_size(coords, IVec2::ONE),
+        });
+    }
+
+    fn extend_dirty_rect(&mut self, coords: IVec2) {
+        self.should_process_next_frame = true;
         if let Some(dirty_rect) = self.dirty_rect {
             self.dirty_rect = Some(dirty_rect.union_point(coords));
         } else {
             self.dirty_rect = Some(IRect::from_center_size(coords, IVec2::ONE));
         }
-
-        self.chunk.insert(coords, entity)
     }
 }
 
@@ -301,13 +905,42 @@ impl Chunk {
         self.prev_dirty_rect
     }
 
+    /// The cells inserted into or removed from this chunk since the last simulation step,
+    /// including cells that were emptied.
+    pub fn changed_rect(&self) -> Option<IRect> {
+        self.changed_rect
+    }
+
+    /// A counter that changes every time a cell of this chunk is inserted, removed or cleared.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     pub fn empty(&self) -> bool {
         self.chunk.len() == 0
     }
+
+    pub fn len(&self) -> usize {
+        self.chunk.len()
+    }
+
+    pub fn region(&self) -> IRect {
+        self.region
+    }
+
+    /// Whether any cell of this chunk lies within `region`. Both are treated as inclusive.
+    pub fn intersects(&self, region: IRect) -> bool {
+        self.region.min.cmple(region.max).all() && self.region.max.cmpge(region.min).all()
+    }
 }
 
-pub fn reset_chunks(mut map: ResMut<ChunkMap>) {
-    map.reset_chunks();
+pub fn reset_chunks(
+    mut map: ResMut<ChunkMap>,
+    hibernation_threshold: Res<HibernationThreshold>,
+    mut ev_chunk_woke: EventWriter<ChunkWokeEvent>,
+) {
+    let woken = map.reset_chunks(hibernation_threshold.0);
+    ev_chunk_woke.send_batch(woken.into_iter().map(ChunkWokeEvent));
 }
 
 #[derive(Event)]
@@ -346,7 +979,7 @@ pub fn on_clear_chunk_map(
 pub fn on_clear_particle_type_children(
     trigger: Trigger<ClearParticleTypeChildrenEvent>,
     mut commands: Commands,
-    particle_query: Query<&Coordinates, With<Particle>>,
+    particle_query: Query<(Option<&Coordinates>, Option<&Submerged>), With<Particle>>,
     parent_query: Query<&Children, With<ParticleType>>,
 
     particle_parent_map: Res<ParticleTypeMap>,
@@ -356,8 +989,10 @@ pub fn on_clear_particle_type_children(
     if let Some(parent_entity) = particle_parent_map.get(&particle_type) {
         if let Ok(children) = parent_query.get(*parent_entity) {
             children.iter().for_each(|child_entity| {
-                if let Ok(coordinates) = particle_query.get(*child_entity) {
+                if let Ok((Some(coordinates), _)) = particle_query.get(*child_entity) {
                     map.remove(&coordinates.0);
+                } else if let Ok((_, Some(submerged))) = particle_query.get(*child_entity) {
+                    map.remove_secondary(&submerged.0);
                 } else {
                     // If this happens, something is seriously amiss.
                     error!("No child entity found for particle type '{particle_type}' while removing child from chunk map.")
@@ -369,3 +1004,437 @@ pub fn on_clear_particle_type_children(
         warn!("Ignoring particle type '{particle_type}': not found in particle type map.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn map_with(particles: &[IVec2]) -> ChunkMap {
+        let mut map = ChunkMap::new(128);
+        for (i, coordinates) in particles.iter().enumerate() {
+            map.insert_overwrite(*coordinates, Entity::from_raw(i as u32));
+        }
+        map
+    }
+
+    fn region_coordinates(map: &ChunkMap, min: IVec2, max: IVec2) -> Vec<IVec2> {
+        let mut coordinates: Vec<IVec2> = map
+            .iter_region(min, max)
+            .map(|(coordinates, _)| coordinates)
+            .collect();
+        coordinates.sort_by_key(|coordinates| (coordinates.x, coordinates.y));
+        coordinates
+    }
+
+    #[test]
+    fn region_single_cell() {
+        let map = map_with(&[IVec2::new(3, 4), IVec2::new(4, 4)]);
+        let cell = IVec2::new(3, 4);
+        assert_eq!(region_coordinates(&map, cell, cell), vec![cell]);
+        assert_eq!(map.count_region(cell, cell), 1);
+        assert_eq!(map.count_region(IVec2::new(5, 4), IVec2::new(5, 4)), 0);
+    }
+
+    #[test]
+    fn region_row_on_chunk_edge() {
+        // y = 0 is the top row of its chunks and x = -1/0 is a vertical chunk boundary.
+        let row: Vec<IVec2> = (-5..=5).map(|x| IVec2::new(x, 0)).collect();
+        let mut particles = row.clone();
+        particles.extend([IVec2::new(0, 1), IVec2::new(0, -1)]);
+        let map = map_with(&particles);
+
+        let (min, max) = (IVec2::new(-5, 0), IVec2::new(5, 0));
+        assert_eq!(region_coordinates(&map, min, max), row);
+        assert_eq!(map.count_region(min, max), row.len());
+
+        let column = (IVec2::new(0, -1), IVec2::new(0, 1));
+        assert_eq!(map.count_region(column.0, column.1), 3);
+        assert_eq!(region_coordinates(&map, column.0, column.1).len(), 3);
+    }
+
+    #[test]
+    fn region_crossing_chunks() {
+        let inside = [
+            IVec2::new(-40, -40),
+            IVec2::new(40, 40),
+            IVec2::new(-40, 40),
+            IVec2::new(40, -40),
+            IVec2::new(0, 0),
+            IVec2::new(-1, 1),
+        ];
+        let outside = [IVec2::new(-41, 0), IVec2::new(0, 41), IVec2::new(63, -63)];
+        let map = map_with(&[inside.as_slice(), outside.as_slice()].concat());
+
+        let (min, max) = (IVec2::new(-40, -40), IVec2::new(40, 40));
+        let mut expected = inside.to_vec();
+        expected.sort_by_key(|coordinates| (coordinates.x, coordinates.y));
+        assert_eq!(region_coordinates(&map, min, max), expected);
+        assert_eq!(map.count_region(min, max), inside.len());
+        // Corners may be given in either order.
+        assert_eq!(map.count_region(max, min), inside.len());
+    }
+
+    #[test]
+    fn growing_keeps_every_particle() {
+        // The corners of a 128 cell map, plus a cell on either side of the chunk edges at the
+        // origin.
+        let particles = [
+            IVec2::new(-64, -63),
+            IVec2::new(63, 64),
+            IVec2::new(-1, 0),
+            IVec2::new(0, 1),
+        ];
+        let mut map = map_with(&particles);
+
+        assert_eq!(map.resize(256), vec![]);
+        assert_eq!(map.map_size(), 256);
+        assert_eq!(map.world_bounds_i(), IRect::new(-128, -127, 127, 128));
+        for (i, coordinates) in particles.iter().enumerate() {
+            assert_eq!(map.entity(coordinates), Some(&Entity::from_raw(i as u32)));
+        }
+        assert_eq!(map.iter_particles().count(), particles.len());
+        map.insert_overwrite(IVec2::new(127, 128), Entity::from_raw(10));
+        assert!(map.is_occupied(IVec2::new(127, 128)));
+    }
+
+    #[test]
+    fn shrinking_returns_the_particles_that_no_longer_fit() {
+        // A 64 cell map spans -32..=31 horizontally and -31..=32 vertically.
+        let kept = [IVec2::new(-32, -31), IVec2::new(31, 32), IVec2::ZERO];
+        let dropped = [IVec2::new(32, 0), IVec2::new(0, -32), IVec2::new(-40, 40)];
+        let mut map = map_with(&[kept.as_slice(), dropped.as_slice()].concat());
+
+        let mut overflow = map.resize(64);
+        overflow.sort();
+        assert_eq!(overflow, (3..6).map(Entity::from_raw).collect::<Vec<_>>());
+        assert_eq!(map.map_size(), 64);
+        for (i, coordinates) in kept.iter().enumerate() {
+            assert_eq!(map.entity(coordinates), Some(&Entity::from_raw(i as u32)));
+        }
+        assert_eq!(map.iter_particles().count(), kept.len());
+        assert!(dropped
+            .iter()
+            .all(|coordinates| !map.contains(*coordinates)));
+    }
+
+    #[test]
+    fn secondary_occupants_stay_out_of_the_occupancy() {
+        let mut map = map_with(&[IVec2::ZERO]);
+        let (bubble, dropped) = (Entity::from_raw(10), Entity::from_raw(11));
+        assert_eq!(map.insert_secondary(IVec2::ZERO, bubble), None);
+        assert_eq!(map.insert_secondary(IVec2::new(50, 0), dropped), None);
+        assert_eq!(map.insert_secondary(IVec2::new(500, 0), dropped), None);
+
+        assert_eq!(map.secondary(&IVec2::ZERO), Some(&bubble));
+        assert_eq!(map.entity(&IVec2::ZERO), Some(&Entity::from_raw(0)));
+        assert!(!map.is_occupied(IVec2::new(50, 0)));
+        assert_eq!(map.iter_particles().count(), 1);
+        assert_eq!(map.secondary(&IVec2::new(500, 0)), None);
+
+        // Removing the occupant leaves the secondary occupant in place.
+        map.remove(&IVec2::ZERO);
+        assert_eq!(map.secondary(&IVec2::ZERO), Some(&bubble));
+
+        // A 64 cell map spans -32..=31 horizontally.
+        assert_eq!(map.resize(64), vec![dropped]);
+        assert_eq!(
+            map.iter_secondary().collect::<Vec<_>>(),
+            vec![(IVec2::ZERO, bubble)]
+        );
+        map.clear();
+        assert_eq!(map.iter_secondary().count(), 0);
+    }
+
+    #[test]
+    fn clearing_a_type_removes_its_secondary_occupants() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, crate::FallingSandCorePlugin::default()));
+        app.world_mut().spawn(ParticleType::new("Water"));
+        app.world_mut().spawn(ParticleType::new("Steam"));
+        app.update();
+        crate::testing::spawn(&mut app, "Water", [IVec2::ZERO]);
+        crate::testing::spawn(&mut app, "Steam", [IVec2::Y]);
+        app.update();
+
+        // Submerge the steam into the water's cell.
+        let steam = *app
+            .world()
+            .resource::<ChunkMap>()
+            .entity(&IVec2::Y)
+            .unwrap();
+        let mut map = app.world_mut().resource_mut::<ChunkMap>();
+        map.remove(&IVec2::Y);
+        map.insert_secondary(IVec2::ZERO, steam);
+        app.world_mut()
+            .entity_mut(steam)
+            .remove::<Coordinates>()
+            .insert(Submerged(IVec2::ZERO));
+
+        app.world_mut()
+            .trigger(ClearParticleTypeChildrenEvent("Steam".to_string()));
+        app.update();
+
+        let map = app.world().resource::<ChunkMap>();
+        assert_eq!(map.secondary(&IVec2::ZERO), None);
+        assert!(map.is_occupied(IVec2::ZERO));
+        assert!(app.world().get_entity(steam).is_err());
+    }
+
+    #[test]
+    fn particles_dropped_by_shrinking_can_still_be_removed() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, crate::FallingSandCorePlugin::default()));
+        app.insert_resource(ChunkMap::new(128));
+        app.world_mut().spawn(ParticleType::new("Sand"));
+        app.update();
+        crate::testing::spawn(
+            &mut app,
+            "Sand",
+            [IVec2::ZERO, IVec2::new(40, 0), IVec2::new(50, 0)],
+        );
+        app.update();
+
+        // A 64 cell map spans -32..=31 horizontally, so two of the particles no longer fit.
+        let overflow = app.world_mut().resource_mut::<ChunkMap>().resize(64);
+        assert_eq!(overflow.len(), 2);
+
+        app.world_mut().trigger(RemoveParticleEvent {
+            coordinates: IVec2::new(40, 0),
+            despawn: true,
+        });
+        app.world_mut()
+            .trigger(ClearParticleTypeChildrenEvent("Sand".to_string()));
+        app.update();
+
+        assert_eq!(crate::testing::positions(&mut app, "Sand"), vec![]);
+        assert_eq!(
+            app.world().resource::<ChunkMap>().iter_particles().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn nearest_empty_ranks_cells_by_euclidean_distance() {
+        // Everything up to three cells from the origin is occupied, except the corner at (3, 3).
+        let occupied: Vec<IVec2> = (-3..=3)
+            .flat_map(|y| (-3..=3).map(move |x| IVec2::new(x, y)))
+            .filter(|cell| *cell != IVec2::splat(3))
+            .collect();
+        let map = map_with(&occupied);
+
+        // The cells four cells straight out are closer than the corner. The first of those
+        // scanned, with the lowest y, wins.
+        assert_eq!(map.nearest_empty(IVec2::ZERO, 4), Some(IVec2::new(0, -4)));
+        // The search still covers the corners of the square.
+        assert_eq!(map.nearest_empty(IVec2::ZERO, 3), Some(IVec2::splat(3)));
+        assert_eq!(map.nearest_empty(IVec2::ZERO, 2), None);
+    }
+
+    #[test]
+    fn nearest_empty_breaks_ties_by_lowest_y_then_x() {
+        let mut map = map_with(&[IVec2::ZERO]);
+        assert_eq!(map.nearest_empty(IVec2::ZERO, 1), Some(IVec2::NEG_Y));
+
+        map.insert_overwrite(IVec2::NEG_Y, Entity::PLACEHOLDER);
+        assert_eq!(map.nearest_empty(IVec2::ZERO, 1), Some(IVec2::NEG_X));
+    }
+
+    #[test]
+    fn try_swap_rejects_invalid_positions() {
+        let (occupied, empty, outside) = (IVec2::ZERO, IVec2::X, IVec2::new(64, 0));
+        let mut map = map_with(&[occupied]);
+
+        assert_eq!(
+            map.try_swap(outside, occupied),
+            Err(SwapError::FirstOutOfBounds(outside))
+        );
+        assert_eq!(
+            map.try_swap(occupied, outside),
+            Err(SwapError::SecondOutOfBounds(outside))
+        );
+        assert_eq!(
+            map.try_swap(empty, IVec2::Y),
+            Err(SwapError::BothEmpty(empty, IVec2::Y))
+        );
+        assert_eq!(map.entity(&occupied), Some(&Entity::from_raw(0)));
+        assert_eq!(map.iter_particles().count(), 1);
+    }
+
+    #[test]
+    fn cells_outside_the_map_are_empty() {
+        // A 128 cell map spans -64..=63 horizontally and -63..=64 vertically.
+        let (min, max) = (IVec2::new(-64, -63), IVec2::new(63, 64));
+        let mut map = map_with(&[min, max]);
+        let outside = [
+            IVec2::new(min.x - 1, 0),
+            IVec2::new(max.x + 1, 0),
+            IVec2::new(0, min.y - 1),
+            IVec2::new(0, max.y + 1),
+            // Past the right edge of the top row, which used to wrap into the next row.
+            IVec2::new(max.x + 1, max.y),
+            min - 1,
+            max + 1,
+        ];
+
+        for coordinates in outside {
+            assert_eq!(map.entity(&coordinates), None);
+            map.swap(max, coordinates);
+        }
+        assert_eq!(map.entity(&min), Some(&Entity::from_raw(0)));
+        assert_eq!(map.entity(&max), Some(&Entity::from_raw(1)));
+    }
+
+    #[test]
+    fn try_swap_crosses_chunks() {
+        // x = -1/0 is a vertical chunk boundary and y = 0/1 a horizontal one.
+        let (a, b) = (IVec2::new(-1, 0), IVec2::ZERO);
+        let mut map = map_with(&[a, b]);
+        map.reset_chunks(1);
+
+        assert_eq!(map.try_swap(a, b), Ok(()));
+        assert_eq!(map.entity(&a), Some(&Entity::from_raw(1)));
+        assert_eq!(map.entity(&b), Some(&Entity::from_raw(0)));
+
+        // Only the second position is occupied, so its particle moves into the first.
+        let above = IVec2::new(0, 1);
+        assert_eq!(map.try_swap(above, b), Ok(()));
+        assert_eq!(map.entity(&above), Some(&Entity::from_raw(0)));
+        assert!(!map.is_occupied(b));
+        assert!([a, b, above]
+            .iter()
+            .all(|coordinates| map.is_dirty(*coordinates)));
+    }
+
+    #[test]
+    fn marked_cells_are_dirty() {
+        let mut map = ChunkMap::new(128);
+        let (cell, other) = (IVec2::new(5, -5), IVec2::new(20, -20));
+        assert!(!map.is_dirty(cell));
+
+        map.mark_dirty(cell);
+        let dirty_rect = map.chunk(&cell).unwrap().dirty_rect().unwrap();
+        assert!(dirty_rect.contains(cell));
+        assert!(map.is_dirty(cell));
+        assert!(!map.is_dirty(other));
+
+        // The dirty rect grows to cover every marked cell in the chunk.
+        map.mark_dirty(other);
+        assert!(map.is_dirty(IVec2::new(10, -10)));
+
+        // Cells outside of the map are ignored.
+        map.mark_dirty(IVec2::new(100, 0));
+        assert!(!map.is_dirty(IVec2::new(100, 0)));
+    }
+
+    #[test]
+    fn settled_chunks_hibernate_until_a_neighbor_wakes_them() {
+        // (5, -5) and (40, -5) sit in side-by-side chunks. x = 32 is the left edge of the second.
+        let (settled, busy, edge) = (IVec2::new(5, -5), IVec2::new(40, -5), IVec2::new(32, -5));
+        let mut world = World::new();
+        world.insert_resource(map_with(&[settled, busy]));
+        world.insert_resource(HibernationThreshold(3));
+        world.init_resource::<Events<ChunkWokeEvent>>();
+        let index = |world: &World, cell: IVec2| {
+            world
+                .resource::<ChunkMap>()
+                .chunks
+                .iter()
+                .position(|chunk| chunk.region().contains(cell))
+                .unwrap()
+        };
+        let hibernating = |world: &World, cell: IVec2| {
+            world.resource::<ChunkMap>().chunks[index(world, cell)].hibernating()
+        };
+        let tick = |world: &mut World, change: Option<(IVec2, IVec2)>| {
+            if let Some((first, second)) = change {
+                let mut map = world.resource_mut::<ChunkMap>();
+                map.try_swap(first, second).unwrap();
+            }
+            world.run_system_once(reset_chunks).unwrap();
+            let mut events = world.resource_mut::<Events<ChunkWokeEvent>>();
+            let woken: Vec<usize> = events.drain().map(|ev| ev.0).collect();
+            woken
+        };
+
+        world.resource_mut::<ChunkMap>().mark_dirty(settled);
+        tick(&mut world, None);
+        // The busy chunk keeps moving away from the shared edge, which leaves its neighbor be.
+        for step in 0..3 {
+            assert!(!hibernating(&world, settled), "step {step}");
+            let (from, to) = if step % 2 == 0 {
+                (busy, busy + IVec2::X)
+            } else {
+                (busy + IVec2::X, busy)
+            };
+            tick(&mut world, Some((from, to)));
+        }
+        assert!(hibernating(&world, settled));
+        assert!(!hibernating(&world, busy));
+
+        let woken = tick(&mut world, Some((busy + IVec2::X, edge)));
+        assert_eq!(woken, vec![index(&world, settled)]);
+        assert!(!hibernating(&world, settled));
+    }
+
+    #[test]
+    fn checkerboard_phases_cover_every_chunk_once_without_touching() {
+        let mut map = ChunkMap::new(128);
+        let grid_width = map.grid_width();
+        let mut phases: Vec<Vec<usize>> = vec![];
+        map.for_each_checkerboard_phase(|views| {
+            phases.push(views.iter().map(|view| view.index()).collect());
+        });
+
+        assert_eq!(phases.len(), 4);
+        let mut covered: Vec<usize> = phases.iter().flatten().copied().collect();
+        covered.sort_unstable();
+        assert_eq!(covered, (0..map.chunks.len()).collect::<Vec<_>>());
+        for phase in &phases {
+            for (first, second) in phase.iter().zip(phase.iter().skip(1)) {
+                assert!(first < second);
+            }
+            for first in phase {
+                for second in phase.iter().filter(|second| *second != first) {
+                    let cols = (first % grid_width).abs_diff(second % grid_width);
+                    let rows = (first / grid_width).abs_diff(second / grid_width);
+                    assert!(cols > 1 || rows > 1, "chunks {first} and {second} touch");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn chunk_view_swaps_within_its_chunk() {
+        // (5, -5) lies inside the chunk spanning (0, 0) to (31, -31), (40, -5) in the next one.
+        let (first, second, beside) = (IVec2::new(5, -5), IVec2::new(6, -5), IVec2::new(40, -5));
+        let mut map = map_with(&[first, beside]);
+        let index = map.chunk_index(&first).unwrap();
+        map.for_each_checkerboard_phase(|views| {
+            for mut view in views.into_iter().filter(|view| view.index() == index) {
+                assert!(view.writable_region().contains(first));
+                assert_eq!(view.entity(&beside), Some(&Entity::from_raw(1)));
+                view.swap(first, second);
+                view.mark_dirty(second);
+            }
+        });
+
+        assert_eq!(map.entity(&second), Some(&Entity::from_raw(0)));
+        assert!(!map.is_occupied(first));
+        assert!(map.is_dirty(second));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the cells writable")]
+    fn chunk_view_rejects_changes_on_its_edge() {
+        // x = 31 is the right edge of the chunk holding (30, -5).
+        let (inside, edge) = (IVec2::new(30, -5), IVec2::new(31, -5));
+        let mut map = map_with(&[inside]);
+        let index = map.chunk_index(&inside).unwrap();
+        map.for_each_checkerboard_phase(|views| {
+            for mut view in views.into_iter().filter(|view| view.index() == index) {
+                view.swap(inside, edge);
+            }
+        });
+    }
+}
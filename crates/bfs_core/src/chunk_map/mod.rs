@@ -1,3 +1,5 @@
 mod chunk_map;
+mod neighbor_cache;
 
 pub use chunk_map::*;
+pub use neighbor_cache::*;
@@ -0,0 +1,181 @@
+use ahash::HashMap;
+use bevy::prelude::*;
+
+use crate::{Chunk, ChunkMap};
+
+/// An opt-in, per-tick memo of 8-neighbor lookups into the `ChunkMap`.
+///
+/// Insert this resource to enable it. Systems that scan neighborhoods look them up through
+/// [`cached_neighbors`], which goes through the cache when it's enabled and straight to
+/// [`ChunkMap::neighbors`] otherwise. Liquid cohesion and erosion, heat conduction, evaporation,
+/// propagation and the heat given off by burning particles all share lookups this way, so a cell
+/// scanned by several of them in one tick is only looked up once.
+///
+/// Invalidation contract: each entry remembers the [`Chunk::generation`]s of the chunks its 3x3
+/// neighborhood overlaps. If none of them changed, the entry is returned as is. If one did, the
+/// entry is only looked up again when the neighborhood overlaps a chunk's
+/// [`Chunk::changed_rect`], so moving particles in one corner of a chunk doesn't invalidate
+/// lookups in the rest of it. Clearing or resizing the map discards every entry, and the cache is
+/// also cleared after every simulation tick, when the chunks' changed rects are reset.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct NeighborCache {
+    generation: u64,
+    neighbors: HashMap<IVec2, CachedNeighbors>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CachedNeighbors {
+    neighbors: [Option<Entity>; 8],
+    /// The sum of the generations of the chunks overlapping the neighborhood when it was cached.
+    /// Chunk generations only ever increase, so this changes whenever any one of them does.
+    stamp: u64,
+}
+
+impl NeighborCache {
+    pub fn neighbors(&mut self, map: &ChunkMap, coords: IVec2) -> [Option<Entity>; 8] {
+        if self.generation != map.generation() {
+            self.neighbors.clear();
+            self.generation = map.generation();
+        }
+
+        let neighborhood = IRect::from_center_half_size(coords, IVec2::ONE);
+        let chunks = neighborhood_chunks(map, neighborhood);
+        let stamp = chunks.iter().flatten().fold(0, |stamp: u64, chunk| {
+            stamp.wrapping_add(chunk.generation())
+        });
+
+        if let Some(cached) = self.neighbors.get_mut(&coords) {
+            let unchanged = cached.stamp == stamp
+                || !chunks.iter().flatten().any(|chunk| {
+                    chunk.changed_rect().is_some_and(|changed_rect| {
+                        changed_rect.min.cmple(neighborhood.max).all()
+                            && changed_rect.max.cmpge(neighborhood.min).all()
+                    })
+                });
+            if unchanged {
+                cached.stamp = stamp;
+                return cached.neighbors;
+            }
+        }
+
+        let neighbors = map.neighbors(coords);
+        self.neighbors
+            .insert(coords, CachedNeighbors { neighbors, stamp });
+        neighbors
+    }
+
+    pub fn clear(&mut self) {
+        self.neighbors.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.neighbors.is_empty()
+    }
+}
+
+/// The distinct chunks holding the corners of `neighborhood`. A 3x3 neighborhood overlaps at most
+/// four chunks, and corners outside of the map are skipped.
+fn neighborhood_chunks(map: &ChunkMap, neighborhood: IRect) -> [Option<&Chunk>; 4] {
+    let corners = [
+        neighborhood.min,
+        IVec2::new(neighborhood.max.x, neighborhood.min.y),
+        IVec2::new(neighborhood.min.x, neighborhood.max.y),
+        neighborhood.max,
+    ];
+    let mut chunks = [None; 4];
+    // Most neighborhoods lie within a single chunk.
+    if let Some(chunk) = map.chunk(&neighborhood.min) {
+        let region = chunk.region();
+        if region.contains(neighborhood.min) && region.contains(neighborhood.max) {
+            chunks[0] = Some(chunk);
+            return chunks;
+        }
+    }
+    for (i, corner) in corners.into_iter().enumerate() {
+        if !map.contains(corner) {
+            continue;
+        }
+        let chunk = map.chunk(&corner);
+        if !chunks[..i]
+            .iter()
+            .any(|seen: &Option<&Chunk>| seen.map(Chunk::region) == chunk.map(Chunk::region))
+        {
+            chunks[i] = chunk;
+        }
+    }
+    chunks
+}
+
+/// The entities occupying the 8 cells around `coords`, ordered as
+/// [`NEIGHBOR_OFFSETS`](crate::NEIGHBOR_OFFSETS). Looks them up through `cache` if the
+/// [`NeighborCache`] is enabled, and straight from the map otherwise.
+pub fn cached_neighbors(
+    map: &ChunkMap,
+    cache: Option<&mut NeighborCache>,
+    coords: IVec2,
+) -> [Option<Entity>; 8] {
+    match cache {
+        Some(cache) => cache.neighbors(map, coords),
+        None => map.neighbors(coords),
+    }
+}
+
+pub fn clear_neighbor_cache(mut cache: ResMut<NeighborCache>) {
+    cache.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_neighbors_follow_changes() {
+        let mut map = ChunkMap::new(128);
+        let mut cache = NeighborCache::default();
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        map.insert_overwrite(IVec2::new(1, 1), a);
+
+        assert_eq!(cache.neighbors(&map, IVec2::new(20, 20))[3], None);
+        assert_eq!(cache.neighbors(&map, IVec2::ZERO)[2], Some(a));
+        map.insert_overwrite(IVec2::new(21, 20), b);
+        map.swap(IVec2::new(1, 1), IVec2::new(-1, -1));
+
+        assert_eq!(cache.neighbors(&map, IVec2::new(20, 20))[3], Some(b));
+        assert_eq!(cache.neighbors(&map, IVec2::ZERO)[2], None);
+        assert_eq!(cache.neighbors(&map, IVec2::ZERO)[6], Some(a));
+    }
+
+    #[test]
+    fn cached_neighbors_across_chunk_edges_follow_changes() {
+        let mut map = ChunkMap::new(128);
+        let mut cache = NeighborCache::default();
+        let a = Entity::from_raw(1);
+        // x = 0 starts a chunk and y = 0 is the top row of its chunk, so (0, 0) sits in a corner
+        // of a neighborhood spanning four chunks.
+        let corner = IVec2::new(-1, 1);
+        assert_eq!(cache.neighbors(&map, IVec2::ZERO), [None; 8]);
+
+        map.insert_overwrite(corner, a);
+        assert_eq!(cache.neighbors(&map, IVec2::ZERO)[0], Some(a));
+        map.remove(&corner);
+        assert_eq!(cache.neighbors(&map, IVec2::ZERO)[0], None);
+    }
+
+    #[test]
+    fn clearing_the_map_discards_cached_neighbors() {
+        let mut map = ChunkMap::new(128);
+        let mut cache = NeighborCache::default();
+        map.insert_overwrite(IVec2::X, Entity::from_raw(1));
+        assert_eq!(
+            cache.neighbors(&map, IVec2::ZERO)[3],
+            Some(Entity::from_raw(1))
+        );
+
+        map.clear();
+        assert_eq!(cache.neighbors(&map, IVec2::ZERO), [None; 8]);
+    }
+}
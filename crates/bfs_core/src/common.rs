@@ -1,21 +1,229 @@
+use bevy::ecs::schedule::ScheduleLabel;
 use bevy::prelude::*;
+use bevy_turborand::GlobalRng;
+
+use crate::Coordinates;
 
 pub struct CommonUtilitiesPlugin;
 
 impl Plugin for CommonUtilitiesPlugin {
     fn build(&self, app: &mut App) {
-        app.configure_sets(
-            Update,
-            ParticleSimulationSet.run_if(resource_exists::<SimulationRun>),
-        );
+        app.init_schedule(ParticleSimulation)
+            .configure_sets(Update, ParticleSimulationSet.run_if(simulation_running))
+            .configure_sets(
+                FixedUpdate,
+                ParticleSimulationSet.run_if(simulation_running),
+            );
         app.add_event::<ParticleRegistrationEvent>();
+        app.init_resource::<SimulationControl>()
+            .register_type::<SimulationControl>()
+            .add_event::<StepSimulationEvent>()
+            .add_systems(
+                PreUpdate,
+                (sync_simulation_run, queue_simulation_steps).chain(),
+            );
+        #[allow(deprecated)]
         app.init_resource::<SimulationRun>();
+        app.register_type::<SimulationSeed>().add_systems(
+            PreUpdate,
+            reseed_global_rng.run_if(resource_exists_and_changed::<SimulationSeed>),
+        );
+        app.init_resource::<SimulationTick>()
+            .register_type::<SimulationTick>()
+            .add_systems(
+                ParticleSimulation,
+                advance_simulation_tick.in_set(ParticleSimulationSet),
+            );
+        app.init_resource::<SimulationActivity>()
+            .register_type::<SimulationActivity>()
+            .add_event::<SimulationSettled>()
+            .add_systems(
+                ParticleSimulation,
+                track_simulation_activity.in_set(ParticleSimulationSet),
+            );
     }
 }
 
-#[derive(Resource, Default)]
+/// Pauses and resumes the simulation.
+///
+/// While paused, the [`ParticleSimulationSet`] doesn't run, except for one step per
+/// [`StepSimulationEvent`]. Several requested steps run one after another, one per frame (or per
+/// fixed tick, see [`ParticleSimulation`]).
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SimulationControl {
+    pub paused: bool,
+    queued_steps: u32,
+}
+
+impl SimulationControl {
+    /// Pauses the simulation if it's running, or resumes it if it's paused.
+    pub fn toggle(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Whether the simulation advances on its next step.
+    pub fn is_running(&self) -> bool {
+        !self.paused || self.queued_steps > 0
+    }
+
+    /// The number of requested steps that haven't run yet.
+    pub fn queued_steps(&self) -> u32 {
+        self.queued_steps
+    }
+}
+
+/// Runs the simulation while present.
+///
+/// Kept so existing code that pauses by removing this resource, or checks for it with
+/// `resource_exists`, keeps working: it's kept in sync with [`SimulationControl::paused`] every
+/// frame, in both directions.
+#[deprecated(since = "0.4.0", note = "use `SimulationControl::paused` instead")]
+#[derive(Default)]
 pub struct SimulationRun;
 
+#[allow(deprecated)]
+impl Resource for SimulationRun {}
+
+/// Advances a paused simulation by exactly one step. Has no effect while the simulation runs.
+#[derive(Event, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct StepSimulationEvent;
+
+/// Run condition for systems that should only run while the simulation advances. See
+/// [`SimulationControl`].
+pub fn simulation_running(control: Res<SimulationControl>) -> bool {
+    control.is_running()
+}
+
+/// Pauses or resumes the simulation when [`SimulationRun`] is removed or inserted, and otherwise
+/// inserts or removes it to match [`SimulationControl::paused`].
+#[allow(deprecated)]
+pub fn sync_simulation_run(
+    mut commands: Commands,
+    mut control: ResMut<SimulationControl>,
+    run: Option<Res<SimulationRun>>,
+    mut was_running: Local<Option<bool>>,
+) {
+    let running = run.is_some();
+    if was_running.is_some_and(|was_running| was_running != running) {
+        control.paused = !running;
+    } else if control.paused == running {
+        if control.paused {
+            commands.remove_resource::<SimulationRun>();
+        } else {
+            commands.init_resource::<SimulationRun>();
+        }
+    }
+    *was_running = Some(!control.paused);
+}
+
+pub fn queue_simulation_steps(
+    mut control: ResMut<SimulationControl>,
+    mut ev_step_simulation: EventReader<StepSimulationEvent>,
+) {
+    let requested = ev_step_simulation.read().count() as u32;
+    if control.paused {
+        control.queued_steps = control.queued_steps.saturating_add(requested);
+    } else {
+        control.queued_steps = 0;
+    }
+}
+
+/// The schedule every simulation system runs in, once per simulation step.
+///
+/// By default it runs once per frame during `Update`, so the simulation speeds up and slows down
+/// with the frame rate. With a tick rate set through
+/// [`FallingSandCorePlugin::with_tick_rate`](crate::FallingSandCorePlugin::with_tick_rate), it
+/// runs during `FixedUpdate` instead, as many times per frame as needed to keep up. Either way the
+/// step is driven by a system in the [`ParticleSimulationSet`] of that schedule, so other systems
+/// can still be ordered before or after the simulation there.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParticleSimulation;
+
+/// Runs one step of the [`ParticleSimulation`] schedule, using up a queued step if paused.
+pub fn run_particle_simulation(world: &mut World) {
+    world.run_schedule(ParticleSimulation);
+    let mut control = world.resource_mut::<SimulationControl>();
+    if control.paused {
+        control.queued_steps = control.queued_steps.saturating_sub(1);
+    }
+}
+
+/// Seeds the simulation's random number generators while present, making it deterministic.
+///
+/// Inserting (or changing) this resource reseeds `GlobalRng`, which every per-particle generator
+/// in the movement, color and reaction crates is derived from as the particle is registered. Given
+/// the same seed and the same spawns in the same order, the simulation then evolves identically.
+/// Particles registered before the seed was set keep their existing generators.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SimulationSeed(pub u64);
+
+pub fn reseed_global_rng(mut commands: Commands, seed: Res<SimulationSeed>) {
+    commands.insert_resource(GlobalRng::with_seed(seed.0));
+}
+
+/// The number of simulation steps that have run so far.
+///
+/// This advances once per step of the [`ParticleSimulationSet`], so it stands still while the
+/// simulation is paused (see [`SimulationControl`]). Prefer it over wall-clock time
+/// for anything that should be frame-rate independent or replayable.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SimulationTick(pub u64);
+
+impl SimulationTick {
+    /// Returns the current tick.
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Tracks how long it has been since any particle moved.
+///
+/// A particle counts as having moved on a tick if its [`Coordinates`] changed, which includes being
+/// spawned, swapped or teleported. Particles that only change type or color don't count.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Hash, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct SimulationActivity {
+    /// The number of consecutive ticks without movement after which the simulation is settled.
+    pub settle_after: u64,
+    ticks_since_movement: u64,
+    settled: bool,
+}
+
+impl Default for SimulationActivity {
+    fn default() -> SimulationActivity {
+        SimulationActivity::new(60)
+    }
+}
+
+impl SimulationActivity {
+    pub fn new(settle_after: u64) -> SimulationActivity {
+        SimulationActivity {
+            settle_after,
+            ticks_since_movement: 0,
+            settled: false,
+        }
+    }
+
+    pub fn ticks_since_movement(&self) -> u64 {
+        self.ticks_since_movement
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.settled
+    }
+}
+
+/// Sent once when the simulation settles, i.e. no particle has moved for
+/// [`SimulationActivity::settle_after`] ticks. It's sent again the next time the simulation
+/// settles after something moves.
+#[derive(Event, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SimulationSettled {
+    pub tick: u64,
+}
+
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ParticleSimulationSet;
 
@@ -26,3 +234,127 @@ pub struct ParticleDebugSet;
 pub struct ParticleRegistrationEvent {
     pub entities: Vec<Entity>
 }
+
+pub fn advance_simulation_tick(mut tick: ResMut<SimulationTick>) {
+    tick.0 = tick.0.wrapping_add(1);
+}
+
+pub fn track_simulation_activity(
+    mut activity: ResMut<SimulationActivity>,
+    tick: Res<SimulationTick>,
+    moved_query: Query<(), Changed<Coordinates>>,
+    mut ev_simulation_settled: EventWriter<SimulationSettled>,
+) {
+    if !moved_query.is_empty() {
+        activity.ticks_since_movement = 0;
+        activity.settled = false;
+        return;
+    }
+    activity.ticks_since_movement = activity.ticks_since_movement.saturating_add(1);
+    if !activity.settled && activity.ticks_since_movement >= activity.settle_after {
+        activity.settled = true;
+        ev_simulation_settled.send(SimulationSettled { tick: tick.get() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChunkMap, FallingSandCorePlugin, Lifetime, Particle, ParticleType};
+
+    fn paused_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FallingSandCorePlugin::default()));
+        app.world_mut().spawn(ParticleType::new("Spark"));
+        app.update();
+        app.world_mut().resource_mut::<SimulationControl>().paused = true;
+        app
+    }
+
+    fn tick(app: &App) -> u64 {
+        app.world().resource::<SimulationTick>().get()
+    }
+
+    #[test]
+    fn nothing_changes_while_paused() {
+        let mut app = paused_app();
+        app.world_mut().spawn((
+            Particle::new("Spark"),
+            Transform::from_xyz(0., 0., 0.),
+            Lifetime(3),
+        ));
+        let start = tick(&app);
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(tick(&app), start);
+        let lifetime = app.world_mut().query::<&Lifetime>().single(app.world()).0;
+        assert_eq!(lifetime, 3);
+        assert!(app.world().resource::<ChunkMap>().is_occupied(IVec2::ZERO));
+    }
+
+    #[test]
+    fn each_step_advances_exactly_one_tick() {
+        let mut app = paused_app();
+        let start = tick(&app);
+
+        app.world_mut().send_event(StepSimulationEvent);
+        app.update();
+        assert_eq!(tick(&app), start + 1);
+        app.update();
+        assert_eq!(tick(&app), start + 1);
+
+        // Steps requested in the same frame run one per frame.
+        app.world_mut().send_event_batch([StepSimulationEvent; 3]);
+        for step in 2..=4 {
+            app.update();
+            assert_eq!(tick(&app), start + step);
+        }
+        app.update();
+        assert_eq!(tick(&app), start + 4);
+        assert_eq!(
+            app.world().resource::<SimulationControl>().queued_steps(),
+            0
+        );
+    }
+
+    #[test]
+    fn steps_requested_while_running_are_ignored() {
+        let mut app = paused_app();
+        app.world_mut().resource_mut::<SimulationControl>().paused = false;
+        app.world_mut().send_event_batch([StepSimulationEvent; 3]);
+        app.update();
+        let resumed = tick(&app);
+
+        app.world_mut().resource_mut::<SimulationControl>().paused = true;
+        app.update();
+        assert_eq!(tick(&app), resumed);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn simulation_run_still_pauses_and_resumes() {
+        let mut app = paused_app();
+        app.update();
+        assert!(!app.world().contains_resource::<SimulationRun>());
+
+        app.world_mut().insert_resource(SimulationRun);
+        app.update();
+        assert!(!app.world().resource::<SimulationControl>().paused);
+        let resumed = tick(&app);
+        app.update();
+        assert_eq!(tick(&app), resumed + 1);
+
+        app.world_mut().remove_resource::<SimulationRun>();
+        app.update();
+        assert!(app.world().resource::<SimulationControl>().paused);
+        let paused = tick(&app);
+        app.update();
+        assert_eq!(tick(&app), paused);
+
+        app.world_mut().resource_mut::<SimulationControl>().paused = false;
+        app.update();
+        assert!(app.world().contains_resource::<SimulationRun>());
+    }
+}
@@ -2,6 +2,11 @@ mod particle;
 mod chunk_map;
 mod particle_type;
 mod common;
+mod modifiers;
+mod regions;
+#[cfg(any(test, feature = "testing"))]
+#[doc(hidden)]
+pub mod testing;
 
 use bevy::prelude::*;
 
@@ -9,16 +14,44 @@ pub use particle::*;
 pub use chunk_map::*;
 pub use common::*;
 pub use particle_type::*;
+pub use modifiers::*;
+pub use regions::*;
 
-pub struct FallingSandCorePlugin;
+#[derive(Default)]
+pub struct FallingSandCorePlugin {
+    tick_rate: Option<f64>,
+}
+
+impl FallingSandCorePlugin {
+    /// Steps the simulation `hz` times per second of game time, regardless of the frame rate,
+    /// instead of once per frame. This sets the app's `Time<Fixed>` rate, which is shared with any
+    /// other `FixedUpdate` systems.
+    pub fn with_tick_rate(mut self, hz: f64) -> FallingSandCorePlugin {
+        self.tick_rate = Some(hz);
+        self
+    }
+}
 
 impl Plugin for FallingSandCorePlugin {
     fn build(&self, app: &mut App) {
+        if let Some(hz) = self.tick_rate {
+            app.insert_resource(Time::<Fixed>::from_hz(hz)).add_systems(
+                FixedUpdate,
+                run_particle_simulation.in_set(ParticleSimulationSet),
+            );
+        } else {
+            app.add_systems(
+                Update,
+                run_particle_simulation.in_set(ParticleSimulationSet),
+            );
+        }
         app.add_plugins((
             ParticlePlugin,
             ParticleTypePlugin,
             ChunkMapPlugin,
             CommonUtilitiesPlugin,
+            NamedRegionsPlugin,
+            GlobalModifiersPlugin,
         ));
     }
 }
@@ -0,0 +1,174 @@
+use bevy::prelude::*;
+
+pub struct GlobalModifiersPlugin;
+
+impl Plugin for GlobalModifiersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GlobalModifiers>()
+            .register_type::<GlobalModifier>();
+    }
+}
+
+/// A game-wide change to how every particle behaves, such as a low-gravity mode or an
+/// "everything burns" power-up.
+///
+/// Modifiers layer on top of each particle type's own configuration rather than replacing it:
+/// gravity scales whatever velocity a type already has, and flammability only matters for
+/// particles that don't burn on their own.
+#[derive(Copy, Clone, PartialEq, Debug, Reflect)]
+pub struct GlobalModifier {
+    /// Scales how many cells particles move each tick. `0.5` halves movement, `0.` stops it.
+    pub gravity_scale: f32,
+    /// Lets fire ignite particles whose type doesn't burn.
+    pub all_flammable: bool,
+    /// Pauses fire, burning and other reactions.
+    pub freeze_reactions: bool,
+}
+
+impl Default for GlobalModifier {
+    fn default() -> GlobalModifier {
+        GlobalModifier {
+            gravity_scale: 1.,
+            all_flammable: false,
+            freeze_reactions: false,
+        }
+    }
+}
+
+/// Identifies a modifier pushed onto [`GlobalModifiers`] so it can be removed later.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ModifierId(u64);
+
+/// The stack of active [`GlobalModifier`]s.
+///
+/// Any number of modifiers can be active at once. Gravity scales multiply together, while
+/// `all_flammable` and `freeze_reactions` apply if any active modifier sets them. With no active
+/// modifiers, every particle behaves exactly as its type defines.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct GlobalModifiers {
+    modifiers: Vec<(ModifierId, GlobalModifier)>,
+    next_id: u64,
+}
+
+impl GlobalModifiers {
+    /// Activates a modifier, returning an id that can be passed to [`GlobalModifiers::remove`].
+    pub fn push(&mut self, modifier: GlobalModifier) -> ModifierId {
+        let id = ModifierId(self.next_id);
+        self.next_id += 1;
+        self.modifiers.push((id, modifier));
+        id
+    }
+
+    /// Removes the most recently pushed modifier that is still active.
+    pub fn pop(&mut self) -> Option<GlobalModifier> {
+        self.modifiers.pop().map(|(_, modifier)| modifier)
+    }
+
+    /// Removes a specific modifier, regardless of what was pushed after it.
+    pub fn remove(&mut self, id: ModifierId) -> Option<GlobalModifier> {
+        let index = self
+            .modifiers
+            .iter()
+            .position(|(modifier_id, _)| *modifier_id == id)?;
+        Some(self.modifiers.remove(index).1)
+    }
+
+    pub fn clear(&mut self) {
+        self.modifiers.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modifiers.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GlobalModifier> {
+        self.modifiers.iter().map(|(_, modifier)| modifier)
+    }
+
+    pub fn gravity_scale(&self) -> f32 {
+        self.iter()
+            .map(|modifier| modifier.gravity_scale.max(0.))
+            .product()
+    }
+
+    pub fn all_flammable(&self) -> bool {
+        self.iter().any(|modifier| modifier.all_flammable)
+    }
+
+    pub fn reactions_frozen(&self) -> bool {
+        self.iter().any(|modifier| modifier.freeze_reactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gravity(gravity_scale: f32) -> GlobalModifier {
+        GlobalModifier {
+            gravity_scale,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn modifiers_stack_on_top_of_each_other() {
+        let mut modifiers = GlobalModifiers::default();
+        assert_eq!(modifiers.gravity_scale(), 1.);
+        assert!(!modifiers.all_flammable());
+
+        modifiers.push(gravity(0.5));
+        modifiers.push(GlobalModifier {
+            all_flammable: true,
+            ..gravity(0.5)
+        });
+        // Neither modifier overrides the other: scales multiply and flags add up.
+        assert_eq!(modifiers.gravity_scale(), 0.25);
+        assert!(modifiers.all_flammable());
+        assert!(!modifiers.reactions_frozen());
+
+        // Negative scales count as zero rather than flipping gravity.
+        modifiers.push(gravity(-1.));
+        assert_eq!(modifiers.gravity_scale(), 0.);
+    }
+
+    #[test]
+    fn pop_removes_the_latest_modifier() {
+        let mut modifiers = GlobalModifiers::default();
+        assert_eq!(modifiers.pop(), None);
+
+        modifiers.push(gravity(0.5));
+        modifiers.push(gravity(2.));
+        assert_eq!(modifiers.pop(), Some(gravity(2.)));
+        assert_eq!(modifiers.gravity_scale(), 0.5);
+        assert_eq!(modifiers.pop(), Some(gravity(0.5)));
+        assert!(modifiers.is_empty());
+    }
+
+    #[test]
+    fn remove_takes_a_modifier_out_of_the_middle() {
+        let mut modifiers = GlobalModifiers::default();
+        let low = modifiers.push(gravity(0.5));
+        let frozen = modifiers.push(GlobalModifier {
+            freeze_reactions: true,
+            ..default()
+        });
+        modifiers.push(gravity(3.));
+
+        assert!(modifiers.reactions_frozen());
+        assert!(modifiers
+            .remove(frozen)
+            .is_some_and(|modifier| modifier.freeze_reactions));
+        assert!(!modifiers.reactions_frozen());
+        assert_eq!(modifiers.gravity_scale(), 1.5);
+        // A removed id matches nothing, the others still match, and ids are never reused.
+        assert_eq!(modifiers.remove(frozen), None);
+        assert_eq!(modifiers.remove(low), Some(gravity(0.5)));
+        assert_ne!(modifiers.push(gravity(0.5)), low);
+        assert_eq!(
+            modifiers.iter().copied().collect::<Vec<_>>(),
+            vec![gravity(3.), gravity(0.5)]
+        );
+        assert_eq!(modifiers.pop(), Some(gravity(0.5)));
+    }
+}
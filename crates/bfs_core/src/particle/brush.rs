@@ -0,0 +1,307 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_turborand::TurboRand;
+
+use crate::{ChunkMap, MutateParticleEvent, Particle, ParticleMix, RemoveParticleEvent};
+
+pub struct BrushPlugin;
+
+impl Plugin for BrushPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ReplaceMatchingEvent>()
+            .add_observer(on_replace_matching);
+    }
+}
+
+/// The footprint of a brush at a single point.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Reflect)]
+pub enum BrushShape {
+    /// A filled circle with a radius of the brush size.
+    #[default]
+    Circle,
+    /// A horizontal line three times the brush size in length.
+    Line,
+}
+
+/// What a brush stroke does to the cells it covers.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default, Reflect)]
+pub enum BrushMode {
+    /// Spawn the stroke's particle into empty cells.
+    #[default]
+    Spawn,
+    /// Remove and despawn any particle under the stroke.
+    Despawn,
+    /// Convert particles of the named type under the stroke into the stroke's particle, leaving
+    /// every other cell untouched. `None` matches empty cells, filling only the gaps.
+    ReplaceMatching(Option<String>),
+}
+
+/// A single brush movement from `from` to `to`.
+///
+/// The brush shape is swept along the whole segment, so a fast-moving brush leaves no gaps between
+/// frames. A stroke whose endpoints are the same paints the shape once.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BrushStroke {
+    pub shape: BrushShape,
+    pub from: Vec2,
+    pub to: Vec2,
+    pub size: f32,
+    pub particle: Particle,
+    pub mode: BrushMode,
+}
+
+impl BrushStroke {
+    pub fn new(
+        shape: BrushShape,
+        from: Vec2,
+        to: Vec2,
+        size: f32,
+        particle: Particle,
+        mode: BrushMode,
+    ) -> BrushStroke {
+        BrushStroke {
+            shape,
+            from,
+            to,
+            size,
+            particle,
+            mode,
+        }
+    }
+
+    /// Returns every cell covered by the stroke.
+    pub fn points(&self) -> HashSet<IVec2> {
+        match self.shape {
+            BrushShape::Circle => points_within_capsule(self.from, self.to, self.size),
+            BrushShape::Line => points_along_line(self.from, self.to, self.size),
+        }
+    }
+}
+
+/// Applies a brush stroke to the simulation.
+///
+/// Cells outside of the map are skipped. Spawning only fills empty cells. Despawning triggers a [`RemoveParticleEvent`] for each
+/// occupied cell under the stroke. Replacing triggers a [`ReplaceMatchingEvent`] for the occupied
+/// cells, as the occupants' types are only known to the world.
+pub fn paint_stroke(commands: &mut Commands, map: &ChunkMap, stroke: &BrushStroke) {
+    let points = stroke.points();
+    match &stroke.mode {
+        BrushMode::Spawn | BrushMode::ReplaceMatching(None) => {
+            let particle = stroke.particle.clone();
+            let points: Vec<IVec2> = points
+                .into_iter()
+                .filter(|point| map.contains(*point) && !map.is_occupied(*point))
+                .collect();
+            commands.spawn_batch(points.into_iter().map(move |point| {
+                (
+                    particle.clone(),
+                    Transform::from_xyz(point.x as f32, point.y as f32, 0.0),
+                )
+            }));
+        }
+        BrushMode::Despawn => {
+            points
+                .into_iter()
+                .filter(|point| map.is_occupied(*point))
+                .for_each(|coordinates| {
+                    commands.trigger(RemoveParticleEvent {
+                        coordinates,
+                        despawn: true,
+                    });
+                });
+        }
+        BrushMode::ReplaceMatching(Some(from)) => {
+            commands.trigger(ReplaceMatchingEvent {
+                cells: points
+                    .into_iter()
+                    .filter(|point| map.is_occupied(*point))
+                    .collect(),
+                from: from.clone(),
+                to: stroke.particle.clone(),
+            });
+        }
+    }
+}
+
+/// Converts the particles at `cells` whose type is `from` into `to` in place. Cells that are
+/// empty, hold any other type or lie outside of the map are left alone.
+#[derive(Event, Clone, Debug)]
+pub struct ReplaceMatchingEvent {
+    pub cells: Vec<IVec2>,
+    pub from: String,
+    pub to: Particle,
+}
+
+pub fn on_replace_matching(
+    trigger: Trigger<ReplaceMatchingEvent>,
+    map: Res<ChunkMap>,
+    particle_query: Query<&Particle>,
+    mut ev_mutate_particle: EventWriter<MutateParticleEvent>,
+) {
+    let ReplaceMatchingEvent { cells, from, to } = trigger.event();
+    if from == &to.name {
+        return;
+    }
+    ev_mutate_particle.send_batch(
+        cells
+            .iter()
+            .filter_map(|coordinates| map.entity(coordinates))
+            .filter(|entity| {
+                particle_query
+                    .get(**entity)
+                    .is_ok_and(|particle| &particle.name == from)
+            })
+            .map(|entity| MutateParticleEvent {
+                entity: *entity,
+                particle: to.clone(),
+            }),
+    );
+}
+
+/// Applies a brush stroke like [`paint_stroke`], but picks each spawned particle from `mix`
+/// instead of using the stroke's particle.
+///
+/// Only spawning picks from the mix; other modes behave exactly as in [`paint_stroke`]. If the
+/// mix can't pick anything (see [`ParticleMix`]), nothing is spawned.
+pub fn paint_stroke_with_mix(
+    commands: &mut Commands,
+    map: &ChunkMap,
+    stroke: &BrushStroke,
+    mix: &ParticleMix,
+    rng: &mut impl TurboRand,
+) {
+    if stroke.mode != BrushMode::Spawn {
+        paint_stroke(commands, map, stroke);
+        return;
+    }
+    let particles: Vec<(Particle, Transform)> = stroke
+        .points()
+        .into_iter()
+        .filter(|point| map.contains(*point) && !map.is_occupied(*point))
+        .filter_map(|point| {
+            mix.pick(rng).map(|particle| {
+                (
+                    particle.clone(),
+                    Transform::from_xyz(point.x as f32, point.y as f32, 0.0),
+                )
+            })
+        })
+        .collect();
+    commands.spawn_batch(particles);
+}
+
+/// Every cell within `radius` of the segment from `start` to `end`.
+fn points_within_capsule(start: Vec2, end: Vec2, radius: f32) -> HashSet<IVec2> {
+    let mut points: HashSet<IVec2> = HashSet::default();
+
+    let min_x = (start.x.min(end.x) - radius).floor() as i32;
+    let max_x = (start.x.max(end.x) + radius).ceil() as i32;
+    let min_y = (start.y.min(end.y) - radius).floor() as i32;
+    let max_y = (start.y.max(end.y) + radius).ceil() as i32;
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+
+    for x in min_x..=max_x {
+        for y in min_y..=max_y {
+            let point = Vec2::new(x as f32, y as f32);
+            let closest_point = if length_squared == 0. {
+                start
+            } else {
+                let t = ((point - start).dot(segment) / length_squared).clamp(0., 1.);
+                start + segment * t
+            };
+            if (point - closest_point).length() <= radius {
+                points.insert(IVec2::new(x, y));
+            }
+        }
+    }
+
+    points
+}
+
+/// Every cell swept by a horizontal line of half-width `size * 1.5` moving from `start` to `end`.
+fn points_along_line(start: Vec2, end: Vec2, size: f32) -> HashSet<IVec2> {
+    let mut points: HashSet<IVec2> = HashSet::default();
+
+    let half_width = (size * 1.5) as i32;
+    let steps = (end - start).abs().max_element().ceil().max(1.) as i32;
+    for step in 0..=steps {
+        let center = start
+            .lerp(end, step as f32 / steps as f32)
+            .round()
+            .as_ivec2();
+        for x in -half_width..=half_width {
+            points.insert(IVec2::new(center.x + x, center.y));
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::positions;
+    use crate::{FallingSandCorePlugin, ParticleType};
+
+    fn paint(app: &mut App, stroke: &BrushStroke) {
+        let map = app.world().resource::<ChunkMap>().clone();
+        paint_stroke(&mut app.world_mut().commands(), &map, stroke);
+        app.world_mut().flush();
+        for _ in 0..3 {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn strokes_overlapping_the_map_edge() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FallingSandCorePlugin::default()));
+        app.insert_resource(ChunkMap::new(64));
+        app.world_mut().spawn(ParticleType::new("Sand"));
+        app.world_mut().spawn(ParticleType::new("Water"));
+        app.update();
+
+        // Centered on the top-right corner of the map, so three quarters of the brush is outside.
+        let corner = app.world().resource::<ChunkMap>().world_bounds_i().max;
+        let stroke = |particle: &str, mode: BrushMode| {
+            BrushStroke::new(
+                BrushShape::Circle,
+                corner.as_vec2(),
+                corner.as_vec2(),
+                3.,
+                Particle::new(particle),
+                mode,
+            )
+        };
+        let mut inside: Vec<IVec2> = stroke("Sand", BrushMode::Spawn)
+            .points()
+            .into_iter()
+            .filter(|point| point.x <= corner.x && point.y <= corner.y)
+            .collect();
+        inside.sort_by_key(|coordinates| (coordinates.y, coordinates.x));
+
+        paint(&mut app, &stroke("Sand", BrushMode::Spawn));
+        assert_eq!(positions(&mut app, "Sand"), inside);
+
+        // Replacing goes through `ReplaceMatchingEvent` with every cell of the brush.
+        app.world_mut().trigger(ReplaceMatchingEvent {
+            cells: stroke("Water", BrushMode::Spawn)
+                .points()
+                .into_iter()
+                .collect(),
+            from: "Sand".to_string(),
+            to: Particle::new("Water"),
+        });
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(positions(&mut app, "Sand"), vec![]);
+        assert_eq!(positions(&mut app, "Water"), inside);
+
+        paint(&mut app, &stroke("Sand", BrushMode::Despawn));
+        paint(&mut app, &stroke("Sand", BrushMode::ReplaceMatching(None)));
+        assert_eq!(positions(&mut app, "Water"), vec![]);
+        assert_eq!(positions(&mut app, "Sand"), inside);
+    }
+}
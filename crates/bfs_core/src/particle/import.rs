@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::{Particle, ParticleTypeMap};
+
+/// Spawns a particle for every named cell in `grid`, skipping `None` cells.
+///
+/// Rows run top to bottom, so `grid[0][0]` is placed at `origin` and each following row is placed
+/// one cell lower. Names that aren't in the type map are skipped with a warning. Returns the
+/// number of particles queued; as with any spawn, those landing on occupied cells are dropped.
+pub fn spawn_from_grid(
+    commands: &mut Commands,
+    type_map: &ParticleTypeMap,
+    grid: &[&[Option<&str>]],
+    origin: IVec2,
+) -> usize {
+    let cells = grid.iter().enumerate().flat_map(|(row, cells)| {
+        cells.iter().enumerate().filter_map(move |(column, name)| {
+            name.map(|name| (origin + IVec2::new(column as i32, -(row as i32)), name))
+        })
+    });
+    spawn_cells(commands, type_map, cells)
+}
+
+/// Spawns a particle for every pixel of `image` whose color appears in `color_to_type`, keyed by
+/// its sRGBA bytes. Pixels with other colors are left empty.
+///
+/// The image's top-left pixel is placed at `origin`, with the image extending right and down.
+/// Unknown type names are skipped with a warning. Returns the number of particles queued.
+pub fn spawn_from_image(
+    commands: &mut Commands,
+    type_map: &ParticleTypeMap,
+    image: &Image,
+    color_to_type: &HashMap<[u8; 4], String>,
+    origin: IVec2,
+) -> usize {
+    let size = image.size();
+    let cells = (0..size.y).flat_map(|y| {
+        (0..size.x).filter_map(move |x| {
+            let color = image.get_color_at(x, y).ok()?.to_srgba().to_u8_array();
+            color_to_type
+                .get(&color)
+                .map(|name| (origin + IVec2::new(x as i32, -(y as i32)), name.as_str()))
+        })
+    });
+    spawn_cells(commands, type_map, cells)
+}
+
+fn spawn_cells<'a>(
+    commands: &mut Commands,
+    type_map: &ParticleTypeMap,
+    cells: impl Iterator<Item = (IVec2, &'a str)>,
+) -> usize {
+    let mut unknown: HashSet<&str> = HashSet::default();
+    let mut particles: Vec<(Particle, Transform)> = vec![];
+    for (coordinates, name) in cells {
+        if type_map.id_of(name).is_none() {
+            if unknown.insert(name) {
+                warn!("Skipping unknown particle type '{}' during import.", name);
+            }
+            continue;
+        }
+        particles.push((
+            Particle::new(name),
+            Transform::from_xyz(coordinates.x as f32, coordinates.y as f32, 0.),
+        ));
+    }
+    let count = particles.len();
+    commands.spawn_batch(particles);
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::positions;
+    use crate::{ChunkMap, FallingSandCorePlugin, ParticleType};
+    use bevy::render::render_asset::RenderAssetUsages;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FallingSandCorePlugin::default()));
+        app.insert_resource(ChunkMap::new(64));
+        app.world_mut().spawn(ParticleType::new("Sand"));
+        app.world_mut().spawn(ParticleType::new("Water"));
+        app.update();
+        app
+    }
+
+    fn import(app: &mut App, grid: &[&[Option<&str>]], origin: IVec2) -> usize {
+        let type_map = app.world().resource::<ParticleTypeMap>().clone();
+        let count = spawn_from_grid(&mut app.world_mut().commands(), &type_map, grid, origin);
+        app.world_mut().flush();
+        app.update();
+        count
+    }
+
+    #[test]
+    fn grid_rows_run_down_from_the_origin() {
+        let mut app = app();
+        let origin = IVec2::new(2, 3);
+        let count = import(
+            &mut app,
+            &[
+                &[Some("Sand"), None, Some("Sand")],
+                &[None, Some("Water"), None],
+            ],
+            origin,
+        );
+
+        assert_eq!(count, 3);
+        assert_eq!(
+            positions(&mut app, "Sand"),
+            vec![origin, origin + IVec2::new(2, 0)]
+        );
+        assert_eq!(
+            positions(&mut app, "Water"),
+            vec![origin + IVec2::new(1, -1)]
+        );
+    }
+
+    #[test]
+    fn empty_and_unknown_cells_are_skipped() {
+        let mut app = app();
+        let count = import(
+            &mut app,
+            &[&[Some("Lava"), None, Some("Sand"), Some("Lava")], &[]],
+            IVec2::ZERO,
+        );
+
+        assert_eq!(count, 1);
+        assert_eq!(positions(&mut app, "Sand"), vec![IVec2::new(2, 0)]);
+        assert_eq!(positions(&mut app, "Lava"), vec![]);
+    }
+
+    #[test]
+    fn cells_outside_the_map_are_dropped() {
+        let mut app = app();
+        // Only the bottom-left cell of the grid lands on the map.
+        let corner = app.world().resource::<ChunkMap>().world_bounds_i().max;
+        let count = import(
+            &mut app,
+            &[&[Some("Sand"), Some("Sand")], &[Some("Sand"), Some("Sand")]],
+            corner + IVec2::Y,
+        );
+
+        assert_eq!(count, 4);
+        assert_eq!(positions(&mut app, "Sand"), vec![corner]);
+    }
+
+    #[test]
+    fn image_pixels_run_right_and_down_from_the_origin() {
+        let mut app = app();
+        // A 2x2 image: red, white on top; white, blue below.
+        let (red, white, blue) = ([255, 0, 0, 255], [255; 4], [0, 0, 255, 255]);
+        let image = Image::new(
+            Extent3d {
+                width: 2,
+                height: 2,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            [red, white, white, blue].concat(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        let color_to_type: HashMap<[u8; 4], String> =
+            [(red, "Sand".to_string()), (blue, "Water".to_string())]
+                .into_iter()
+                .collect();
+        let origin = IVec2::new(-4, 4);
+
+        let type_map = app.world().resource::<ParticleTypeMap>().clone();
+        let count = spawn_from_image(
+            &mut app.world_mut().commands(),
+            &type_map,
+            &image,
+            &color_to_type,
+            origin,
+        );
+        app.world_mut().flush();
+        app.update();
+
+        assert_eq!(count, 2);
+        assert_eq!(positions(&mut app, "Sand"), vec![origin]);
+        assert_eq!(
+            positions(&mut app, "Water"),
+            vec![origin + IVec2::new(1, -1)]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use bevy_turborand::TurboRand;
+use thiserror::Error;
+
+use crate::Particle;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ParticleMixError {
+    #[error("Invalid particle mix: weight for '{0}' is negative or not a number")]
+    InvalidWeight(String),
+    #[error("Invalid particle mix: at least one weight must be positive")]
+    NoPositiveWeight,
+}
+
+/// A weighted list of particle types to choose from each time a particle is spawned.
+///
+/// Each entry is picked with a probability proportional to its weight, so
+/// `[(sand, 3.), (gravel, 1.)]` produces sand three times as often as gravel. Entries with a weight
+/// of zero are never picked. [`ParticleMix::new`] validates the weights; a mix built directly from
+/// an empty list (or one without any positive weight) is allowed, but never picks anything.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ParticleMix(pub Vec<(Particle, f32)>);
+
+impl ParticleMix {
+    /// Creates a mix, returning an error if any weight is negative or no weight is positive.
+    pub fn new(entries: Vec<(Particle, f32)>) -> Result<ParticleMix, ParticleMixError> {
+        if let Some((particle, _)) = entries
+            .iter()
+            .find(|(_, weight)| weight.is_nan() || *weight < 0.)
+        {
+            return Err(ParticleMixError::InvalidWeight(particle.name.clone()));
+        }
+        if !entries.iter().any(|(_, weight)| *weight > 0.) {
+            return Err(ParticleMixError::NoPositiveWeight);
+        }
+        Ok(ParticleMix(entries))
+    }
+
+    /// Creates a mix that always picks `particle`.
+    pub fn single(particle: Particle) -> ParticleMix {
+        ParticleMix(vec![(particle, 1.)])
+    }
+
+    /// The sum of all positive weights.
+    pub fn total_weight(&self) -> f32 {
+        self.positive().map(|(_, weight)| weight).sum()
+    }
+
+    /// Picks a particle by weight, or `None` if the mix has no positively weighted entries.
+    ///
+    /// Pass the simulation's seeded generator (e.g. `GlobalRng`) to keep spawning reproducible.
+    pub fn pick(&self, rng: &mut impl TurboRand) -> Option<&Particle> {
+        let total = self.total_weight();
+        if total <= 0. {
+            return None;
+        }
+        let mut roll = rng.f32() * total;
+        let mut picked = None;
+        for (particle, weight) in self.positive() {
+            picked = Some(particle);
+            if roll < weight {
+                break;
+            }
+            roll -= weight;
+        }
+        picked
+    }
+
+    fn positive(&self) -> impl Iterator<Item = (&Particle, f32)> {
+        self.0
+            .iter()
+            .filter(|(_, weight)| *weight > 0.)
+            .map(|(particle, weight)| (particle, *weight))
+    }
+}
+
+impl From<Particle> for ParticleMix {
+    fn from(particle: Particle) -> ParticleMix {
+        ParticleMix::single(particle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_turborand::{DelegatedRng, GlobalRng};
+
+    fn mix(entries: &[(&str, f32)]) -> ParticleMix {
+        ParticleMix(
+            entries
+                .iter()
+                .map(|(name, weight)| (Particle::new(name), *weight))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn new_rejects_invalid_weights() {
+        let new = |entries: &[(&str, f32)]| ParticleMix::new(mix(entries).0);
+        assert_eq!(
+            new(&[("Sand", 1.), ("Gravel", -1.)]),
+            Err(ParticleMixError::InvalidWeight("Gravel".to_string()))
+        );
+        assert_eq!(
+            new(&[("Sand", f32::NAN), ("Gravel", 1.)]),
+            Err(ParticleMixError::InvalidWeight("Sand".to_string()))
+        );
+        assert_eq!(
+            new(&[("Sand", 0.), ("Gravel", 0.)]),
+            Err(ParticleMixError::NoPositiveWeight)
+        );
+        assert_eq!(new(&[]), Err(ParticleMixError::NoPositiveWeight));
+        assert_eq!(
+            new(&[("Sand", 0.), ("Gravel", 2.)]),
+            Ok(mix(&[("Sand", 0.), ("Gravel", 2.)]))
+        );
+    }
+
+    #[test]
+    fn pick_skips_entries_without_a_positive_weight() {
+        let mut rng = GlobalRng::with_seed(7);
+        let rng = rng.get_mut();
+
+        // Mixes built directly aren't validated, so they may hold any weight.
+        let gravel = mix(&[("Sand", 0.), ("Gravel", 1.), ("Dirt", -3.)]);
+        for _ in 0..100 {
+            assert_eq!(gravel.pick(rng), Some(&Particle::new("Gravel")));
+        }
+        assert_eq!(gravel.total_weight(), 1.);
+        for nothing in [mix(&[]), mix(&[("Sand", 0.)]), mix(&[("Sand", -1.)])] {
+            assert_eq!(nothing.pick(rng), None);
+            assert_eq!(nothing.total_weight(), 0.);
+        }
+    }
+
+    #[test]
+    fn pick_follows_the_weights() {
+        let mut rng = GlobalRng::with_seed(7);
+        let rng = rng.get_mut();
+        let sand = Particle::new("Sand");
+        let mix = mix(&[("Sand", 3.), ("Gravel", 1.)]);
+
+        let picks = 10_000;
+        let sand_picks = (0..picks).filter(|_| mix.pick(rng) == Some(&sand)).count();
+        let share = sand_picks as f32 / picks as f32;
+        assert!(
+            (0.72..0.78).contains(&share),
+            "sand was picked {share} of the time"
+        );
+    }
+}
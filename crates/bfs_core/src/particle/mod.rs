@@ -1,15 +1,31 @@
 use bevy::prelude::*;
 
+mod brush;
+mod import;
+mod mix;
 mod particle_definitions;
+mod spawn;
+mod stamp;
 mod systems;
 
+pub use brush::*;
+pub use import::*;
+pub use mix::*;
 pub use particle_definitions::*;
+pub use spawn::*;
+pub use stamp::*;
 use systems::*;
 
 pub struct ParticlePlugin;
 
 impl Plugin for ParticlePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((ParticleSystemsPlugin, ParticleDefinitionsPlugin));
+        app.add_plugins((
+            ParticleSystemsPlugin,
+            ParticleDefinitionsPlugin,
+            ParticleStampPlugin,
+            ParticleSpawnPlugin,
+            BrushPlugin,
+        ));
     }
 }
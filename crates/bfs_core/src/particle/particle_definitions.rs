@@ -1,7 +1,11 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 use serde::{Deserialize, Serialize};
 
-use crate::{ChunkMap, ParticleRegistrationEvent, ParticleSimulationSet, ParticleType, ParticleTypeMap};
+use crate::{
+    ChunkMap, ParticleRegistrationEvent, ParticleSimulationSet, ParticleType, ParticleTypeId,
+    ParticleTypeMap,
+};
 
 pub struct ParticleDefinitionsPlugin;
 
@@ -13,7 +17,23 @@ impl Plugin for ParticleDefinitionsPlugin {
             .register_type::<Particle>()
             .add_event::<ResetParticleEvent>()
             .add_event::<RemoveParticleEvent>()
-            .add_observer(on_reset_particle);
+            .add_event::<FreezeRegionEvent>()
+            .add_event::<UnfreezeRegionEvent>()
+            .add_event::<ConvertAllEvent>()
+            .register_type::<Frozen>()
+            .register_type::<Submerged>()
+            .register_type::<Lifetime>()
+            .register_type::<ParticleId>()
+            .register_type::<TypeInstanceLimit>()
+            .register_type::<SpawnLimit>()
+            .init_resource::<ParticleTypeCounts>()
+            .register_type::<ParticleTypeCounts>()
+            .add_observer(on_insert_particle_type_id)
+            .add_observer(on_replace_particle_type_id)
+            .add_observer(on_reset_particle)
+            .add_observer(on_freeze_region)
+            .add_observer(on_unfreeze_region)
+            .add_observer(on_convert_all);
     }
 }
 
@@ -41,6 +61,40 @@ pub struct ParticleBlueprint(pub Particle);
 #[reflect(Component)]
 pub struct Coordinates(pub IVec2);
 
+/// Marker for particles that are temporarily excluded from simulation.
+///
+/// Frozen particles keep all of their components and resume simulating as soon as the marker is
+/// removed.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Frozen;
+
+/// Marks a particle sharing the cell it holds with that cell's occupant, as the cell's secondary
+/// occupant (see [`ChunkMap::secondary`]), such as a gas bubble rising through a liquid.
+///
+/// Submerged particles have no [`Coordinates`] until they take a cell of their own, so the systems
+/// that work on particles by position leave them alone. A [`RemoveParticleEvent`] for their cell
+/// removes the occupant rather than them.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Submerged(pub IVec2);
+
+/// Removes a particle once the given number of simulation ticks have elapsed.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Lifetime(pub u64);
+
+/// An optional identifier for particles that other particles or game logic need to refer to.
+///
+/// Entities change every time a scene is loaded, but ids are saved with the particle. Features that
+/// link particles together should store ids rather than entities and resolve them after loading.
+/// Ids are chosen by the application and are expected to be unique.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct ParticleId(pub u64);
+
 #[derive(Event)]
 pub struct MutateParticleEvent {
     pub entity: Entity,
@@ -58,6 +112,25 @@ pub struct ResetParticleEvent {
     pub entity: Entity,
 }
 
+/// Converts every particle of type `from` into type `to` in place.
+#[derive(Event)]
+pub struct ConvertAllEvent {
+    pub from: String,
+    pub to: String,
+}
+
+/// Freezes every particle within the region (inclusive).
+#[derive(Event)]
+pub struct FreezeRegionEvent {
+    pub region: IRect,
+}
+
+/// Unfreezes every particle within the region (inclusive).
+#[derive(Event)]
+pub struct UnfreezeRegionEvent {
+    pub region: IRect,
+}
+
 pub fn on_reset_particle(
     trigger: Trigger<ResetParticleEvent>,
     mut particle_query: Query<&mut Particle>,
@@ -68,34 +141,242 @@ pub fn on_reset_particle(
         .into_inner();
 }
 
+pub fn on_convert_all(
+    trigger: Trigger<ConvertAllEvent>,
+    parent_query: Query<&Children, With<ParticleType>>,
+    type_map: Res<ParticleTypeMap>,
+    mut ev_mutate_particle: EventWriter<MutateParticleEvent>,
+) {
+    let ConvertAllEvent { from, to } = trigger.event();
+    if type_map.get(to).is_none() {
+        warn!("Ignoring conversion from '{from}' to '{to}': '{to}' not found in particle type map.");
+        return;
+    }
+    let Some(parent_entity) = type_map.get(from) else {
+        warn!("Ignoring conversion from '{from}' to '{to}': '{from}' not found in particle type map.");
+        return;
+    };
+    if let Ok(children) = parent_query.get(*parent_entity) {
+        let particle = Particle::new(to);
+        ev_mutate_particle.send_batch(children.iter().map(|entity| MutateParticleEvent {
+            entity: *entity,
+            particle: particle.clone(),
+        }));
+    }
+}
+
+pub fn on_freeze_region(
+    trigger: Trigger<FreezeRegionEvent>,
+    mut commands: Commands,
+    map: Res<ChunkMap>,
+) {
+    let region = trigger.event().region;
+    map.iter_chunks()
+        .filter(|chunk| chunk.intersects(region))
+        .flat_map(|chunk| chunk.iter())
+        .filter(|(coordinates, _)| region.contains(**coordinates))
+        .for_each(|(_, entity)| {
+            commands.entity(*entity).insert(Frozen);
+        });
+}
+
+pub fn on_unfreeze_region(
+    trigger: Trigger<UnfreezeRegionEvent>,
+    mut commands: Commands,
+    map: Res<ChunkMap>,
+) {
+    let region = trigger.event().region;
+    map.iter_chunks()
+        .filter(|chunk| chunk.intersects(region))
+        .flat_map(|chunk| chunk.iter())
+        .filter(|(coordinates, _)| region.contains(**coordinates))
+        .for_each(|(_, entity)| {
+            commands.entity(*entity).remove::<Frozen>();
+        });
+}
+
+/// Caps how many particles of each type can exist at once, e.g. so decorative smoke can't crowd
+/// out gameplay particles. Types without a limit are unrestricted.
+///
+/// Limits are checked when new particles are spawned. A spawn of a type already at its limit is
+/// dropped, unless `evict_oldest` is set, in which case the oldest particle of that type is removed
+/// to make room. Particles converted into a limited type in place are not checked, so conversions
+/// can push a type past its limit.
+#[derive(Resource, Clone, PartialEq, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct TypeInstanceLimit {
+    pub limits: HashMap<String, usize>,
+    pub evict_oldest: bool,
+}
+
+impl TypeInstanceLimit {
+    pub fn with_limit(mut self, name: impl Into<String>, limit: usize) -> TypeInstanceLimit {
+        self.limits.insert(name.into(), limit);
+        self
+    }
+
+    pub fn with_evict_oldest(mut self, evict_oldest: bool) -> TypeInstanceLimit {
+        self.evict_oldest = evict_oldest;
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.limits.get(name).copied()
+    }
+}
+
+/// Caps how many particles of a type can exist at once when placed on its [`ParticleType`]
+/// entity, e.g. to keep fire or gas from growing without bound.
+///
+/// Spawns of a type already at its limit are dropped. This is checked alongside
+/// [`TypeInstanceLimit`], and a spawn must pass both. Unlike `TypeInstanceLimit`, it never evicts
+/// older particles. The same caveat about in-place conversions applies.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct SpawnLimit(pub u32);
+
+/// The number of live particles of each type, kept up to date as particles are registered,
+/// converted and despawned.
+#[derive(Resource, Clone, PartialEq, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct ParticleTypeCounts {
+    counts: HashMap<ParticleTypeId, usize>,
+}
+
+impl ParticleTypeCounts {
+    pub fn get(&self, id: ParticleTypeId) -> usize {
+        self.counts.get(&id).copied().unwrap_or(0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ParticleTypeId, usize)> + '_ {
+        self.counts.iter().map(|(id, count)| (*id, *count))
+    }
+}
+
+pub fn on_insert_particle_type_id(
+    trigger: Trigger<OnInsert, ParticleTypeId>,
+    particle_query: Query<&ParticleTypeId, With<Particle>>,
+    mut counts: ResMut<ParticleTypeCounts>,
+) {
+    if let Ok(id) = particle_query.get(trigger.entity()) {
+        *counts.counts.entry(*id).or_default() += 1;
+    }
+}
+
+/// Runs before a particle's id is overwritten, and when the particle is despawned.
+pub fn on_replace_particle_type_id(
+    trigger: Trigger<OnReplace, ParticleTypeId>,
+    particle_query: Query<&ParticleTypeId, With<Particle>>,
+    mut counts: ResMut<ParticleTypeCounts>,
+) {
+    if let Ok(id) = particle_query.get(trigger.entity()) {
+        if let Some(count) = counts.counts.get_mut(id) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+/// Particles whose type was just set, along with whether they already hold a cell, either as its
+/// occupant or as its secondary occupant.
+type NewParticleQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Particle,
+        &'static Transform,
+        Entity,
+        Has<Coordinates>,
+        Has<Submerged>,
+    ),
+    Changed<Particle>,
+>;
+
 pub fn handle_new_particles(
     mut commands: Commands,
-    parent_query: Query<Entity, With<ParticleType>>,
-    particle_query: Query<(&Particle, &Transform, Entity), Changed<Particle>>,
+    parent_query: Query<(Entity, Option<&Children>, Option<&SpawnLimit>), With<ParticleType>>,
+    particle_query: NewParticleQuery,
+    coordinates_query: Query<&Coordinates>,
     mut map: ResMut<ChunkMap>,
     type_map: Res<ParticleTypeMap>,
+    instance_limit: Option<Res<TypeInstanceLimit>>,
+    counts: Res<ParticleTypeCounts>,
     mut ev_particle_registered: EventWriter<ParticleRegistrationEvent>,
 ) {
     let mut entities: Vec<Entity> = vec![];
-    for (particle_type, transform, entity) in particle_query.iter() {
+    // Particles of each limited type accepted and evicted so far this frame, as neither is
+    // reflected in the counts until commands are applied.
+    let mut spawned: HashMap<String, (usize, usize)> = HashMap::default();
+    for (particle_type, transform, entity, has_coordinates, submerged) in particle_query.iter() {
+        let registered = has_coordinates || submerged;
         let coordinates = IVec2::new(
             transform.translation.x as i32,
             transform.translation.y as i32,
         );
 
-        let new = map.insert_no_overwrite(coordinates, entity);
-        if *new != entity {
+        let parent = type_map
+            .get(&particle_type.name)
+            .and_then(|parent_entity| parent_query.get(*parent_entity).ok());
+        let spawn_limit = parent.and_then(|(_, _, spawn_limit)| spawn_limit);
+        let limit = instance_limit
+            .as_ref()
+            .and_then(|instance_limit| instance_limit.get(&particle_type.name));
+        if (spawn_limit.is_some() || limit.is_some())
+            && !registered
+            && !map.is_occupied(coordinates)
+        {
+            let children = parent.and_then(|(_, children, _)| children);
+            let live = type_map
+                .id_of(&particle_type.name)
+                .map_or(0, |id| counts.get(id));
+            let (accepted, evicted) = spawned.entry(particle_type.name.clone()).or_default();
+            if spawn_limit
+                .is_some_and(|spawn_limit| live + *accepted - *evicted >= spawn_limit.0 as usize)
+            {
+                commands.entity(entity).despawn();
+                continue;
+            }
+            if limit.is_some_and(|limit| live + *accepted - *evicted >= limit) {
+                // Children are appended as particles spawn, so the first is the oldest.
+                let oldest = instance_limit
+                    .as_ref()
+                    .is_some_and(|instance_limit| instance_limit.evict_oldest)
+                    .then(|| children.and_then(|children| children.get(*evicted)))
+                    .flatten()
+                    .and_then(|oldest| coordinates_query.get(*oldest).ok());
+                let Some(oldest) = oldest else {
+                    commands.entity(entity).despawn();
+                    continue;
+                };
+                commands.trigger(RemoveParticleEvent {
+                    coordinates: oldest.0,
+                    despawn: true,
+                });
+                *evicted += 1;
+            }
+            *accepted += 1;
+        }
+
+        // Particles spawned outside of the map or onto an occupied cell are discarded. Submerged
+        // particles changing type already hold their cell as its secondary occupant.
+        if !submerged && map.insert_no_overwrite(coordinates, entity).copied() != Some(entity) {
             commands.entity(entity).despawn();
             continue;
         }
 
         if let Some(parent_entity) = type_map.get(&particle_type.name) {
-            if let Ok(parent_entity) = parent_query.get(*parent_entity) {
+            if let Ok((parent_entity, _, _)) = parent_query.get(*parent_entity) {
                 entities.push(entity);
                 commands.entity(parent_entity).add_child(entity);
-                commands.entity(entity).insert((
-                    Coordinates(coordinates),
-                ));
+                let type_id = type_map
+                    .id_of(&particle_type.name)
+                    .expect("Particle type was registered without an id");
+                if submerged {
+                    commands.entity(entity).insert(type_id);
+                } else {
+                    commands
+                        .entity(entity)
+                        .insert((Coordinates(coordinates), type_id));
+                }
             }
         } else {
             panic!(
@@ -106,3 +387,123 @@ pub fn handle_new_particles(
     }
     ev_particle_registered.send(ParticleRegistrationEvent { entities });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing, FallingSandCorePlugin};
+
+    fn app_with_type(particle_type: impl Bundle) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FallingSandCorePlugin::default()));
+        app.world_mut().spawn(particle_type);
+        app.update();
+        app
+    }
+
+    fn spawn(app: &mut App, name: &str, xs: impl IntoIterator<Item = i32>) {
+        testing::spawn(app, name, xs.into_iter().map(|x| IVec2::new(x, 0)));
+    }
+
+    fn count(app: &App, name: &str) -> usize {
+        let id = app
+            .world()
+            .resource::<ParticleTypeMap>()
+            .id_of(name)
+            .unwrap();
+        app.world().resource::<ParticleTypeCounts>().get(id)
+    }
+
+    fn live(app: &mut App, name: &str) -> Vec<i32> {
+        testing::positions(app, name)
+            .into_iter()
+            .map(|position| position.x)
+            .collect()
+    }
+
+    #[test]
+    fn spawn_limit_caps_live_particles() {
+        let mut app = app_with_type((ParticleType::new("Smoke"), SpawnLimit(3)));
+
+        spawn(&mut app, "Smoke", 0..5);
+        app.update();
+        assert_eq!(count(&app, "Smoke"), 3);
+        assert_eq!(live(&mut app, "Smoke").len(), 3);
+
+        spawn(&mut app, "Smoke", 5..10);
+        app.update();
+        assert_eq!(count(&app, "Smoke"), 3);
+        assert_eq!(live(&mut app, "Smoke").len(), 3);
+    }
+
+    #[test]
+    fn removed_particles_free_their_spawn_limit() {
+        let mut app = app_with_type((ParticleType::new("Smoke"), SpawnLimit(3)));
+        spawn(&mut app, "Smoke", 0..3);
+        app.update();
+
+        let removed = live(&mut app, "Smoke")[0];
+        app.world_mut().trigger(RemoveParticleEvent {
+            coordinates: IVec2::new(removed, 0),
+            despawn: true,
+        });
+        app.world_mut().flush();
+        assert_eq!(count(&app, "Smoke"), 2);
+
+        spawn(&mut app, "Smoke", 10..12);
+        app.update();
+        assert_eq!(count(&app, "Smoke"), 3);
+        assert_eq!(live(&mut app, "Smoke").len(), 3);
+    }
+
+    #[test]
+    fn converted_particles_are_counted_as_their_new_type() {
+        let mut app = app_with_type(ParticleType::new("Water"));
+        app.world_mut().spawn(ParticleType::new("Steam"));
+        spawn(&mut app, "Water", 0..4);
+        app.update();
+
+        app.world_mut().trigger(ConvertAllEvent {
+            from: "Water".to_string(),
+            to: "Steam".to_string(),
+        });
+        app.update();
+        app.update();
+        assert_eq!(count(&app, "Water"), 0);
+        assert_eq!(count(&app, "Steam"), 4);
+    }
+
+    #[test]
+    fn type_instance_limit_evicts_the_oldest_particles_first() {
+        let mut app = app_with_type(ParticleType::new("Smoke"));
+        app.insert_resource(
+            TypeInstanceLimit::default()
+                .with_limit("Smoke", 2)
+                .with_evict_oldest(true),
+        );
+        for x in 0..5 {
+            spawn(&mut app, "Smoke", [x]);
+            app.update();
+        }
+        assert_eq!(live(&mut app, "Smoke"), vec![3, 4]);
+        assert_eq!(count(&app, "Smoke"), 2);
+    }
+
+    #[test]
+    fn type_instance_limit_holds_across_frames() {
+        let mut app = app_with_type(ParticleType::new("Smoke"));
+        app.world_mut().spawn(ParticleType::new("Sand"));
+        app.insert_resource(TypeInstanceLimit::default().with_limit("Smoke", 3));
+        app.update();
+        for frame in 0..5 {
+            spawn(&mut app, "Smoke", [frame * 2, frame * 2 + 1]);
+            spawn(&mut app, "Sand", [100 + frame]);
+            app.update();
+            assert!(count(&app, "Smoke") <= 3);
+            assert_eq!(live(&mut app, "Smoke").len(), count(&app, "Smoke"));
+        }
+        // Without eviction, the first particles keep their places.
+        assert_eq!(live(&mut app, "Smoke"), vec![0, 1, 2]);
+        assert_eq!(count(&app, "Sand"), 5);
+    }
+}
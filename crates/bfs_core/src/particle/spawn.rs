@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::{ChunkMap, Particle, RemoveParticleEvent};
+
+pub struct ParticleSpawnPlugin;
+
+impl Plugin for ParticleSpawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnParticlesEvent>()
+            .add_observer(on_spawn_particles);
+    }
+}
+
+/// Spawns `particle` at every position in `positions` in a single batch, which is much cheaper
+/// than spawning many particles one by one.
+///
+/// Positions outside of the map are ignored, as are repeated positions. Occupied positions are
+/// skipped, unless `overwrite` is set, in which case their particles are despawned and replaced.
+#[derive(Event, Clone, Debug)]
+pub struct SpawnParticlesEvent {
+    pub particle: Particle,
+    pub positions: Vec<IVec2>,
+    pub overwrite: bool,
+}
+
+impl SpawnParticlesEvent {
+    pub fn new(particle: Particle, positions: Vec<IVec2>) -> SpawnParticlesEvent {
+        SpawnParticlesEvent {
+            particle,
+            positions,
+            overwrite: false,
+        }
+    }
+
+    /// Replaces particles at occupied positions instead of skipping them.
+    pub fn overwriting(mut self) -> SpawnParticlesEvent {
+        self.overwrite = true;
+        self
+    }
+}
+
+pub fn on_spawn_particles(
+    trigger: Trigger<SpawnParticlesEvent>,
+    mut commands: Commands,
+    map: Res<ChunkMap>,
+) {
+    let event = trigger.event();
+    let mut seen: HashSet<IVec2> = HashSet::default();
+    let mut positions: Vec<IVec2> = Vec::with_capacity(event.positions.len());
+    for position in &event.positions {
+        if !map.contains(*position) || !seen.insert(*position) {
+            continue;
+        }
+        if map.is_occupied(*position) {
+            if !event.overwrite {
+                continue;
+            }
+            commands.trigger(RemoveParticleEvent {
+                coordinates: *position,
+                despawn: true,
+            });
+        }
+        positions.push(*position);
+    }
+
+    let particle = event.particle.clone();
+    commands.spawn_batch(positions.into_iter().map(move |position| {
+        (
+            particle.clone(),
+            Transform::from_xyz(position.x as f32, position.y as f32, 0.0),
+        )
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::positions;
+    use crate::{FallingSandCorePlugin, ParticleType};
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FallingSandCorePlugin::default()));
+        app.world_mut().spawn(ParticleType::new("Sand"));
+        app.world_mut().spawn(ParticleType::new("Water"));
+        app.update();
+        app
+    }
+
+    /// 1000 positions in a 40 by 25 block, followed by the first 100 of them again.
+    fn cells() -> Vec<IVec2> {
+        let block: Vec<IVec2> = (0..25)
+            .flat_map(|y| (0..40).map(move |x| IVec2::new(x, y)))
+            .collect();
+        [block.as_slice(), &block[..100]].concat()
+    }
+
+    fn trigger(app: &mut App, event: SpawnParticlesEvent) {
+        app.world_mut().trigger(event);
+        app.world_mut().flush();
+        app.update();
+    }
+
+    #[test]
+    fn spawns_each_position_once() {
+        let mut app = app();
+        trigger(
+            &mut app,
+            SpawnParticlesEvent::new(Particle::new("Sand"), cells()),
+        );
+
+        let expected = cells()[..1000].to_vec();
+        assert_eq!(positions(&mut app, "Sand"), expected);
+        let map = app.world().resource::<ChunkMap>();
+        assert!(expected.iter().all(|position| map.is_occupied(*position)));
+        assert_eq!(
+            app.world_mut()
+                .query_filtered::<(), With<Particle>>()
+                .iter(app.world())
+                .count(),
+            1000
+        );
+    }
+
+    #[test]
+    fn occupied_positions_are_skipped_unless_overwriting() {
+        let mut app = app();
+        let water: Vec<IVec2> = cells().into_iter().step_by(2).take(500).collect();
+        trigger(
+            &mut app,
+            SpawnParticlesEvent::new(Particle::new("Water"), water.clone()),
+        );
+
+        trigger(
+            &mut app,
+            SpawnParticlesEvent::new(Particle::new("Sand"), cells()),
+        );
+        assert_eq!(positions(&mut app, "Water"), water);
+        assert_eq!(positions(&mut app, "Sand").len(), 500);
+
+        trigger(
+            &mut app,
+            SpawnParticlesEvent::new(Particle::new("Sand"), cells()).overwriting(),
+        );
+        assert!(positions(&mut app, "Water").is_empty());
+        assert_eq!(positions(&mut app, "Sand"), cells()[..1000].to_vec());
+        assert_eq!(
+            app.world_mut()
+                .query_filtered::<(), With<Particle>>()
+                .iter(app.world())
+                .count(),
+            1000
+        );
+    }
+}
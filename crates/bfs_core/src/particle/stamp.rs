@@ -0,0 +1,393 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::{ChunkMap, Coordinates, Particle};
+
+pub struct ParticleStampPlugin;
+
+impl Plugin for ParticleStampPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleStamps>()
+            .register_type::<ParticleGroup>()
+            .add_event::<SpawnStampEvent>()
+            .add_event::<TranslateGroupEvent>()
+            .add_observer(on_spawn_stamp)
+            .add_observer(on_translate_group);
+    }
+}
+
+/// A multi-cell particle shape that is placed as a single unit, such as a boulder or a pipe
+/// segment. Each cell is an offset relative to the stamp's origin.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct ParticleStamp {
+    pub cells: Vec<(IVec2, Particle)>,
+}
+
+impl ParticleStamp {
+    pub fn new(cells: Vec<(IVec2, Particle)>) -> ParticleStamp {
+        ParticleStamp { cells }
+    }
+}
+
+/// Named stamps available to [`SpawnStampEvent`].
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ParticleStamps(HashMap<String, ParticleStamp>);
+
+impl ParticleStamps {
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        stamp: ParticleStamp,
+    ) -> Option<ParticleStamp> {
+        self.0.insert(name.into(), stamp)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParticleStamp> {
+        self.0.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<ParticleStamp> {
+        self.0.remove(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ParticleStamp)> {
+        self.0.iter()
+    }
+}
+
+/// Tags particles that were placed together so they can be manipulated as a group.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct ParticleGroup(pub u64);
+
+/// How a stamp is placed when some of its cells are already occupied or fall outside the map.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum StampPlacement {
+    /// Place every cell that is free and skip the rest.
+    #[default]
+    Skip,
+    /// Place nothing unless every cell is free.
+    Abort,
+}
+
+/// Spawns the named stamp with its origin at `origin`.
+#[derive(Event, Clone, Debug)]
+pub struct SpawnStampEvent {
+    pub stamp: String,
+    pub origin: IVec2,
+    pub placement: StampPlacement,
+    pub group: Option<ParticleGroup>,
+}
+
+impl SpawnStampEvent {
+    pub fn new(stamp: impl Into<String>, origin: IVec2) -> SpawnStampEvent {
+        SpawnStampEvent {
+            stamp: stamp.into(),
+            origin,
+            placement: StampPlacement::default(),
+            group: None,
+        }
+    }
+}
+
+pub fn on_spawn_stamp(
+    trigger: Trigger<SpawnStampEvent>,
+    mut commands: Commands,
+    stamps: Res<ParticleStamps>,
+    map: Res<ChunkMap>,
+) {
+    let event = trigger.event();
+    let Some(stamp) = stamps.get(&event.stamp) else {
+        warn!("Ignoring stamp '{}': stamp not found.", event.stamp);
+        return;
+    };
+
+    let is_free = |coordinates: &IVec2| {
+        map.chunk(coordinates).is_some_and(|chunk| {
+            chunk.region().contains(*coordinates) && !chunk.is_occupied(*coordinates)
+        })
+    };
+
+    let cells: Vec<(IVec2, &Particle)> = stamp
+        .cells
+        .iter()
+        .map(|(offset, particle)| (event.origin + *offset, particle))
+        .collect();
+
+    if event.placement == StampPlacement::Abort
+        && !cells.iter().all(|(coordinates, _)| is_free(coordinates))
+    {
+        return;
+    }
+
+    for (coordinates, particle) in cells {
+        if !is_free(&coordinates) {
+            continue;
+        }
+        let mut entity_commands = commands.spawn((
+            particle.clone(),
+            Transform::from_xyz(coordinates.x as f32, coordinates.y as f32, 0.),
+        ));
+        if let Some(group) = event.group {
+            entity_commands.insert(group);
+        }
+    }
+}
+
+/// Moves every particle in `group` by `offset`, e.g. to nudge a stamp into place after spawning
+/// it.
+///
+/// A member is blocked if its destination is outside the map or holds a particle from outside the
+/// group. With [`StampPlacement::Abort`], nothing moves if any member is blocked. With
+/// [`StampPlacement::Skip`], blocked members stay where they are, as do members that would land
+/// on them, so a group pushed partially off the edge of the map keeps the part that fits.
+#[derive(Event, Clone, Debug)]
+pub struct TranslateGroupEvent {
+    pub group: ParticleGroup,
+    pub offset: IVec2,
+    pub placement: StampPlacement,
+}
+
+impl TranslateGroupEvent {
+    pub fn new(group: ParticleGroup, offset: IVec2) -> TranslateGroupEvent {
+        TranslateGroupEvent {
+            group,
+            offset,
+            placement: StampPlacement::default(),
+        }
+    }
+}
+
+pub fn on_translate_group(
+    trigger: Trigger<TranslateGroupEvent>,
+    mut map: ResMut<ChunkMap>,
+    mut particle_query: Query<(Entity, &ParticleGroup, &mut Coordinates, &mut Transform)>,
+) {
+    let event = trigger.event();
+    if event.offset == IVec2::ZERO {
+        return;
+    }
+
+    let members: HashMap<IVec2, Entity> = particle_query
+        .iter()
+        .filter(|(_, group, _, _)| **group == event.group)
+        .map(|(entity, _, coordinates, _)| (coordinates.0, entity))
+        .collect();
+
+    let is_blocked = |coordinates: &IVec2| {
+        let on_map = map
+            .chunk(coordinates)
+            .is_some_and(|chunk| chunk.region().contains(*coordinates));
+        !on_map || map.is_occupied(*coordinates) && !members.contains_key(coordinates)
+    };
+
+    let mut staying: HashSet<IVec2> = members
+        .keys()
+        .filter(|coordinates| is_blocked(&(**coordinates + event.offset)))
+        .copied()
+        .collect();
+    if event.placement == StampPlacement::Abort && !staying.is_empty() {
+        return;
+    }
+    // Members that stay put block any member that would have moved onto them, which may in turn
+    // block others further along.
+    loop {
+        let blocked: Vec<IVec2> = members
+            .keys()
+            .filter(|coordinates| {
+                !staying.contains(*coordinates) && staying.contains(&(**coordinates + event.offset))
+            })
+            .copied()
+            .collect();
+        if blocked.is_empty() {
+            break;
+        }
+        staying.extend(blocked);
+    }
+
+    let moving: Vec<(IVec2, Entity)> = members
+        .iter()
+        .filter(|(coordinates, _)| !staying.contains(*coordinates))
+        .map(|(coordinates, entity)| (*coordinates, *entity))
+        .collect();
+    // Clear every source cell before filling any destination, as members may move into cells
+    // that other members are leaving.
+    for (coordinates, _) in &moving {
+        map.remove(coordinates);
+    }
+    for (coordinates, entity) in moving {
+        let destination = coordinates + event.offset;
+        map.insert_overwrite(destination, entity);
+        if let Ok((_, _, mut coordinates, mut transform)) = particle_query.get_mut(entity) {
+            coordinates.0 = destination;
+            transform.translation.x = destination.x as f32;
+            transform.translation.y = destination.y as f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{positions, spawn};
+    use crate::{FallingSandCorePlugin, ParticleType};
+
+    const GROUP: ParticleGroup = ParticleGroup(1);
+
+    /// A 64x64 map with an "L" shaped stamp of three rocks: its origin, the cell above it and the
+    /// cell to its right.
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FallingSandCorePlugin::default()));
+        app.insert_resource(ChunkMap::new(64));
+        app.world_mut().spawn(ParticleType::new("Rock"));
+        app.world_mut().spawn(ParticleType::new("Wall"));
+        app.world_mut().resource_mut::<ParticleStamps>().insert(
+            "L",
+            ParticleStamp::new(
+                [IVec2::ZERO, IVec2::Y, IVec2::X]
+                    .into_iter()
+                    .map(|offset| (offset, Particle::new("Rock")))
+                    .collect(),
+            ),
+        );
+        app.update();
+        app
+    }
+
+    fn stamp(app: &mut App, origin: IVec2, placement: StampPlacement) {
+        app.world_mut().trigger(SpawnStampEvent {
+            placement,
+            group: Some(GROUP),
+            ..SpawnStampEvent::new("L", origin)
+        });
+        app.world_mut().flush();
+        app.update();
+    }
+
+    fn translate(app: &mut App, offset: IVec2, placement: StampPlacement) {
+        app.world_mut().trigger(TranslateGroupEvent {
+            placement,
+            ..TranslateGroupEvent::new(GROUP, offset)
+        });
+        app.update();
+    }
+
+    /// The cells of every member of [`GROUP`], checked against the map, sorted by row, then
+    /// column.
+    fn members(app: &mut App) -> Vec<IVec2> {
+        let mut members: Vec<(IVec2, Entity)> = app
+            .world_mut()
+            .query::<(Entity, &ParticleGroup, &Coordinates)>()
+            .iter(app.world())
+            .filter(|(_, group, _)| **group == GROUP)
+            .map(|(entity, _, coordinates)| (coordinates.0, entity))
+            .collect();
+        let map = app.world().resource::<ChunkMap>();
+        for (coordinates, entity) in &members {
+            assert_eq!(map.entity(coordinates), Some(entity));
+        }
+        members.sort_by_key(|(coordinates, _)| (coordinates.y, coordinates.x));
+        members
+            .into_iter()
+            .map(|(coordinates, _)| coordinates)
+            .collect()
+    }
+
+    #[test]
+    fn stamps_are_placed_relative_to_their_origin() {
+        let mut app = app();
+        stamp(&mut app, IVec2::new(5, -5), StampPlacement::Skip);
+
+        let placed = vec![IVec2::new(5, -5), IVec2::new(6, -5), IVec2::new(5, -4)];
+        assert_eq!(members(&mut app), placed);
+        assert_eq!(positions(&mut app, "Rock"), placed);
+    }
+
+    #[test]
+    fn occupied_cells_are_skipped_or_abort_the_stamp() {
+        let mut app = app();
+        spawn(&mut app, "Wall", [IVec2::new(6, -5)]);
+        app.update();
+
+        stamp(&mut app, IVec2::new(5, -5), StampPlacement::Abort);
+        assert_eq!(members(&mut app), vec![]);
+
+        stamp(&mut app, IVec2::new(5, -5), StampPlacement::Skip);
+        assert_eq!(
+            members(&mut app),
+            vec![IVec2::new(5, -5), IVec2::new(5, -4)]
+        );
+        assert_eq!(positions(&mut app, "Wall"), vec![IVec2::new(6, -5)]);
+    }
+
+    #[test]
+    fn stamps_crossing_the_map_edge() {
+        let mut app = app();
+        // The cell to the right of the origin lies past the right edge of the map.
+        let edge = IVec2::new(app.world().resource::<ChunkMap>().world_bounds_i().max.x, 0);
+
+        stamp(&mut app, edge, StampPlacement::Abort);
+        assert_eq!(members(&mut app), vec![]);
+
+        stamp(&mut app, edge, StampPlacement::Skip);
+        assert_eq!(members(&mut app), vec![edge, edge + IVec2::Y]);
+    }
+
+    #[test]
+    fn groups_shift_onto_their_own_cells() {
+        let mut app = app();
+        stamp(&mut app, IVec2::ZERO, StampPlacement::Skip);
+
+        // The origin moves onto the cell its right-hand neighbor leaves.
+        translate(&mut app, IVec2::X, StampPlacement::Abort);
+        assert_eq!(
+            members(&mut app),
+            vec![IVec2::new(1, 0), IVec2::new(2, 0), IVec2::new(1, 1)]
+        );
+        let map = app.world().resource::<ChunkMap>();
+        assert!(!map.is_occupied(IVec2::ZERO));
+        assert!(!map.is_occupied(IVec2::Y));
+    }
+
+    #[test]
+    fn blocked_members_stay_along_with_those_behind_them() {
+        let mut app = app();
+        stamp(&mut app, IVec2::ZERO, StampPlacement::Skip);
+        spawn(&mut app, "Wall", [IVec2::new(2, 0)]);
+        app.update();
+        let start = members(&mut app);
+
+        translate(&mut app, IVec2::X, StampPlacement::Abort);
+        assert_eq!(members(&mut app), start);
+
+        // The bottom row is held up by the wall, the top one moves on.
+        translate(&mut app, IVec2::X, StampPlacement::Skip);
+        assert_eq!(
+            members(&mut app),
+            vec![IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(1, 1)]
+        );
+        assert_eq!(positions(&mut app, "Wall"), vec![IVec2::new(2, 0)]);
+    }
+
+    #[test]
+    fn groups_pushed_off_the_map_keep_the_part_that_fits() {
+        let mut app = app();
+        let max_x = app.world().resource::<ChunkMap>().world_bounds_i().max.x;
+        let origin = IVec2::new(max_x - 1, 0);
+        stamp(&mut app, origin, StampPlacement::Skip);
+        let start = members(&mut app);
+
+        translate(&mut app, IVec2::X, StampPlacement::Abort);
+        assert_eq!(members(&mut app), start);
+
+        translate(&mut app, IVec2::X, StampPlacement::Skip);
+        assert_eq!(
+            members(&mut app),
+            vec![origin, origin + IVec2::X, IVec2::new(max_x, 1)]
+        );
+    }
+}
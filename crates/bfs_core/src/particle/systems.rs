@@ -1,12 +1,22 @@
 use bevy::prelude::*;
 
-use crate::{Particle, MutateParticleEvent, ParticleSimulationSet};
+use crate::{
+    Coordinates, Frozen, Lifetime, MutateParticleEvent, Particle, ParticleSimulation,
+    ParticleSimulationSet, RemoveParticleEvent,
+};
 
 pub struct ParticleSystemsPlugin;
 
 impl Plugin for ParticleSystemsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, ev_mutate_particle.in_set(ParticleSimulationSet));
+        app.add_systems(
+            ParticleSimulation,
+            ev_mutate_particle.in_set(ParticleSimulationSet),
+        )
+        .add_systems(
+            ParticleSimulation,
+            handle_lifetimes.in_set(ParticleSimulationSet),
+        );
     }
 }
 
@@ -20,3 +30,21 @@ pub fn ev_mutate_particle(
     }
 }
 
+
+pub fn handle_lifetimes(
+    mut commands: Commands,
+    mut lifetime_query: Query<(&mut Lifetime, &Coordinates), Without<Frozen>>,
+) {
+    lifetime_query
+        .iter_mut()
+        .for_each(|(mut lifetime, coordinates)| {
+            if lifetime.0 == 0 {
+                commands.trigger(RemoveParticleEvent {
+                    coordinates: coordinates.0,
+                    despawn: true,
+                });
+            } else {
+                lifetime.0 -= 1;
+            }
+        });
+}
@@ -0,0 +1,187 @@
+use bevy::ecs::world::Command;
+use bevy::prelude::*;
+
+use crate::ParticleTypeMap;
+
+/// A particle component that is configured per particle type through a blueprint component on the
+/// type's entity.
+pub trait BlueprintComponent: Component + Clone {
+    /// The component holding this value on the particle type entity.
+    type Blueprint: Component;
+
+    fn into_blueprint(self) -> Self::Blueprint;
+
+    /// Returns the value a particle should hold after its type's value changes to `self`. This
+    /// defaults to `self`, but components that also carry per-particle state can override it to
+    /// preserve that state.
+    fn updated(&self, _existing: &Self) -> Self {
+        self.clone()
+    }
+}
+
+/// Sets a single component on a particle type, and optionally pushes the new value to every
+/// existing particle of that type without resetting them.
+///
+/// Unlike resetting the type, particles keep their position and any other state.
+pub struct UpdateTypeComponent<T: BlueprintComponent> {
+    pub type_name: String,
+    pub value: T,
+    pub propagate: bool,
+}
+
+impl<T: BlueprintComponent> Command for UpdateTypeComponent<T> {
+    fn apply(self, world: &mut World) {
+        let Some(type_entity) = world
+            .get_resource::<ParticleTypeMap>()
+            .and_then(|type_map| type_map.get(&self.type_name).copied())
+        else {
+            warn!(
+                "Ignoring component update for '{}': type not found in particle type map.",
+                self.type_name
+            );
+            return;
+        };
+
+        world
+            .entity_mut(type_entity)
+            .insert(self.value.clone().into_blueprint());
+
+        if !self.propagate {
+            return;
+        }
+        let children: Vec<Entity> = world
+            .get::<Children>(type_entity)
+            .map(|children| children.to_vec())
+            .unwrap_or_default();
+        for child in children {
+            let Ok(mut child) = world.get_entity_mut(child) else {
+                continue;
+            };
+            let value = match child.get::<T>() {
+                Some(existing) => self.value.updated(existing),
+                None => self.value.clone(),
+            };
+            child.insert(value);
+        }
+    }
+}
+
+/// Queues an [`UpdateTypeComponent`] for the named particle type.
+pub fn update_type_component<T: BlueprintComponent>(
+    commands: &mut Commands,
+    type_name: impl Into<String>,
+    value: T,
+    propagate: bool,
+) {
+    commands.queue(UpdateTypeComponent {
+        type_name: type_name.into(),
+        value,
+        propagate,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{positions, spawn};
+    use crate::{FallingSandCorePlugin, ParticleType};
+
+    /// A component with a per-type `level` and a per-particle `ticks` count.
+    #[derive(Component, Clone, PartialEq, Debug)]
+    struct Heat {
+        level: u32,
+        ticks: u32,
+    }
+
+    #[derive(Component, Clone, PartialEq, Debug)]
+    struct HeatBlueprint(Heat);
+
+    impl BlueprintComponent for Heat {
+        type Blueprint = HeatBlueprint;
+
+        fn into_blueprint(self) -> HeatBlueprint {
+            HeatBlueprint(self)
+        }
+
+        fn updated(&self, existing: &Heat) -> Heat {
+            Heat {
+                level: self.level,
+                ticks: existing.ticks,
+            }
+        }
+    }
+
+    /// Spawns two "Sand" particles, only the first of which holds a [`Heat`], and returns both.
+    fn app() -> (App, [Entity; 2]) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FallingSandCorePlugin::default()));
+        app.world_mut().spawn((
+            ParticleType::new("Sand"),
+            HeatBlueprint(Heat { level: 1, ticks: 0 }),
+        ));
+        app.update();
+        spawn(&mut app, "Sand", [IVec2::new(0, 0), IVec2::new(1, 0)]);
+        app.update();
+
+        let children = app.world().get::<Children>(sand(&app)).unwrap();
+        let particles = [children[0], children[1]];
+        app.world_mut()
+            .entity_mut(particles[0])
+            .insert(Heat { level: 1, ticks: 5 });
+        (app, particles)
+    }
+
+    fn update(app: &mut App, level: u32, propagate: bool) {
+        let value = Heat { level, ticks: 0 };
+        update_type_component(&mut app.world_mut().commands(), "Sand", value, propagate);
+        app.world_mut().flush();
+    }
+
+    fn sand(app: &App) -> Entity {
+        *app.world()
+            .resource::<ParticleTypeMap>()
+            .get(&"Sand".to_string())
+            .unwrap()
+    }
+
+    fn blueprint(app: &App) -> Heat {
+        app.world()
+            .get::<HeatBlueprint>(sand(app))
+            .unwrap()
+            .0
+            .clone()
+    }
+
+    #[test]
+    fn existing_particles_pick_up_the_new_value() {
+        let (mut app, [heated, unheated]) = app();
+        let before = positions(&mut app, "Sand");
+        update(&mut app, 3, true);
+
+        assert_eq!(blueprint(&app), Heat { level: 3, ticks: 0 });
+        // The particle's own state survives the update, and particles without the component get it.
+        assert_eq!(
+            app.world().get::<Heat>(heated),
+            Some(&Heat { level: 3, ticks: 5 })
+        );
+        assert_eq!(
+            app.world().get::<Heat>(unheated),
+            Some(&Heat { level: 3, ticks: 0 })
+        );
+        app.update();
+        assert_eq!(positions(&mut app, "Sand"), before);
+    }
+
+    #[test]
+    fn updates_without_propagation_only_change_the_type() {
+        let (mut app, [heated, unheated]) = app();
+        update(&mut app, 3, false);
+
+        assert_eq!(blueprint(&app), Heat { level: 3, ticks: 0 });
+        assert_eq!(
+            app.world().get::<Heat>(heated),
+            Some(&Heat { level: 1, ticks: 5 })
+        );
+        assert_eq!(app.world().get::<Heat>(unheated), None);
+    }
+}
@@ -1,8 +1,10 @@
 use bevy::prelude::*;
 
+mod blueprint;
 mod particle_type;
 mod systems;
 
+pub use blueprint::*;
 pub use particle_type::*;
 use systems::*;
 
@@ -29,10 +29,46 @@ impl ParticleType {
     }
 }
 
+/// Sent once a particle type has been added to the [`ParticleTypeMap`], however it was created
+/// (loaded from an asset, built in code or edited at runtime).
+///
+/// Changing an existing type doesn't send it again, unless the type is renamed, which registers
+/// it under its new name.
+///
+/// This is both triggered for observers and sent as a regular event. Use it to validate or
+/// augment particle types, e.g. to attach a gameplay component to every type of some kind.
+#[derive(Event, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParticleTypeRegistered {
+    pub entity: Entity,
+    pub name: String,
+}
+
+/// An interned identifier for a particle type, assigned by [`ParticleTypeMap`] when the type is
+/// registered. Comparing ids is much cheaper than comparing particle names.
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    Component,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+#[reflect(Component)]
+pub struct ParticleTypeId(pub u32);
+
 #[derive(Resource, Clone, Default, Debug, Reflect)]
 #[reflect(Resource)]
 pub struct ParticleTypeMap {
     map: HashMap<String, Entity>,
+    ids: HashMap<String, ParticleTypeId>,
+    names: Vec<String>,
 }
 
 impl ParticleTypeMap {
@@ -45,9 +81,24 @@ impl ParticleTypeMap {
     }
 
     pub fn insert(&mut self, ptype: String, entity: Entity) -> &mut Entity {
+        if !self.ids.contains_key(&ptype) {
+            let id = ParticleTypeId(self.names.len() as u32);
+            self.ids.insert(ptype.clone(), id);
+            self.names.push(ptype.clone());
+        }
         self.map.entry(ptype).or_insert(entity)
     }
 
+    /// Returns the interned id for a particle type. Ids are stable for the lifetime of the map.
+    pub fn id_of(&self, ptype: &str) -> Option<ParticleTypeId> {
+        self.ids.get(ptype).copied()
+    }
+
+    /// Returns the particle type name an id was assigned to.
+    pub fn name_of(&self, id: ParticleTypeId) -> Option<&String> {
+        self.names.get(id.0 as usize)
+    }
+
     pub fn get(&self, ptype: &String) -> Option<&Entity> {
         self.map.get(ptype)
     }
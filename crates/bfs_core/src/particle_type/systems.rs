@@ -4,20 +4,88 @@ pub struct ParticleTypeSystemsPlugin;
 
 impl Plugin for ParticleTypeSystemsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, handle_new_particle_types);
+        app.add_event::<ParticleTypeRegistered>()
+            .add_systems(Update, handle_new_particle_types);
     }
 }
 pub fn handle_new_particle_types(
     mut commands: Commands,
-    particle_type_query: Query<(Entity, &ParticleType), Changed<ParticleType>>,
+    particle_type_query: Query<
+        (Entity, &ParticleType, Option<&ParticleTypeId>),
+        Changed<ParticleType>,
+    >,
     mut type_map: ResMut<ParticleTypeMap>,
+    mut ev_type_registered: EventWriter<ParticleTypeRegistered>,
 ) {
     particle_type_query
         .iter()
-        .for_each(|(entity, particle_type)| {
+        .for_each(|(entity, particle_type, registered_id)| {
+            // Any mutable access to a type marks it changed, but only a new or renamed type needs
+            // registering.
+            if registered_id.is_some_and(|id| type_map.id_of(&particle_type.name) == Some(*id)) {
+                return;
+            }
+            type_map.insert(particle_type.name.clone(), entity);
+            let id = type_map
+                .id_of(&particle_type.name)
+                .expect("Particle type was just registered");
             commands
                 .entity(entity)
-                .insert((Transform::default(), Visibility::default()));
-            type_map.insert(particle_type.name.clone(), entity);
+                .insert((Transform::default(), Visibility::default(), id));
+
+            let event = ParticleTypeRegistered {
+                entity,
+                name: particle_type.name.clone(),
+            };
+            commands.trigger(event.clone());
+            ev_type_registered.send(event);
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registered(app: &mut App) -> Vec<String> {
+        app.world_mut()
+            .resource_mut::<Events<ParticleTypeRegistered>>()
+            .drain()
+            .map(|ev| ev.name)
+            .collect()
+    }
+
+    #[test]
+    fn types_are_registered_once() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FallingSandCorePlugin::default()));
+        let sand = app.world_mut().spawn(ParticleType::new("Sand")).id();
+        app.update();
+        assert_eq!(registered(&mut app), vec!["Sand".to_string()]);
+
+        // Moving the type entity or touching its `ParticleType` leaves it be.
+        app.world_mut()
+            .entity_mut(sand)
+            .insert(Transform::from_xyz(5., 0., 0.));
+        app.world_mut()
+            .get_mut::<ParticleType>(sand)
+            .unwrap()
+            .set_changed();
+        app.update();
+        assert_eq!(registered(&mut app), Vec::<String>::new());
+        assert_eq!(
+            app.world().get::<Transform>(sand),
+            Some(&Transform::from_xyz(5., 0., 0.))
+        );
+
+        // Renaming it registers it under the new name.
+        app.world_mut().get_mut::<ParticleType>(sand).unwrap().name = "Dust".to_string();
+        app.update();
+        assert_eq!(registered(&mut app), vec!["Dust".to_string()]);
+        assert_eq!(
+            app.world()
+                .resource::<ParticleTypeMap>()
+                .get(&"Dust".to_string()),
+            Some(&sand)
+        );
+    }
+}
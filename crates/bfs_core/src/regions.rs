@@ -0,0 +1,234 @@
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::{Coordinates, ParticleSimulationSet};
+
+pub struct NamedRegionsPlugin;
+
+impl Plugin for NamedRegionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NamedRegions>()
+            .register_type::<TracksRegions>()
+            .add_event::<RegionEnteredEvent>()
+            .add_event::<RegionExitedEvent>()
+            .add_systems(Update, track_region_occupancy.after(ParticleSimulationSet));
+    }
+}
+
+/// Named rectangles in the world, such as spawn zones, goals or hazards.
+///
+/// Regions are purely for gameplay and have no effect on the simulation itself. Bounds are
+/// inclusive, and regions may overlap.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct NamedRegions(HashMap<String, IRect>);
+
+impl NamedRegions {
+    /// Adds a region, returning the bounds it replaced if the name was already taken.
+    pub fn add(&mut self, name: impl Into<String>, region: IRect) -> Option<IRect> {
+        self.0.insert(name.into(), region)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<IRect> {
+        self.0.remove(name)
+    }
+
+    pub fn region(&self, name: &str) -> Option<IRect> {
+        self.0.get(name).copied()
+    }
+
+    /// Whether `coordinates` lies within the named region. Always false for unknown names.
+    pub fn contains(&self, name: &str, coordinates: IVec2) -> bool {
+        self.region(name)
+            .is_some_and(|region| region.contains(coordinates))
+    }
+
+    /// The names of every region containing `coordinates`.
+    pub fn regions_at(&self, coordinates: IVec2) -> impl Iterator<Item = &str> {
+        self.0
+            .iter()
+            .filter(move |(_, region)| region.contains(coordinates))
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &IRect)> {
+        self.0.iter()
+    }
+}
+
+/// Marks a particle whose movement between named regions should be reported with
+/// [`RegionEnteredEvent`] and [`RegionExitedEvent`].
+///
+/// Tracking is opt-in, as checking every particle against every region each tick is wasteful when
+/// only a few particles matter. A particle that is despawned inside a region sends no exit event.
+#[derive(Component, Clone, PartialEq, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct TracksRegions {
+    regions: HashSet<String>,
+}
+
+impl TracksRegions {
+    /// The regions the particle was in as of the last check.
+    pub fn regions(&self) -> impl Iterator<Item = &str> {
+        self.regions.iter().map(|name| name.as_str())
+    }
+}
+
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct RegionEnteredEvent {
+    pub entity: Entity,
+    pub region: String,
+}
+
+#[derive(Event, Clone, PartialEq, Debug)]
+pub struct RegionExitedEvent {
+    pub entity: Entity,
+    pub region: String,
+}
+
+pub fn track_region_occupancy(
+    regions: Res<NamedRegions>,
+    mut particle_query: Query<(Entity, Ref<Coordinates>, &mut TracksRegions)>,
+    mut ev_region_entered: EventWriter<RegionEnteredEvent>,
+    mut ev_region_exited: EventWriter<RegionExitedEvent>,
+) {
+    particle_query
+        .iter_mut()
+        .for_each(|(entity, coordinates, mut tracks_regions)| {
+            if !regions.is_changed() && !coordinates.is_changed() && !tracks_regions.is_added() {
+                return;
+            }
+            let current: HashSet<String> = regions
+                .regions_at(coordinates.0)
+                .map(|name| name.to_string())
+                .collect();
+            if current == tracks_regions.regions {
+                return;
+            }
+            ev_region_exited.send_batch(tracks_regions.regions.difference(&current).map(
+                |region| RegionExitedEvent {
+                    entity,
+                    region: region.clone(),
+                },
+            ));
+            ev_region_entered.send_batch(current.difference(&tracks_regions.regions).map(
+                |region| RegionEnteredEvent {
+                    entity,
+                    region: region.clone(),
+                },
+            ));
+            tracks_regions.regions = current;
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FallingSandCorePlugin, Particle, ParticleType, RemoveParticleEvent};
+
+    /// An app with two regions that overlap along x = 0, and a tracked particle at `start`.
+    fn app(start: IVec2) -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, FallingSandCorePlugin::default()));
+        app.world_mut().spawn(ParticleType::new("Sand"));
+        let mut regions = app.world_mut().resource_mut::<NamedRegions>();
+        regions.add("left", IRect::new(-10, -10, 0, 10));
+        regions.add("right", IRect::new(0, -10, 10, 10));
+        app.update();
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                Particle::new("Sand"),
+                Transform::from_xyz(start.x as f32, start.y as f32, 0.),
+                TracksRegions::default(),
+            ))
+            .id();
+        app.update();
+        (app, entity)
+    }
+
+    /// Moves the particle to `coordinates` and returns the regions it entered and exited, sorted
+    /// by name.
+    fn move_to(app: &mut App, entity: Entity, coordinates: IVec2) -> (Vec<String>, Vec<String>) {
+        app.world_mut().get_mut::<Coordinates>(entity).unwrap().0 = coordinates;
+        app.update();
+        events(app, entity)
+    }
+
+    fn events(app: &mut App, entity: Entity) -> (Vec<String>, Vec<String>) {
+        let mut entered: Vec<String> = app
+            .world_mut()
+            .resource_mut::<Events<RegionEnteredEvent>>()
+            .drain()
+            .inspect(|ev| assert_eq!(ev.entity, entity))
+            .map(|ev| ev.region)
+            .collect();
+        let mut exited: Vec<String> = app
+            .world_mut()
+            .resource_mut::<Events<RegionExitedEvent>>()
+            .drain()
+            .inspect(|ev| assert_eq!(ev.entity, entity))
+            .map(|ev| ev.region)
+            .collect();
+        entered.sort();
+        exited.sort();
+        (entered, exited)
+    }
+
+    #[test]
+    fn particles_enter_and_exit_overlapping_regions() {
+        let (mut app, entity) = app(IVec2::new(-20, 0));
+        let none: Vec<String> = vec![];
+        assert_eq!(events(&mut app, entity), (none.clone(), none.clone()));
+
+        let moves = [
+            (IVec2::new(-5, 0), vec!["left".to_string()], none.clone()),
+            // Moving within a region sends nothing.
+            (IVec2::new(-5, 5), none.clone(), none.clone()),
+            // x = 0 lies in both regions.
+            (IVec2::new(0, 0), vec!["right".to_string()], none.clone()),
+            (IVec2::new(5, 0), none.clone(), vec!["left".to_string()]),
+            (IVec2::new(20, 0), none.clone(), vec!["right".to_string()]),
+            // Jumping straight into the overlap enters both at once.
+            (
+                IVec2::new(0, -10),
+                vec!["left".to_string(), "right".to_string()],
+                none.clone(),
+            ),
+        ];
+        for (coordinates, entered, exited) in moves {
+            assert_eq!(
+                move_to(&mut app, entity, coordinates),
+                (entered, exited),
+                "moving to {coordinates}"
+            );
+        }
+        let world = app.world();
+        let mut regions: Vec<&str> = world
+            .get::<TracksRegions>(entity)
+            .unwrap()
+            .regions()
+            .collect();
+        regions.sort();
+        assert_eq!(regions, vec!["left", "right"]);
+    }
+
+    #[test]
+    fn particles_despawned_inside_a_region_do_not_exit_it() {
+        let (mut app, entity) = app(IVec2::new(5, 0));
+        assert_eq!(
+            events(&mut app, entity),
+            (vec!["right".to_string()], vec![])
+        );
+
+        app.world_mut().trigger(RemoveParticleEvent {
+            coordinates: IVec2::new(5, 0),
+            despawn: true,
+        });
+        app.update();
+        app.update();
+
+        assert!(app.world().get_entity(entity).is_err());
+        assert_eq!(events(&mut app, entity), (vec![], vec![]));
+    }
+}
@@ -0,0 +1,36 @@
+//! Fixtures shared by the simulation tests of the `bfs_*` crates. Other crates get these through
+//! the `testing` feature, which is meant to be enabled for their dev builds only.
+
+use bevy::prelude::*;
+
+use crate::{Coordinates, Particle};
+
+/// Spawns a particle of the named type at each cell. They're added to the map on the next update.
+pub fn spawn(app: &mut App, name: &str, cells: impl IntoIterator<Item = IVec2>) {
+    for cell in cells {
+        app.world_mut().spawn((
+            Particle::new(name),
+            Transform::from_xyz(cell.x as f32, cell.y as f32, 0.),
+        ));
+    }
+}
+
+/// Runs the given number of app updates.
+pub fn step(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+/// Returns the coordinates of every particle of the named type, sorted by row, then column.
+pub fn positions(app: &mut App, name: &str) -> Vec<IVec2> {
+    let mut positions: Vec<IVec2> = app
+        .world_mut()
+        .query::<(&Particle, &Coordinates)>()
+        .iter(app.world())
+        .filter(|(particle, _)| particle.name == name)
+        .map(|(_, coordinates)| coordinates.0)
+        .collect();
+    positions.sort_by_key(|position| (position.y, position.x));
+    positions
+}
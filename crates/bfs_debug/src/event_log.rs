@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use bfs_core::{
+    ChunkMap, Coordinates, MutateParticleEvent, Particle, ParticleSimulationSet,
+    RemoveParticleEvent, SimulationTick,
+};
+use bfs_reactions::{Burning, Reaction, ReactionEvent};
+
+pub struct SimulationEventLogPlugin;
+
+impl Plugin for SimulationEventLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ReactionEvent>()
+            .add_systems(
+                Update,
+                (log_spawns, log_type_changes, log_ignitions, log_reactions)
+                    .after(ParticleSimulationSet)
+                    .run_if(resource_exists::<SimulationEventLog>),
+            )
+            .add_observer(on_log_despawn);
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SimulationEventKind {
+    Spawn,
+    Despawn,
+    /// The particle changed into the named type.
+    TypeChange(String),
+    Ignition,
+    /// A reaction changed or removed the particle.
+    Reaction(Reaction),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SimulationLogEntry {
+    pub tick: u64,
+    pub coordinates: IVec2,
+    /// The particle's type at the time of the event, if it was known.
+    pub particle: Option<String>,
+    pub kind: SimulationEventKind,
+}
+
+/// Records spawns, despawns, type changes, ignitions and reactions while present, keeping the most recent
+/// `capacity` entries.
+///
+/// Nothing is recorded (and nothing runs) unless this resource exists.
+#[derive(Resource, Clone, Debug)]
+pub struct SimulationEventLog {
+    capacity: usize,
+    entries: VecDeque<SimulationLogEntry>,
+}
+
+impl Default for SimulationEventLog {
+    fn default() -> SimulationEventLog {
+        SimulationEventLog::with_capacity(4096)
+    }
+}
+
+impl SimulationEventLog {
+    pub fn with_capacity(capacity: usize) -> SimulationEventLog {
+        SimulationEventLog {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn record(&mut self, entry: SimulationLogEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SimulationLogEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns the entries recorded within the last `last_n_ticks` ticks (counting back from the
+    /// most recent entry) that match `filter`, oldest first.
+    pub fn query<'a>(
+        &'a self,
+        filter: impl Fn(&SimulationLogEntry) -> bool + 'a,
+        last_n_ticks: u64,
+    ) -> impl Iterator<Item = &'a SimulationLogEntry> + 'a {
+        let latest = self.entries.back().map_or(0, |entry| entry.tick);
+        let since = latest.saturating_sub(last_n_ticks);
+        self.entries
+            .iter()
+            .filter(move |entry| entry.tick >= since && filter(entry))
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+pub fn log_spawns(
+    mut log: ResMut<SimulationEventLog>,
+    tick: Res<SimulationTick>,
+    particle_query: Query<(&Particle, &Coordinates), Added<Coordinates>>,
+) {
+    particle_query.iter().for_each(|(particle, coordinates)| {
+        log.record(SimulationLogEntry {
+            tick: tick.get(),
+            coordinates: coordinates.0,
+            particle: Some(particle.name.clone()),
+            kind: SimulationEventKind::Spawn,
+        });
+    });
+}
+
+pub fn log_type_changes(
+    mut log: ResMut<SimulationEventLog>,
+    tick: Res<SimulationTick>,
+    mut ev_mutate_particle: EventReader<MutateParticleEvent>,
+    particle_query: Query<&Coordinates>,
+) {
+    ev_mutate_particle.read().for_each(|ev| {
+        if let Ok(coordinates) = particle_query.get(ev.entity) {
+            log.record(SimulationLogEntry {
+                tick: tick.get(),
+                coordinates: coordinates.0,
+                particle: None,
+                kind: SimulationEventKind::TypeChange(ev.particle.name.clone()),
+            });
+        }
+    });
+}
+
+pub fn log_ignitions(
+    mut log: ResMut<SimulationEventLog>,
+    tick: Res<SimulationTick>,
+    particle_query: Query<(&Particle, &Coordinates), Added<Burning>>,
+) {
+    particle_query.iter().for_each(|(particle, coordinates)| {
+        log.record(SimulationLogEntry {
+            tick: tick.get(),
+            coordinates: coordinates.0,
+            particle: Some(particle.name.clone()),
+            kind: SimulationEventKind::Ignition,
+        });
+    });
+}
+
+pub fn log_reactions(
+    mut log: ResMut<SimulationEventLog>,
+    tick: Res<SimulationTick>,
+    mut ev_reaction: EventReader<ReactionEvent>,
+) {
+    ev_reaction.read().for_each(|ev| {
+        log.record(SimulationLogEntry {
+            tick: tick.get(),
+            coordinates: ev.coordinates,
+            particle: None,
+            kind: SimulationEventKind::Reaction(ev.reaction),
+        });
+    });
+}
+
+pub fn on_log_despawn(
+    trigger: Trigger<RemoveParticleEvent>,
+    log: Option<ResMut<SimulationEventLog>>,
+    tick: Res<SimulationTick>,
+    map: Res<ChunkMap>,
+    particle_query: Query<&Particle>,
+) {
+    let Some(mut log) = log else {
+        return;
+    };
+    let coordinates = trigger.event().coordinates;
+    // The particle may already have been taken out of the map by the time we see the event.
+    let particle = map
+        .chunk(&coordinates)
+        .and_then(|chunk| chunk.get(&coordinates))
+        .and_then(|entity| particle_query.get(*entity).ok())
+        .map(|particle| particle.name.clone());
+    log.record(SimulationLogEntry {
+        tick: tick.get(),
+        coordinates,
+        particle,
+        kind: SimulationEventKind::Despawn,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bfs_core::FallingSandCorePlugin;
+
+    #[test]
+    fn reactions_are_logged() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            FallingSandCorePlugin::default(),
+            SimulationEventLogPlugin,
+        ))
+        .init_resource::<SimulationEventLog>();
+        let reactions = [
+            Reaction::Burn,
+            Reaction::Propagate,
+            Reaction::Corrode,
+            Reaction::Evaporate,
+        ];
+        for (x, reaction) in (0..).zip(reactions) {
+            app.world_mut().send_event(ReactionEvent {
+                coordinates: IVec2::new(x, 0),
+                reaction,
+            });
+        }
+        app.update();
+
+        let logged: Vec<(IVec2, SimulationEventKind)> = app
+            .world()
+            .resource::<SimulationEventLog>()
+            .query(
+                |entry| matches!(entry.kind, SimulationEventKind::Reaction(_)),
+                u64::MAX,
+            )
+            .map(|entry| (entry.coordinates, entry.kind.clone()))
+            .collect();
+        let expected: Vec<(IVec2, SimulationEventKind)> = (0..)
+            .zip(reactions)
+            .map(|(x, reaction)| (IVec2::new(x, 0), SimulationEventKind::Reaction(reaction)))
+            .collect();
+        assert_eq!(logged, expected);
+    }
+}
@@ -1,13 +1,85 @@
+mod event_log;
+mod timings;
+
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
+use bevy::utils::Duration;
+use std::fmt;
 
-use bfs_core::{ChunkMap, Particle};
+use bfs_core::{ChunkMap, Particle, ParticleType, ParticleTypeMap};
 use bfs_movement::Wall;
 
-pub struct FallingSandDebugPlugin;
+pub use event_log::*;
+pub use timings::*;
+
+/// Adds debug visualizations and statistics for the simulation.
+///
+/// Visualizations can be configured when the plugin is added:
+///
+/// ```ignore
+/// FallingSandDebugPlugin::default()
+///     .with_chunk_border_color(Color::WHITE)
+///     .with_dirty_rect_color(Color::srgb(1., 0., 0.))
+///     .show_particle_count(true)
+/// ```
+///
+/// Each `show_*` toggle inserts the matching marker resource (e.g. [`DebugParticleCount`]), so
+/// visualizations can still be turned on and off at runtime by inserting or removing those
+/// resources, and colors changed through [`DebugColors`].
+#[derive(Clone, Debug, Default)]
+pub struct FallingSandDebugPlugin {
+    pub colors: DebugColors,
+    pub show_particle_count: bool,
+    pub show_hibernating_chunks: bool,
+    pub show_dirty_rects: bool,
+    pub record_timings: bool,
+}
+
+impl FallingSandDebugPlugin {
+    /// Sets the border color of chunks that are awake.
+    pub fn with_chunk_border_color(mut self, color: Color) -> FallingSandDebugPlugin {
+        self.colors.chunk_border = color;
+        self
+    }
+
+    /// Sets the border color of hibernating chunks.
+    pub fn with_hibernating_chunk_color(mut self, color: Color) -> FallingSandDebugPlugin {
+        self.colors.hibernating_chunk = color;
+        self
+    }
+
+    pub fn with_dirty_rect_color(mut self, color: Color) -> FallingSandDebugPlugin {
+        self.colors.dirty_rect = color;
+        self
+    }
+
+    pub fn show_particle_count(mut self, show: bool) -> FallingSandDebugPlugin {
+        self.show_particle_count = show;
+        self
+    }
+
+    pub fn show_hibernating_chunks(mut self, show: bool) -> FallingSandDebugPlugin {
+        self.show_hibernating_chunks = show;
+        self
+    }
+
+    pub fn show_dirty_rects(mut self, show: bool) -> FallingSandDebugPlugin {
+        self.show_dirty_rects = show;
+        self
+    }
+
+    /// Records how long each simulation phase takes in [`SimulationTimings`].
+    pub fn record_timings(mut self, record: bool) -> FallingSandDebugPlugin {
+        self.record_timings = record;
+        self
+    }
+}
 
 impl Plugin for FallingSandDebugPlugin {
     fn build(&self, app: &mut App) {
-        app.init_gizmo_group::<DebugGizmos>()
+        app.add_plugins((SimulationEventLogPlugin, SimulationTimingsPlugin))
+            .init_gizmo_group::<DebugGizmos>()
+            .insert_resource(self.colors.clone())
             .init_resource::<DynamicParticleCount>()
             .init_resource::<TotalParticleCount>()
             .add_systems(
@@ -22,7 +94,40 @@ impl Plugin for FallingSandDebugPlugin {
                 Update,
                 (count_dynamic_particles, count_total_particles)
                     .run_if(resource_exists::<DebugParticleCount>),
-            );
+            )
+            .add_event::<LogSimulationStatsEvent>()
+            .add_observer(on_log_simulation_stats);
+
+        if self.show_particle_count {
+            app.init_resource::<DebugParticleCount>();
+        }
+        if self.show_hibernating_chunks {
+            app.init_resource::<DebugHibernatingChunks>();
+        }
+        if self.show_dirty_rects {
+            app.init_resource::<DebugDirtyRects>();
+        }
+        if self.record_timings {
+            app.init_resource::<DebugSimulationTimings>();
+        }
+    }
+}
+
+/// Colors used by the debug visualizations.
+#[derive(Resource, Clone, Debug)]
+pub struct DebugColors {
+    pub chunk_border: Color,
+    pub hibernating_chunk: Color,
+    pub dirty_rect: Color,
+}
+
+impl Default for DebugColors {
+    fn default() -> DebugColors {
+        DebugColors {
+            chunk_border: Color::srgba(0.52, 0.80, 0.51, 1.0),
+            hibernating_chunk: Color::srgba(0.67, 0.21, 0.24, 1.),
+            dirty_rect: Color::srgba(1., 1., 1., 1.),
+        }
     }
 }
 
@@ -44,26 +149,34 @@ pub struct DynamicParticleCount(pub u64);
 #[derive(Default, Resource)]
 pub struct TotalParticleCount(pub u64);
 
-pub fn color_dirty_rects(map: Res<ChunkMap>, mut chunk_gizmos: Gizmos<DebugGizmos>) {
+pub fn color_dirty_rects(
+    map: Res<ChunkMap>,
+    colors: Res<DebugColors>,
+    mut chunk_gizmos: Gizmos<DebugGizmos>,
+) {
     map.iter_chunks().for_each(|chunk| {
         if let Some(dirty_rect) = chunk.prev_dirty_rect() {
             chunk_gizmos.rect_2d(
                 dirty_rect.center().as_vec2(),
                 dirty_rect.size().as_vec2() + Vec2::splat(1.),
-                Color::srgba(1., 1., 1., 1.),
+                colors.dirty_rect,
             )
         }
     });
 }
 
-pub fn color_hibernating_chunks(map: Res<ChunkMap>, mut chunk_gizmos: Gizmos<DebugGizmos>) {
+pub fn color_hibernating_chunks(
+    map: Res<ChunkMap>,
+    colors: Res<DebugColors>,
+    mut chunk_gizmos: Gizmos<DebugGizmos>,
+) {
     map.iter_chunks().for_each(|chunk| {
         let rect = Rect::from_corners(chunk.min().as_vec2(), chunk.max().as_vec2());
         if chunk.hibernating() == true {
             chunk_gizmos.rect_2d(
                 rect.center(),
                 rect.size() + Vec2::splat(1.),
-                Color::srgba(0.67, 0.21, 0.24, 1.),
+                colors.hibernating_chunk,
             );
         }
     });
@@ -74,7 +187,7 @@ pub fn color_hibernating_chunks(map: Res<ChunkMap>, mut chunk_gizmos: Gizmos<Deb
             chunk_gizmos.rect_2d(
                 rect.center(),
                 rect.size() + Vec2::splat(1.),
-                Color::srgba(0.52, 0.80, 0.51, 1.0),
+                colors.chunk_border,
             );
         }
     });
@@ -93,3 +206,132 @@ pub fn count_total_particles(
 ) {
     total_particle_count.0 = particle_query.iter().fold(0, |acc, _| acc + 1);
 }
+
+/// Triggers a one-off report of the current simulation state, written to the console.
+#[derive(Event)]
+pub struct LogSimulationStatsEvent;
+
+/// A snapshot of simulation statistics.
+#[derive(Clone, Debug, Default)]
+pub struct SimulationStats {
+    pub total_particles: u64,
+    pub dynamic_particles: u64,
+    pub wall_particles: u64,
+    pub active_particles: u64,
+    pub fps: Option<f64>,
+    pub occupied_cells: usize,
+    pub total_cells: usize,
+    pub dirty_chunks: usize,
+    pub hibernating_chunks: usize,
+    pub total_chunks: usize,
+    pub particle_type_counts: Vec<(String, usize)>,
+    pub timings: Vec<(SimulationPhase, Duration)>,
+}
+
+impl fmt::Display for SimulationStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "simulation stats:")?;
+        match self.fps {
+            Some(fps) => writeln!(f, "  fps: {:.1}", fps)?,
+            None => writeln!(f, "  fps: n/a")?,
+        }
+        writeln!(f, "  particles:")?;
+        writeln!(f, "    total: {}", self.total_particles)?;
+        writeln!(f, "    dynamic: {}", self.dynamic_particles)?;
+        writeln!(f, "    wall: {}", self.wall_particles)?;
+        writeln!(f, "    active: {}", self.active_particles)?;
+        writeln!(f, "  map:")?;
+        writeln!(
+            f,
+            "    occupancy: {}/{} ({:.2}%)",
+            self.occupied_cells,
+            self.total_cells,
+            if self.total_cells == 0 {
+                0.
+            } else {
+                self.occupied_cells as f64 / self.total_cells as f64 * 100.
+            }
+        )?;
+        writeln!(f, "    dirty chunks: {}/{}", self.dirty_chunks, self.total_chunks)?;
+        writeln!(
+            f,
+            "    hibernating chunks: {}/{}",
+            self.hibernating_chunks, self.total_chunks
+        )?;
+        writeln!(f, "  particle types:")?;
+        for (name, count) in &self.particle_type_counts {
+            writeln!(f, "    {}: {}", name, count)?;
+        }
+        if !self.timings.is_empty() {
+            writeln!(f, "  timings:")?;
+            for (phase, duration) in &self.timings {
+                writeln!(f, "    {}: {:.3}ms", phase, duration.as_secs_f64() * 1000.)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn on_log_simulation_stats(
+    _trigger: Trigger<LogSimulationStatsEvent>,
+    map: Res<ChunkMap>,
+    type_map: Res<ParticleTypeMap>,
+    diagnostics: Option<Res<DiagnosticsStore>>,
+    timings: Option<Res<SimulationTimings>>,
+    debug_particle_count: Option<Res<DebugParticleCount>>,
+    total_particle_count: Res<TotalParticleCount>,
+    dynamic_particle_count: Res<DynamicParticleCount>,
+    particle_query: Query<Has<Wall>, With<Particle>>,
+    parent_query: Query<&Children, With<ParticleType>>,
+) {
+    // Prefer the continuously updated debug counters if they're enabled.
+    let (total_particles, dynamic_particles) = if debug_particle_count.is_some() {
+        (total_particle_count.0, dynamic_particle_count.0)
+    } else {
+        particle_query
+            .iter()
+            .fold((0, 0), |(total, dynamic), is_wall| {
+                (total + 1, dynamic + u64::from(!is_wall))
+            })
+    };
+
+    let mut stats = SimulationStats {
+        total_particles,
+        dynamic_particles,
+        wall_particles: total_particles.saturating_sub(dynamic_particles),
+        fps: diagnostics.and_then(|diagnostics| {
+            diagnostics
+                .get(&FrameTimeDiagnosticsPlugin::FPS)
+                .and_then(|fps| fps.smoothed())
+        }),
+        ..default()
+    };
+
+    map.iter_chunks().for_each(|chunk| {
+        stats.total_chunks += 1;
+        stats.occupied_cells += chunk.len();
+        stats.total_cells += (chunk.region().size() + IVec2::ONE).element_product() as usize;
+        if chunk.prev_dirty_rect().is_some() {
+            stats.dirty_chunks += 1;
+        }
+        if chunk.hibernating() {
+            stats.hibernating_chunks += 1;
+        } else {
+            stats.active_particles += chunk.len() as u64;
+        }
+    });
+
+    stats.particle_type_counts = type_map
+        .iter()
+        .map(|(name, entity)| {
+            let count = parent_query
+                .get(*entity)
+                .map_or(0, |children| children.len());
+            (name.clone(), count)
+        })
+        .collect();
+    stats.particle_type_counts.sort();
+    stats.timings = timings.map_or_else(Vec::new, |timings| timings.iter().collect());
+
+    info!("{}", stats);
+}
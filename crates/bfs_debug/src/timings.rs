@@ -0,0 +1,210 @@
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+use bevy::utils::{Duration, HashMap, Instant};
+use std::fmt;
+
+use bfs_color::ParticleColorSet;
+use bfs_core::{handle_new_particles, ParticleSimulation, ParticleSimulationSet};
+use bfs_movement::ParticleMovementSet;
+use bfs_reactions::ParticleReactionSet;
+
+pub(crate) struct SimulationTimingsPlugin;
+
+impl Plugin for SimulationTimingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationTimings>();
+        for phase in SimulationPhase::ALL {
+            app.register_diagnostic(Diagnostic::new(phase.diagnostic_path()).with_suffix("ms"));
+        }
+        app.add_systems(
+            PreUpdate,
+            (
+                begin_phase(SimulationPhase::Registration).before(handle_new_particles),
+                end_phase(SimulationPhase::Registration).after(handle_new_particles),
+            )
+                .run_if(resource_exists::<DebugSimulationTimings>),
+        )
+        .add_systems(
+            ParticleSimulation,
+            (
+                begin_phase(SimulationPhase::Movement).before(ParticleMovementSet),
+                end_phase(SimulationPhase::Movement).after(ParticleMovementSet),
+                begin_phase(SimulationPhase::Reactions).before(ParticleReactionSet),
+                end_phase(SimulationPhase::Reactions).after(ParticleReactionSet),
+                begin_phase(SimulationPhase::Color).before(ParticleColorSet),
+                end_phase(SimulationPhase::Color).after(ParticleColorSet),
+            )
+                .in_set(ParticleSimulationSet)
+                .run_if(resource_exists::<DebugSimulationTimings>),
+        );
+    }
+}
+
+/// Records [`SimulationTimings`] while present.
+#[derive(Default, Resource)]
+pub struct DebugSimulationTimings;
+
+/// A stage of the simulation that [`SimulationTimings`] measures.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum SimulationPhase {
+    Registration,
+    Movement,
+    Reactions,
+    Color,
+}
+
+impl SimulationPhase {
+    pub const ALL: [SimulationPhase; 4] = [
+        SimulationPhase::Registration,
+        SimulationPhase::Movement,
+        SimulationPhase::Reactions,
+        SimulationPhase::Color,
+    ];
+
+    /// The path this phase's timings are reported under in Bevy's [`Diagnostics`], in
+    /// milliseconds.
+    pub fn diagnostic_path(self) -> DiagnosticPath {
+        DiagnosticPath::const_new(match self {
+            SimulationPhase::Registration => "bfs/registration",
+            SimulationPhase::Movement => "bfs/movement",
+            SimulationPhase::Reactions => "bfs/reactions",
+            SimulationPhase::Color => "bfs/color",
+        })
+    }
+}
+
+impl fmt::Display for SimulationPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SimulationPhase::Registration => "registration",
+            SimulationPhase::Movement => "movement",
+            SimulationPhase::Reactions => "reactions",
+            SimulationPhase::Color => "color",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How long each [`SimulationPhase`] took the last time it ran.
+///
+/// Only recorded while [`DebugSimulationTimings`] is present. Each phase is timed from just
+/// before its first system to just after its last, so systems running in parallel with a phase
+/// are counted towards it. Timings are also reported to Bevy's [`Diagnostics`], where smoothed
+/// values and history are available.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SimulationTimings {
+    durations: HashMap<SimulationPhase, Duration>,
+    started: HashMap<SimulationPhase, Instant>,
+}
+
+impl SimulationTimings {
+    pub fn get(&self, phase: SimulationPhase) -> Option<Duration> {
+        self.durations.get(&phase).copied()
+    }
+
+    /// Every phase that has been timed, in the order phases run.
+    pub fn iter(&self) -> impl Iterator<Item = (SimulationPhase, Duration)> + '_ {
+        SimulationPhase::ALL
+            .into_iter()
+            .filter_map(|phase| Some((phase, self.get(phase)?)))
+    }
+
+    /// The combined duration of every timed phase.
+    pub fn total(&self) -> Duration {
+        self.durations.values().sum()
+    }
+
+    fn begin(&mut self, phase: SimulationPhase, now: Instant) {
+        self.started.insert(phase, now);
+    }
+
+    fn end(&mut self, phase: SimulationPhase, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.started.remove(&phase)?);
+        self.durations.insert(phase, elapsed);
+        Some(elapsed)
+    }
+}
+
+fn begin_phase(phase: SimulationPhase) -> impl FnMut(ResMut<SimulationTimings>) {
+    move |mut timings: ResMut<SimulationTimings>| timings.begin(phase, Instant::now())
+}
+
+fn end_phase(phase: SimulationPhase) -> impl FnMut(ResMut<SimulationTimings>, Diagnostics) {
+    move |mut timings: ResMut<SimulationTimings>, mut diagnostics: Diagnostics| {
+        if let Some(elapsed) = timings.end(phase, Instant::now()) {
+            diagnostics.add_measurement(&phase.diagnostic_path(), || elapsed.as_secs_f64() * 1000.);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bfs_core::FallingSandCorePlugin;
+
+    #[test]
+    fn timings_keep_the_latest_duration_of_each_phase() {
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let mut timings = SimulationTimings::default();
+
+        timings.begin(SimulationPhase::Movement, at(0));
+        timings.begin(SimulationPhase::Color, at(5));
+        assert_eq!(
+            timings.end(SimulationPhase::Movement, at(20)),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            timings.end(SimulationPhase::Color, at(10)),
+            Some(Duration::from_millis(5))
+        );
+        // Phases that never began aren't timed.
+        assert_eq!(timings.end(SimulationPhase::Reactions, at(10)), None);
+
+        // A phase that runs again replaces its previous duration.
+        timings.begin(SimulationPhase::Movement, at(30));
+        timings.end(SimulationPhase::Movement, at(38));
+
+        assert_eq!(
+            timings.iter().collect::<Vec<_>>(),
+            vec![
+                (SimulationPhase::Movement, Duration::from_millis(8)),
+                (SimulationPhase::Color, Duration::from_millis(5)),
+            ]
+        );
+        assert_eq!(timings.total(), Duration::from_millis(13));
+    }
+
+    #[test]
+    fn phases_cover_every_system_in_their_set() {
+        #[derive(Resource, Default)]
+        struct Timed(Vec<SimulationPhase>);
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            FallingSandCorePlugin::default(),
+            SimulationTimingsPlugin,
+        ))
+        .init_resource::<DebugSimulationTimings>()
+        .init_resource::<Timed>()
+        .add_systems(
+            ParticleSimulation,
+            (|timings: Res<SimulationTimings>, mut timed: ResMut<Timed>| {
+                timed.0 = timings.started.keys().copied().collect();
+            })
+            .in_set(ParticleSimulationSet)
+            .in_set(ParticleMovementSet),
+        );
+        app.update();
+
+        assert!(app
+            .world()
+            .resource::<Timed>()
+            .0
+            .contains(&SimulationPhase::Movement));
+        let timings = app.world().resource::<SimulationTimings>();
+        assert!(timings.get(SimulationPhase::Movement).is_some());
+        assert!(timings.started.is_empty());
+    }
+}
@@ -34,8 +34,30 @@ pub struct ParticleBundle {
     pub movable_solid: MovableSolidBlueprint,
     /// The Liquid blueprint
     pub liquid: LiquidBlueprint,
+    /// The Viscosity blueprint
+    pub viscosity: ViscosityBlueprint,
     /// The Gas blueprint
     pub gas: GasBlueprint,
+    /// The DiagonalMover blueprint
+    pub diagonal_mover: DiagonalMoverBlueprint,
+    /// The particle type's passes through blueprint
+    pub passes_through: PassesThroughBlueprint,
+    /// The particle type's erodes blueprint
+    pub erodes: ErodesBlueprint,
+    /// The particle type's friction blueprint
+    pub friction: FrictionBlueprint,
+    /// The particle type's bounce blueprint
+    pub bounce: BounceBlueprint,
+    /// The particle type's spread budget blueprint
+    pub spread_budget: SpreadBudgetBlueprint,
+    /// The particle type's wind resistance blueprint
+    pub wind_resistance: WindResistanceBlueprint,
+    /// The particle type's leaves trail blueprint
+    pub leaves_trail: LeavesTrailBlueprint,
+    /// The particle type's infects on contact blueprint
+    pub infects_on_contact: InfectsOnContactBlueprint,
+    /// The particle type's allow diagonal blueprint
+    pub allow_diagonal: AllowDiagonalBlueprint,
 }
 
 /// Convenience bundle for adding new static particle types.
@@ -169,6 +191,39 @@ impl SolidBundle {
     }
 }
 
+/// Convenience bundle for adding new particles that only move diagonally downward.
+#[derive(Bundle)]
+pub struct DiagonalMoverBundle {
+    /// The unique identifier for the particle.
+    pub particle_type: ParticleType,
+    /// The particle type's density blueprint.
+    pub density: DensityBlueprint,
+    /// The particle type's velocity blueprint.
+    pub velocity: VelocityBlueprint,
+    /// The particle type's colors blueprint.
+    pub colors: ParticleColorBlueprint,
+    /// The DiagonalMover component blueprint.
+    pub diagonal_mover: DiagonalMoverBlueprint,
+}
+
+impl DiagonalMoverBundle {
+    /// Creates a new DiagonalMoverBundle
+    pub fn new(
+        particle_type: ParticleType,
+        density: Density,
+        velocity: Velocity,
+        colors: ParticleColor,
+    ) -> DiagonalMoverBundle {
+        DiagonalMoverBundle {
+            particle_type,
+            density: DensityBlueprint(density),
+            velocity: VelocityBlueprint(velocity),
+            colors: ParticleColorBlueprint(colors),
+            diagonal_mover: DiagonalMoverBlueprint(DiagonalMover::new()),
+        }
+    }
+}
+
 /// Convenience bundle for adding new particles in a liquid state.
 #[derive(Bundle)]
 pub struct LiquidBundle {
@@ -7,6 +7,7 @@
 #![allow(clippy::default_trait_access, clippy::module_name_repetitions)]
 
 pub mod bundles;
+pub mod rng;
 
 use bevy::prelude::{App, Plugin};
 use bevy_turborand::prelude::*;
@@ -21,17 +22,47 @@ pub use bfs_scenes as scenes;
 pub use bfs_spatial as spatial;
 
 pub use bundles::*;
+pub use rng::*;
 
-pub struct FallingSandPlugin;
+#[derive(Default)]
+pub struct FallingSandPlugin {
+    seed: Option<u64>,
+    tick_rate: Option<f64>,
+}
+
+impl FallingSandPlugin {
+    /// Makes the simulation deterministic by seeding its random number generators. See
+    /// [`SimulationSeed`](core::SimulationSeed).
+    pub fn with_seed(mut self, seed: u64) -> FallingSandPlugin {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Steps the simulation at a fixed rate instead of once per frame. See
+    /// [`FallingSandCorePlugin::with_tick_rate`](core::FallingSandCorePlugin::with_tick_rate).
+    pub fn with_tick_rate(mut self, hz: f64) -> FallingSandPlugin {
+        self.tick_rate = Some(hz);
+        self
+    }
+}
 
 impl Plugin for FallingSandPlugin {
     fn build(&self, app: &mut App) {
+        let mut rng_plugin = RngPlugin::default();
+        let mut core_plugin = core::FallingSandCorePlugin::default();
+        if let Some(hz) = self.tick_rate {
+            core_plugin = core_plugin.with_tick_rate(hz);
+        }
+        if let Some(seed) = self.seed {
+            rng_plugin = rng_plugin.with_rng_seed(seed);
+            app.insert_resource(core::SimulationSeed(seed));
+        }
         app.add_plugins((
-            RngPlugin::default(),
-            core::FallingSandCorePlugin,
+            rng_plugin,
+            core_plugin,
             movement::FallingSandMovementPlugin,
             color::FallingSandColorPlugin,
-            debug::FallingSandDebugPlugin,
+            debug::FallingSandDebugPlugin::default(),
             spatial::FallingSandSpatialPlugin,
             reactions::FallingSandReactionsPlugin,
             asset_loaders::FallingSandAssetLoadersPlugin,
@@ -39,3 +70,73 @@ impl Plugin for FallingSandPlugin {
         ));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::gizmos::GizmoPlugin;
+    use bevy::prelude::*;
+    use bevy::render::render_resource::Shader;
+    use bfs_core::{ChunkMap, Coordinates, Particle, ParticleType};
+    use bfs_movement::{
+        Density, DensityBlueprint, Liquid, LiquidBlueprint, MovableSolid, MovableSolidBlueprint,
+        Velocity, VelocityBlueprint,
+    };
+
+    fn positions_after(seed: u64, ticks: usize) -> Vec<(Entity, IVec2)> {
+        let mut app = App::new();
+        // The debug plugin draws with gizmos, which need shaders to be registered even without a
+        // renderer.
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<Shader>()
+            .add_plugins((GizmoPlugin, FallingSandPlugin::default().with_seed(seed)));
+        app.world_mut().spawn((
+            ParticleType::new("Sand"),
+            DensityBlueprint(Density(1250)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            MovableSolidBlueprint(MovableSolid::new()),
+        ));
+        app.world_mut().spawn((
+            ParticleType::new("Water"),
+            DensityBlueprint(Density(750)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            LiquidBlueprint(Liquid::new(5)),
+        ));
+        app.update();
+        // Sand pours onto a pool of water on the floor of the map, so both piles spread and mix.
+        let floor = app.world().resource::<ChunkMap>().world_bounds_i().min.y;
+        for (name, x, y) in (-5..=5).flat_map(|x| {
+            [
+                ("Water", x, floor),
+                ("Water", x, floor + 1),
+                ("Sand", x, floor + 10),
+            ]
+        }) {
+            app.world_mut().spawn((
+                Particle::new(name),
+                Transform::from_xyz(x as f32, y as f32, 0.),
+            ));
+        }
+        for _ in 0..ticks {
+            app.update();
+        }
+
+        let mut positions: Vec<(Entity, IVec2)> = app
+            .world_mut()
+            .query_filtered::<(Entity, &Coordinates), With<Particle>>()
+            .iter(app.world())
+            .map(|(entity, coordinates)| (entity, coordinates.0))
+            .collect();
+        positions.sort_by_key(|(entity, _)| *entity);
+        positions
+    }
+
+    #[test]
+    fn same_seed_evolves_identically() {
+        let first = positions_after(7, 100);
+        assert_eq!(first.len(), 33);
+        assert_eq!(first, positions_after(7, 100));
+        assert_ne!(first, positions_after(8, 100));
+    }
+}
@@ -0,0 +1,160 @@
+//! Snapshots of the simulation's random number generator state.
+use bevy::prelude::*;
+use bevy_turborand::prelude::*;
+use bfs_color::ColorRng;
+use bfs_movement::PhysicsRng;
+use bfs_reactions::ReactionRng;
+
+/// The exact position of every random number generator used by the simulation.
+///
+/// Restoring a snapshot taken alongside a copy of the map lets the simulation replay the same
+/// ticks identically. Per-particle generators are keyed by entity, so a snapshot can only be
+/// restored onto the world (and particles) it was taken from.
+#[derive(Clone, Debug, Default)]
+pub struct RngState {
+    global: Option<GlobalRng>,
+    physics: Vec<(Entity, PhysicsRng)>,
+    color: Vec<(Entity, ColorRng)>,
+    reaction: Vec<(Entity, ReactionRng)>,
+}
+
+/// Captures the current state of the global and per-particle random number generators.
+pub fn snapshot_rng_state(world: &mut World) -> RngState {
+    RngState {
+        global: world.get_resource::<GlobalRng>().cloned(),
+        physics: collect_rng::<PhysicsRng>(world),
+        color: collect_rng::<ColorRng>(world),
+        reaction: collect_rng::<ReactionRng>(world),
+    }
+}
+
+/// Restores random number generators to the state captured by [`snapshot_rng_state`]. Particles
+/// that no longer exist are skipped.
+pub fn restore_rng_state(world: &mut World, state: &RngState) {
+    if let Some(global) = &state.global {
+        world.insert_resource(global.clone());
+    }
+    restore_rng(world, &state.physics);
+    restore_rng(world, &state.color);
+    restore_rng(world, &state.reaction);
+}
+
+fn collect_rng<T: Component + Clone>(world: &mut World) -> Vec<(Entity, T)> {
+    world
+        .query::<(Entity, &T)>()
+        .iter(world)
+        .map(|(entity, rng)| (entity, rng.clone()))
+        .collect()
+}
+
+fn restore_rng<T: Component + Clone>(world: &mut World, rngs: &[(Entity, T)]) {
+    for (entity, rng) in rngs {
+        if let Ok(mut entity_mut) = world.get_entity_mut(*entity) {
+            entity_mut.insert(rng.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bfs_core::{ChunkMap, Coordinates, FallingSandCorePlugin, Particle, ParticleType};
+    use bfs_movement::{
+        Density, DensityBlueprint, FallingSandMovementPlugin, Liquid, LiquidBlueprint, Momentum,
+        MovableSolid, MovableSolidBlueprint, Velocity, VelocityBlueprint,
+    };
+
+    type ParticleState = (Entity, Coordinates, Transform, Velocity, Option<Momentum>);
+
+    fn sim() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            RngPlugin::default().with_rng_seed(42),
+            FallingSandCorePlugin::default(),
+            FallingSandMovementPlugin,
+        ));
+        app.world_mut().spawn((
+            ParticleType::new("Sand"),
+            DensityBlueprint(Density(1250)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            MovableSolidBlueprint(MovableSolid::new()),
+        ));
+        app.world_mut().spawn((
+            ParticleType::new("Water"),
+            DensityBlueprint(Density(750)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            LiquidBlueprint(Liquid::new(5)),
+        ));
+        app.update();
+        for (name, x) in [("Sand", -3), ("Water", 3)] {
+            for y in 0..10 {
+                app.world_mut().spawn((
+                    Particle::new(name),
+                    Transform::from_xyz(x as f32, y as f32, 0.),
+                ));
+            }
+        }
+        app.update();
+        app
+    }
+
+    fn particle_states(world: &mut World) -> Vec<ParticleState> {
+        let mut states: Vec<ParticleState> = world
+            .query::<(
+                Entity,
+                &Coordinates,
+                &Transform,
+                &Velocity,
+                Option<&Momentum>,
+            )>()
+            .iter(world)
+            .map(|(entity, coordinates, transform, velocity, momentum)| {
+                (
+                    entity,
+                    *coordinates,
+                    *transform,
+                    *velocity,
+                    momentum.copied(),
+                )
+            })
+            .collect();
+        states.sort_by_key(|(entity, ..)| *entity);
+        states
+    }
+
+    fn run(app: &mut App, ticks: usize) -> Vec<(Entity, IVec2)> {
+        for _ in 0..ticks {
+            app.update();
+        }
+        particle_states(app.world_mut())
+            .into_iter()
+            .map(|(entity, coordinates, ..)| (entity, coordinates.0))
+            .collect()
+    }
+
+    #[test]
+    fn restored_rng_state_replays_the_same_ticks() {
+        let mut app = sim();
+        let world = app.world_mut();
+        let map = world.resource::<ChunkMap>().clone();
+        let particles = particle_states(world);
+        let rng_state = snapshot_rng_state(world);
+
+        let first = run(&mut app, 5);
+
+        let world = app.world_mut();
+        world.insert_resource(map);
+        for (entity, coordinates, transform, velocity, momentum) in particles {
+            let mut particle = world.entity_mut(entity);
+            particle.insert((coordinates, transform, velocity));
+            if let Some(momentum) = momentum {
+                particle.insert(momentum);
+            }
+        }
+        restore_rng_state(world, &rng_state);
+
+        let replay = run(&mut app, 5);
+        assert_eq!(first, replay);
+    }
+}
@@ -0,0 +1,401 @@
+use bevy::ecs::system::RunSystemOnce;
+use bevy::ecs::world::EntityWorldMut;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bfs_core::{
+    reset_chunks, ChunkMap, Coordinates, Frozen, GlobalModifiers, Lifetime, MutateParticleEvent,
+    Particle, ParticleSimulation, ParticleSimulationSet, ParticleTypeId,
+};
+
+use crate::systems::MovementData;
+use crate::*;
+
+/// How far from its destination a particle is put down when both its destination and the cell it
+/// started from were filled while its movement was computed in the background.
+const MAX_DISPLACEMENT: i32 = 8;
+
+pub struct BackgroundMovementPlugin;
+
+impl Plugin for BackgroundMovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            ParticleSimulation,
+            apply_background_movement
+                .before(handle_movement)
+                .in_set(ParticleSimulationSet)
+                .in_set(ParticleMovementSet),
+        )
+        .add_systems(
+            ParticleSimulation,
+            start_background_movement
+                .after(reset_chunks)
+                .run_if(resource_exists::<BackgroundMovement>),
+        )
+        .init_resource::<PendingMovement>()
+        .register_type::<BackgroundMovement>();
+    }
+}
+
+/// Moves particles on a background task when present, so each tick's movement is computed while
+/// the rest of the frame runs instead of holding up the tick.
+///
+/// When a tick ends, the map is copied into a back buffer and every movable particle's movement
+/// components are copied alongside it. The next tick's movement is computed against those copies
+/// on the `AsyncComputeTaskPool`, exactly as [`handle_movement`] would (including
+/// [`ParallelMovement`] and the [`MovementConflictPolicy`]). The result is swapped into the live
+/// [`ChunkMap`] when the next tick moves particles, waiting for the task if it hasn't finished.
+///
+/// This introduces up to one tick of latency: movement doesn't see changes made between ticks,
+/// such as particles spawned, removed or edited by other systems, until the tick after. The tick
+/// this is enabled on still moves particles in-line. Changes made in between win over the
+/// background result:
+/// - Particles that were despawned, frozen, moved or changed type in the meantime keep those
+///   changes, and their background movement is dropped.
+/// - Particles whose destination was filled in the meantime stay in the cell they started in, or
+///   are put down in the empty cell nearest their destination if that was filled too. They're
+///   despawned if there's no empty cell within 8 cells.
+/// - Trails are only left in cells that are still empty, as with in-line movement.
+///
+/// In-line movement on the main thread remains the default. Removing this resource drops the
+/// pending result, and in-line movement picks up from the current state.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct BackgroundMovement;
+
+/// The next tick's movement, if it's being computed in the background.
+///
+/// The task moves particles in a world of its own, holding the back buffer and copies of the
+/// particles' movement components under the same entity ids as the app's world.
+#[derive(Resource, Default)]
+pub struct PendingMovement {
+    tick: Option<PendingTick>,
+    /// Whether this tick's movement came from the background, so it isn't run in-line too.
+    applied: bool,
+}
+
+/// Whether this tick's movement was computed in the background, in which case
+/// [`handle_movement`] doesn't run.
+pub fn moved_in_background(pending: Res<PendingMovement>) -> bool {
+    pending.applied
+}
+
+struct PendingTick {
+    task: Task<World>,
+    /// The cell each copied particle started the tick in.
+    started_at: Vec<(Entity, IVec2)>,
+}
+
+type MovementResult = (
+    &'static ParticleTypeId,
+    &'static Coordinates,
+    &'static PhysicsRng,
+    &'static Velocity,
+    Option<&'static Momentum>,
+    &'static MovementPriority,
+    Option<&'static SpreadBudget>,
+);
+
+fn insert_some<T: Component + Clone>(entity: &mut EntityWorldMut, component: Option<&T>) {
+    if let Some(component) = component {
+        entity.insert(component.clone());
+    }
+}
+
+pub fn start_background_movement(
+    particle_query: Query<MovementData, Without<Frozen>>,
+    map: Res<ChunkMap>,
+    modifiers: Res<GlobalModifiers>,
+    policy: Res<MovementConflictPolicy>,
+    parallel: Option<Res<ParallelMovement>>,
+    mut pending: ResMut<PendingMovement>,
+) {
+    let mut world = World::new();
+    world.insert_resource(map.clone());
+    world.insert_resource(modifiers.clone());
+    world.insert_resource(*policy);
+    if let Some(parallel) = parallel {
+        world.insert_resource(parallel.clone());
+    }
+    world.init_resource::<Events<MutateParticleEvent>>();
+
+    let mut started_at: Vec<(Entity, IVec2)> = vec![];
+    let particles: Vec<_> = particle_query.iter().collect();
+    let result = world.insert_or_spawn_batch(particles.iter().map(
+        |(entity, particle, coordinates, transform, rng, velocity, _, density, priority, ..)| {
+            started_at.push((*entity, coordinates.0));
+            (
+                *entity,
+                (
+                    (*particle).clone(),
+                    **coordinates,
+                    **transform,
+                    (*rng).clone(),
+                    **velocity,
+                    **density,
+                    (*priority).clone(),
+                ),
+            )
+        },
+    ));
+    if let Err(invalid) = result {
+        error!(
+            "Failed to copy {} particles for background movement.",
+            invalid.len()
+        );
+    }
+    for (
+        entity,
+        _,
+        _,
+        _,
+        _,
+        _,
+        momentum,
+        _,
+        _,
+        passes_through,
+        type_id,
+        (friction, allow_diagonal, bounce, diagonal_mover, infects_on_contact, viscosity),
+        liquid,
+        leaves_trail,
+        spread_budget,
+    ) in particles
+    {
+        let Ok(mut entity) = world.get_entity_mut(entity) else {
+            continue;
+        };
+        entity.insert(*type_id);
+        insert_some(&mut entity, momentum);
+        insert_some(&mut entity, passes_through);
+        insert_some(&mut entity, friction);
+        insert_some(&mut entity, allow_diagonal);
+        insert_some(&mut entity, bounce);
+        insert_some(&mut entity, diagonal_mover);
+        insert_some(&mut entity, infects_on_contact);
+        insert_some(&mut entity, viscosity);
+        insert_some(&mut entity, liquid);
+        insert_some(&mut entity, leaves_trail);
+        insert_some(&mut entity, spread_budget);
+    }
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        if let Err(err) = world.run_system_once(handle_movement) {
+            error!("Background movement failed: {err}");
+        }
+        world
+    });
+    pending.tick = Some(PendingTick { task, started_at });
+}
+
+#[allow(clippy::type_complexity)]
+pub fn apply_background_movement(
+    mut commands: Commands,
+    background: Option<Res<BackgroundMovement>>,
+    mut pending: ResMut<PendingMovement>,
+    mut particle_query: Query<
+        (
+            &ParticleTypeId,
+            &mut Coordinates,
+            &mut Transform,
+            &mut PhysicsRng,
+            &mut Velocity,
+            Option<&mut Momentum>,
+            &mut MovementPriority,
+            Option<&mut SpreadBudget>,
+        ),
+        Without<Frozen>,
+    >,
+    existing_query: Query<(), With<Particle>>,
+    mut map: ResMut<ChunkMap>,
+    mut ev_mutate_particle: EventWriter<MutateParticleEvent>,
+) {
+    pending.applied = false;
+    let Some(PendingTick { task, started_at }) = pending.tick.take() else {
+        return;
+    };
+    // Dropping the task cancels it, and in-line movement takes over this tick.
+    if background.is_none() {
+        return;
+    }
+    pending.applied = true;
+    let mut world = block_on(task);
+
+    // Particles that moved are lifted off the map first, so particles that moved into each
+    // other's cells can all be put down again.
+    let mut moved: Vec<(Entity, IVec2, IVec2)> = vec![];
+    let mut result_query = world.query::<MovementResult>();
+    for (entity, start) in started_at {
+        let Ok((
+            result_type_id,
+            result_coordinates,
+            result_rng,
+            result_velocity,
+            result_momentum,
+            result_priority,
+            result_spread_budget,
+        )) = result_query.get(&world, entity)
+        else {
+            continue;
+        };
+        let Ok((
+            type_id,
+            coordinates,
+            _,
+            mut rng,
+            mut velocity,
+            momentum,
+            mut priority,
+            spread_budget,
+        )) = particle_query.get_mut(entity)
+        else {
+            continue;
+        };
+        if type_id != result_type_id
+            || coordinates.0 != start
+            || map.entity(&start) != Some(&entity)
+        {
+            continue;
+        }
+        *rng = result_rng.clone();
+        *velocity = *result_velocity;
+        *priority = result_priority.clone();
+        if let (Some(mut momentum), Some(result_momentum)) = (momentum, result_momentum) {
+            *momentum = *result_momentum;
+        }
+        if let (Some(mut spread_budget), Some(result_spread_budget)) =
+            (spread_budget, result_spread_budget)
+        {
+            *spread_budget = *result_spread_budget;
+        }
+        if result_coordinates.0 != start {
+            map.remove(&start);
+            moved.push((entity, start, result_coordinates.0));
+        }
+    }
+
+    for (entity, start, destination) in moved {
+        let Ok((_, mut coordinates, mut transform, ..)) = particle_query.get_mut(entity) else {
+            continue;
+        };
+        let cell = [destination, start]
+            .into_iter()
+            .find(|cell| map.contains(*cell) && !map.is_occupied(*cell))
+            .or_else(|| map.nearest_empty(destination, MAX_DISPLACEMENT));
+        let Some(cell) = cell else {
+            commands.entity(entity).remove_parent().despawn();
+            continue;
+        };
+        map.insert_no_overwrite(cell, entity);
+        coordinates.0 = cell;
+        transform.translation.x = cell.x as f32;
+        transform.translation.y = cell.y as f32;
+    }
+
+    // Particles infected in the background may have been despawned in the meantime.
+    ev_mutate_particle.send_batch(
+        world
+            .resource_mut::<Events<MutateParticleEvent>>()
+            .drain()
+            .filter(|ev| existing_query.contains(ev.entity)),
+    );
+
+    // Trails are the only particles spawned in the background, and the only ones with a lifetime.
+    world
+        .query::<(&Particle, &Transform, &Lifetime)>()
+        .iter(&world)
+        .filter(|(_, transform, _)| !map.is_occupied(transform.translation.truncate().as_ivec2()))
+        .for_each(|(particle, transform, lifetime)| {
+            commands.spawn((particle.clone(), *transform, *lifetime));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_turborand::{GlobalRng, RngPlugin};
+    use bfs_core::testing::{positions, spawn, step};
+    use bfs_core::{FallingSandCorePlugin, ParticleType, RemoveParticleEvent};
+
+    fn sim(background: bool) -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            RngPlugin::default(),
+            FallingSandCorePlugin::default(),
+            crate::FallingSandMovementPlugin,
+        ));
+        if background {
+            app.insert_resource(BackgroundMovement);
+        }
+        app.world_mut()
+            .spawn((ParticleType::new("Wall"), WallBlueprint(Wall)));
+        app.world_mut().spawn((
+            ParticleType::new("Sand"),
+            DensityBlueprint(Density(1250)),
+            VelocityBlueprint(Velocity::new(1, 1)),
+            MovableSolidBlueprint(MovableSolid::new()),
+        ));
+        app.update();
+        app
+    }
+
+    #[test]
+    fn background_movement_matches_in_line_movement() {
+        let mut apps = [sim(false), sim(false)];
+        for app in &mut apps {
+            app.insert_resource(GlobalRng::with_seed(7));
+            spawn(app, "Wall", (-20..=20).map(|x| IVec2::new(x, -1)));
+            spawn(
+                app,
+                "Sand",
+                (0..100).map(|i| IVec2::new(i % 10 - 5, 10 + i / 10)),
+            );
+            app.update();
+        }
+        let [inline, background] = &mut apps;
+
+        // Left alone, particles move the same whichever way their movement is computed.
+        background.insert_resource(BackgroundMovement);
+        for frames in [1, 5, 30] {
+            step(inline, frames);
+            step(background, frames);
+            assert_eq!(positions(inline, "Sand"), positions(background, "Sand"));
+        }
+
+        // Switching back to in-line movement drops the pending tick rather than moving twice.
+        background
+            .world_mut()
+            .remove_resource::<BackgroundMovement>();
+        step(inline, 5);
+        step(background, 5);
+        assert_eq!(positions(inline, "Sand"), positions(background, "Sand"));
+        assert!(background
+            .world()
+            .resource::<PendingMovement>()
+            .tick
+            .is_none());
+    }
+
+    #[test]
+    fn changes_made_while_moving_in_the_background_win() {
+        let mut app = sim(true);
+        let (sand, dropped) = (IVec2::new(0, 10), IVec2::new(5, 10));
+        spawn(&mut app, "Sand", [sand, dropped]);
+        // The sand is added to the map, and its first fall is computed in the background.
+        step(&mut app, 1);
+        assert_eq!(positions(&mut app, "Sand"), vec![sand, dropped]);
+
+        // Fill one particle's destination and remove the other before the fall is applied.
+        spawn(&mut app, "Wall", [sand - IVec2::Y]);
+        app.world_mut().trigger(RemoveParticleEvent {
+            coordinates: dropped,
+            despawn: true,
+        });
+        step(&mut app, 1);
+
+        assert_eq!(positions(&mut app, "Sand"), vec![sand]);
+        assert_eq!(positions(&mut app, "Wall"), vec![sand - IVec2::Y]);
+        let map = app.world().resource::<ChunkMap>();
+        assert!(!map.is_occupied(dropped));
+        assert!(!map.is_occupied(dropped - IVec2::Y));
+    }
+}
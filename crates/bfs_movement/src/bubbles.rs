@@ -0,0 +1,349 @@
+use bevy::prelude::*;
+use bfs_core::{
+    ChunkMap, Coordinates, Frozen, Particle, ParticleSimulation, ParticleSimulationSet, Submerged,
+};
+
+use crate::{
+    apply_background_movement, handle_movement, Gas, Liquid, ParticleMovementSet, PhysicsRng,
+};
+
+/// How far from its cell a bubble is put down when a particle it can't share the cell with moves
+/// in.
+const MAX_EJECT_DISTANCE: i32 = 8;
+
+/// How large a bubble is drawn relative to the liquid whose cell it shares.
+const SUBMERGED_SCALE: f32 = 0.5;
+
+pub struct GasBubblesPlugin;
+
+impl Plugin for GasBubblesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            ParticleSimulation,
+            handle_gas_bubbles
+                .in_set(ParticleSimulationSet)
+                .in_set(ParticleMovementSet)
+                .after(apply_background_movement)
+                .before(handle_movement)
+                .run_if(resource_exists::<GasBubbles>),
+        )
+        .register_type::<GasBubbles>();
+    }
+}
+
+/// Lets gases rise through liquids as bubbles when present, for the declared pairs of gas and
+/// liquid types only.
+///
+/// Cells normally hold a single particle, so a gas below a liquid simply trades places with it.
+/// With this resource, a gas with a compatible liquid right above it instead moves into the
+/// liquid's cell as its secondary occupant (see [`Submerged`]), leaving its own cell for the
+/// liquid to fall into. Each tick, a bubble then:
+/// - separates from the liquid into the empty cell above or diagonally above it, if there is one,
+/// - otherwise rises into the compatible liquid above or diagonally above it, unless that cell
+///   already holds a bubble,
+/// - otherwise stays where it is.
+///
+/// A bubble whose liquid moves away takes the cell over. If a particle it can't share the cell
+/// with moves in instead, the bubble is put down in the empty cell nearest to it, or despawned if
+/// there's none within 8 cells. Submerged bubbles aren't moved, aged or reacted by any other
+/// system until they separate.
+///
+/// Rendering composites the two particles of a cell by drawing the bubble over the liquid: while
+/// submerged, the bubble's sprite is drawn at half its size, one unit closer to the camera, so the
+/// liquid's color shows around it. Its scale is reset to 1 when it separates. Frame exports blend
+/// the bubble's color evenly with the liquid's.
+///
+/// Pairs are matched by particle type name. This is disabled by default.
+#[derive(Resource, Clone, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct GasBubbles {
+    /// The `(gas, liquid)` particle type names that may share a cell.
+    pub pairs: Vec<(String, String)>,
+}
+
+impl GasBubbles {
+    /// Lets `gas` rise through `liquid` as bubbles.
+    pub fn with_pair(mut self, gas: impl Into<String>, liquid: impl Into<String>) -> GasBubbles {
+        self.pairs.push((gas.into(), liquid.into()));
+        self
+    }
+
+    /// Whether `gas` may share a cell with `liquid`.
+    pub fn allows(&self, gas: &str, liquid: &str) -> bool {
+        self.pairs
+            .iter()
+            .any(|(pair_gas, pair_liquid)| pair_gas == gas && pair_liquid == liquid)
+    }
+}
+
+/// Whether the particle at `coordinates` is a liquid that `gas` may share the cell with.
+fn hosts(
+    bubbles: &GasBubbles,
+    map: &ChunkMap,
+    liquid_query: &Query<&Particle, (With<Liquid>, With<Coordinates>)>,
+    gas: &Particle,
+    coordinates: IVec2,
+) -> bool {
+    map.entity(&coordinates)
+        .and_then(|entity| liquid_query.get(*entity).ok())
+        .is_some_and(|liquid| bubbles.allows(&gas.name, &liquid.name))
+}
+
+#[allow(clippy::type_complexity)]
+pub fn handle_gas_bubbles(
+    mut commands: Commands,
+    bubbles: Res<GasBubbles>,
+    mut map: ResMut<ChunkMap>,
+    mut gas_query: Query<
+        (Entity, &Particle, &Coordinates, &mut Transform),
+        (With<Gas>, Without<Frozen>),
+    >,
+    mut submerged_query: Query<
+        (
+            Entity,
+            &Particle,
+            &mut Submerged,
+            &mut Transform,
+            &mut PhysicsRng,
+            Has<Frozen>,
+        ),
+        Without<Coordinates>,
+    >,
+    liquid_query: Query<&Particle, (With<Liquid>, With<Coordinates>)>,
+) {
+    // Bubbles despawned without going through the map leave their cell behind.
+    let stale: Vec<IVec2> = map
+        .iter_secondary()
+        .filter(|(coordinates, entity)| {
+            !submerged_query
+                .get(*entity)
+                .is_ok_and(|(_, _, submerged, ..)| submerged.0 == *coordinates)
+        })
+        .map(|(coordinates, _)| coordinates)
+        .collect();
+    for coordinates in stale {
+        map.remove_secondary(&coordinates);
+    }
+
+    submerged_query.iter_mut().for_each(
+        |(entity, particle, mut submerged, mut transform, mut rng, frozen)| {
+            if frozen {
+                return;
+            }
+            let cell = submerged.0;
+            let separate_at = if !map.is_occupied(cell) {
+                Some(cell)
+            } else if hosts(&bubbles, &map, &liquid_query, particle, cell) {
+                let mut diagonals = [IVec2::new(-1, 1), IVec2::new(1, 1)];
+                rng.shuffle(&mut diagonals);
+                let mut separate_at = None;
+                for target in [IVec2::Y]
+                    .into_iter()
+                    .chain(diagonals)
+                    .map(|offset| cell + offset)
+                {
+                    if !map.contains(target) {
+                        continue;
+                    }
+                    if !map.is_occupied(target) {
+                        separate_at = Some(target);
+                        break;
+                    }
+                    if hosts(&bubbles, &map, &liquid_query, particle, target)
+                        && map.secondary(&target).is_none()
+                    {
+                        map.remove_secondary(&cell);
+                        map.insert_secondary(target, entity);
+                        submerged.0 = target;
+                        transform.translation.x = target.x as f32;
+                        transform.translation.y = target.y as f32;
+                        break;
+                    }
+                }
+                separate_at
+            } else {
+                let nearest = map.nearest_empty(cell, MAX_EJECT_DISTANCE);
+                if nearest.is_none() {
+                    map.remove_secondary(&cell);
+                    commands.entity(entity).remove_parent().despawn();
+                    return;
+                }
+                nearest
+            };
+
+            if let Some(target) = separate_at {
+                map.remove_secondary(&cell);
+                map.insert_no_overwrite(target, entity);
+                commands
+                    .entity(entity)
+                    .remove::<Submerged>()
+                    .insert(Coordinates(target));
+                transform.translation = Vec3::new(
+                    target.x as f32,
+                    target.y as f32,
+                    transform.translation.z - 1.,
+                );
+                transform.scale = Vec3::ONE;
+            }
+        },
+    );
+
+    gas_query
+        .iter_mut()
+        .for_each(|(entity, particle, coordinates, mut transform)| {
+            let above = coordinates.0 + IVec2::Y;
+            if !hosts(&bubbles, &map, &liquid_query, particle, above)
+                || map.secondary(&above).is_some()
+            {
+                return;
+            }
+            map.remove(&coordinates.0);
+            map.insert_secondary(above, entity);
+            commands
+                .entity(entity)
+                .remove::<Coordinates>()
+                .insert(Submerged(above));
+            transform.translation =
+                Vec3::new(above.x as f32, above.y as f32, transform.translation.z + 1.);
+            transform.scale = Vec3::splat(SUBMERGED_SCALE);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_turborand::RngPlugin;
+    use bfs_core::testing::{positions, spawn, step};
+    use bfs_core::{FallingSandCorePlugin, ParticleType, RemoveParticleEvent};
+
+    use crate::{
+        Density, DensityBlueprint, GasBlueprint, LiquidBlueprint, Velocity, VelocityBlueprint,
+        Wall, WallBlueprint,
+    };
+
+    fn sim() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            RngPlugin::default(),
+            FallingSandCorePlugin::default(),
+            crate::FallingSandMovementPlugin,
+        ));
+        app.insert_resource(GasBubbles::default().with_pair("Steam", "Water"));
+        app.world_mut()
+            .spawn((ParticleType::new("Wall"), WallBlueprint(Wall)));
+        app.world_mut().spawn((
+            ParticleType::new("Water"),
+            DensityBlueprint(Density(1000)),
+            VelocityBlueprint(Velocity::new(1, 1)),
+            LiquidBlueprint(Liquid::new(1)),
+        ));
+        for name in ["Steam", "Smoke"] {
+            app.world_mut().spawn((
+                ParticleType::new(name),
+                DensityBlueprint(Density(200)),
+                VelocityBlueprint(Velocity::new(1, 1)),
+                GasBlueprint(Gas::new(1)),
+            ));
+        }
+        app.update();
+        app
+    }
+
+    fn submerged(app: &mut App) -> Vec<IVec2> {
+        let mut cells: Vec<IVec2> = app
+            .world_mut()
+            .query::<&Submerged>()
+            .iter(app.world())
+            .map(|submerged| submerged.0)
+            .collect();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        cells
+    }
+
+    /// A one cell wide, walled in column of water `depth` cells deep, capped at `height`, with
+    /// `gas` at the bottom. The gas is submerged on the first update.
+    fn column(app: &mut App, gas: &str, depth: i32, height: i32) {
+        // Gases hop over thin walls, so these are a few cells thick.
+        spawn(app, "Wall", [IVec2::NEG_Y, IVec2::new(0, height)]);
+        spawn(
+            app,
+            "Wall",
+            (-1..=height + 1)
+                .flat_map(|y| (1..=3).flat_map(move |x| [IVec2::new(-x, y), IVec2::new(x, y)])),
+        );
+        spawn(app, gas, [IVec2::ZERO]);
+        spawn(app, "Water", (1..=depth).map(|y| IVec2::new(0, y)));
+        app.update();
+    }
+
+    #[test]
+    fn gas_bubbles_rise_through_declared_liquids() {
+        let mut app = sim();
+        let depth = 5;
+        column(&mut app, "Steam", depth, depth + 2);
+        assert_eq!(submerged(&mut app), vec![IVec2::new(0, 1)]);
+        assert_eq!(positions(&mut app, "Steam"), vec![]);
+
+        let map = app.world().resource::<ChunkMap>();
+        let steam = *map.secondary(&IVec2::new(0, 1)).unwrap();
+        let transform = *app.world().get::<Transform>(steam).unwrap();
+        assert_eq!(transform.translation, Vec3::new(0., 1., 1.));
+        assert_eq!(transform.scale, Vec3::splat(SUBMERGED_SCALE));
+
+        step(&mut app, 2 * depth as usize);
+        // The bubble has separated above the water, which settled below it.
+        assert_eq!(submerged(&mut app), vec![]);
+        assert_eq!(positions(&mut app, "Steam"), vec![IVec2::new(0, depth + 1)]);
+        assert_eq!(
+            positions(&mut app, "Water"),
+            (0..depth).map(|y| IVec2::new(0, y)).collect::<Vec<_>>()
+        );
+        let transform = app.world().get::<Transform>(steam).unwrap();
+        assert_eq!(transform.translation, Vec3::new(0., (depth + 1) as f32, 0.));
+        assert_eq!(transform.scale, Vec3::ONE);
+    }
+
+    #[test]
+    fn undeclared_gases_swap_with_liquids() {
+        let mut app = sim();
+        column(&mut app, "Smoke", 3, 5);
+        for _ in 0..8 {
+            assert_eq!(submerged(&mut app), vec![]);
+            app.update();
+        }
+        assert_eq!(positions(&mut app, "Smoke"), vec![IVec2::new(0, 4)]);
+        assert_eq!(
+            positions(&mut app, "Water"),
+            (0..3).map(|y| IVec2::new(0, y)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn bubbles_take_over_the_cell_of_removed_liquids() {
+        let mut app = sim();
+        column(&mut app, "Steam", 2, 3);
+        step(&mut app, 1);
+        assert_eq!(submerged(&mut app), vec![IVec2::new(0, 2)]);
+        // Refilling the cell the water above the bubble fell out of keeps it from separating.
+        spawn(&mut app, "Water", [IVec2::new(0, 2)]);
+        step(&mut app, 3);
+        assert_eq!(submerged(&mut app), vec![IVec2::new(0, 2)]);
+        assert_eq!(
+            positions(&mut app, "Water"),
+            vec![IVec2::new(0, 0), IVec2::new(0, 1), IVec2::new(0, 2)]
+        );
+
+        app.world_mut().trigger(RemoveParticleEvent {
+            coordinates: IVec2::new(0, 2),
+            despawn: true,
+        });
+        step(&mut app, 1);
+        assert_eq!(submerged(&mut app), vec![]);
+        assert_eq!(positions(&mut app, "Steam"), vec![IVec2::new(0, 2)]);
+        assert_eq!(
+            positions(&mut app, "Water"),
+            vec![IVec2::new(0, 0), IVec2::new(0, 1)]
+        );
+    }
+}
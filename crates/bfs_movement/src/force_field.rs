@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use bfs_core::{Coordinates, Frozen, ParticleSimulation, ParticleSimulationSet};
+
+use crate::{
+    handle_movement, wind_factor, Density, Gas, Liquid, Momentum, MovementPriority,
+    ParticleMovementSet, PhysicsRng, WindResistance,
+};
+
+pub struct ForceFieldPlugin;
+
+impl Plugin for ForceFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ForceField>()
+            .register_type::<ForceField>()
+            .register_type::<LocalForceField>()
+            .add_systems(
+                ParticleSimulation,
+                apply_force_fields
+                    .in_set(ParticleSimulationSet)
+                    .in_set(ParticleMovementSet)
+                    .before(handle_movement)
+                    .run_if(force_fields_active),
+            );
+    }
+}
+
+/// A steady push, such as wind, applied to gases and liquids.
+///
+/// Each tick, an affected particle is turned towards `direction` with a chance of `strength`
+/// (0.0 to 1.0), scaled by its [`wind_factor`]. The push sets the particle's [`Momentum`] to its
+/// first [`MovementPriority`] candidate heading that way, so it moves that way next if it can.
+/// Particles with no such candidate aren't pushed. The global field is a resource;
+/// [`LocalForceField`] overrides it within a region.
+#[derive(Resource, Copy, Clone, PartialEq, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct ForceField {
+    pub direction: Vec2,
+    pub strength: f32,
+}
+
+impl ForceField {
+    pub fn new(direction: Vec2, strength: f32) -> ForceField {
+        ForceField {
+            direction,
+            strength,
+        }
+    }
+
+    /// Whether the field pushes anything at all.
+    pub fn is_active(&self) -> bool {
+        self.strength > 0. && self.direction != Vec2::ZERO
+    }
+
+    /// The neighboring cell offset closest to the field's direction.
+    pub fn step(&self) -> IVec2 {
+        self.direction.normalize_or_zero().round().as_ivec2()
+    }
+}
+
+/// Replaces the global [`ForceField`] for particles within `region` (inclusive), e.g. for a fan.
+///
+/// Where several local fields overlap, the first one found applies.
+#[derive(Component, Copy, Clone, PartialEq, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct LocalForceField {
+    pub region: IRect,
+    pub field: ForceField,
+}
+
+impl LocalForceField {
+    pub fn new(region: IRect, field: ForceField) -> LocalForceField {
+        LocalForceField { region, field }
+    }
+}
+
+/// Whether any force field could push a particle this tick.
+pub fn force_fields_active(field: Res<ForceField>, local_query: Query<&LocalForceField>) -> bool {
+    field.is_active() || local_query.iter().any(|local| local.field.is_active())
+}
+
+pub fn apply_force_fields(
+    mut commands: Commands,
+    field: Res<ForceField>,
+    local_query: Query<&LocalForceField>,
+    mut particle_query: Query<
+        (
+            Entity,
+            &Coordinates,
+            &mut PhysicsRng,
+            &MovementPriority,
+            Option<&mut Momentum>,
+            Option<&Density>,
+            Option<&WindResistance>,
+        ),
+        (Or<(With<Gas>, With<Liquid>)>, Without<Frozen>),
+    >,
+) {
+    let local_fields: Vec<&LocalForceField> = local_query.iter().collect();
+    particle_query.iter_mut().for_each(
+        |(entity, coordinates, mut rng, movement_priority, momentum, density, resistance)| {
+            let field = local_fields
+                .iter()
+                .find(|local| local.region.contains(coordinates.0))
+                .map_or(*field, |local| local.field);
+            if !field.is_active() {
+                return;
+            }
+            let chance = field.strength.min(1.) * wind_factor(density, resistance);
+            if !rng.chance(f64::from(chance)) {
+                return;
+            }
+            // Gases spread sideways in jumps, so the push follows whichever candidate heads the
+            // field's way rather than a single step.
+            let step = field.step();
+            let Some(push) = movement_priority
+                .neighbor_groups
+                .iter()
+                .flat_map(|neighbor_group| neighbor_group.neighbor_group.iter())
+                .find(|candidate| candidate.signum() == step)
+                .copied()
+            else {
+                return;
+            };
+            match momentum {
+                Some(mut momentum) => momentum.0 = push,
+                None => {
+                    commands.entity(entity).insert(Momentum(push));
+                }
+            }
+        },
+    );
+}
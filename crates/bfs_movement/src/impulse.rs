@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use bfs_core::{ChunkMap, Frozen};
+
+use crate::{Momentum, Velocity, Wall};
+
+pub struct ImpulsePlugin;
+
+impl Plugin for ImpulsePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyImpulseEvent>()
+            .add_observer(on_apply_impulse);
+    }
+}
+
+/// Pushes movable particles within `radius` of `center` outward without destroying anything.
+///
+/// Each particle's velocity is raised by up to `strength`, falling off linearly with distance,
+/// and its momentum is pointed away from the center. When `occluded_by_walls` is set, particles
+/// with a wall between them and the center are left alone.
+#[derive(Event, Copy, Clone, PartialEq, Debug)]
+pub struct ApplyImpulseEvent {
+    pub center: IVec2,
+    pub radius: i32,
+    pub strength: f32,
+    pub occluded_by_walls: bool,
+}
+
+/// Triggers an [`ApplyImpulseEvent`] that isn't blocked by walls.
+pub fn apply_impulse(commands: &mut Commands, center: IVec2, radius: i32, strength: f32) {
+    commands.trigger(ApplyImpulseEvent {
+        center,
+        radius,
+        strength,
+        occluded_by_walls: false,
+    });
+}
+
+pub fn on_apply_impulse(
+    trigger: Trigger<ApplyImpulseEvent>,
+    map: Res<ChunkMap>,
+    mut particle_query: Query<
+        (&mut Velocity, Option<&mut Momentum>),
+        (Without<Wall>, Without<Frozen>),
+    >,
+    wall_query: Query<(), With<Wall>>,
+) {
+    let event = trigger.event();
+    if event.radius <= 0 || event.strength <= 0. {
+        return;
+    }
+    let is_wall = |coordinates: IVec2| {
+        map.entity(&coordinates)
+            .is_some_and(|entity| wall_query.contains(*entity))
+    };
+
+    for y in -event.radius..=event.radius {
+        for x in -event.radius..=event.radius {
+            let offset = IVec2::new(x, y);
+            let distance = offset.as_vec2().length();
+            if offset == IVec2::ZERO || distance > event.radius as f32 {
+                continue;
+            }
+            let coordinates = event.center + offset;
+            let Some(entity) = map.entity(&coordinates) else {
+                continue;
+            };
+            let Ok((mut velocity, momentum)) = particle_query.get_mut(*entity) else {
+                continue;
+            };
+            if event.occluded_by_walls && is_occluded(event.center, coordinates, is_wall) {
+                continue;
+            }
+
+            let falloff = 1. - distance / event.radius as f32;
+            let boost = (event.strength * falloff).round().clamp(0., 255.) as u8;
+            velocity.val = velocity.val.saturating_add(boost).min(velocity.max);
+            if let Some(mut momentum) = momentum {
+                momentum.0 = offset.signum();
+            }
+        }
+    }
+}
+
+/// Whether any cell strictly between `from` and `to` satisfies `blocks`.
+fn is_occluded(from: IVec2, to: IVec2, blocks: impl Fn(IVec2) -> bool) -> bool {
+    let steps = (to - from).abs().max_element();
+    (1..steps).any(|step| {
+        let cell = from
+            .as_vec2()
+            .lerp(to.as_vec2(), step as f32 / steps as f32)
+            .round()
+            .as_ivec2();
+        blocks(cell)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Density, DensityBlueprint, MovableSolid, MovableSolidBlueprint, VelocityBlueprint,
+    };
+    use bevy_turborand::RngPlugin;
+    use bfs_core::{FallingSandCorePlugin, Particle, ParticleType};
+
+    #[test]
+    fn impulse_crossing_the_map_edge() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            RngPlugin::default(),
+            FallingSandCorePlugin::default(),
+            crate::FallingSandMovementPlugin,
+        ));
+        app.insert_resource(ChunkMap::new(64));
+        app.world_mut().spawn((
+            ParticleType::new("Sand"),
+            DensityBlueprint(Density(1250)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            MovableSolidBlueprint(MovableSolid::new()),
+        ));
+        app.update();
+
+        // The grain rests in the bottom row, next to the corner the impulse is centered on.
+        let corner = app.world().resource::<ChunkMap>().world_bounds_i().min;
+        let grain = corner + IVec2::X;
+        app.world_mut().spawn((
+            Particle::new("Sand"),
+            Transform::from_xyz(grain.x as f32, grain.y as f32, 0.),
+        ));
+        app.update();
+        app.world_mut().trigger(ApplyImpulseEvent {
+            center: corner,
+            radius: 4,
+            strength: 8.,
+            occluded_by_walls: true,
+        });
+
+        let velocity = *app.world_mut().query::<&Velocity>().single(app.world());
+        assert_eq!(velocity.val, velocity.max);
+    }
+}
@@ -1,10 +1,18 @@
 use bevy::prelude::*;
 
+mod background;
+mod bubbles;
+mod force_field;
+mod impulse;
 pub mod material;
 mod rng;
 mod particle_definitions;
 mod systems;
 
+pub use background::*;
+pub use bubbles::*;
+pub use force_field::*;
+pub use impulse::*;
 pub use material::*;
 pub use rng::*;
 pub use particle_definitions::*;
@@ -14,6 +22,14 @@ pub struct FallingSandMovementPlugin;
 
 impl Plugin for FallingSandMovementPlugin {
     fn build(&self, app: &mut App) {
-	app.add_plugins((ParticleDefinitionsPlugin, MaterialPlugin, SystemsPlugin));
+	app.add_plugins((
+            ParticleDefinitionsPlugin,
+            MaterialPlugin,
+            SystemsPlugin,
+            ImpulsePlugin,
+            ForceFieldPlugin,
+            BackgroundMovementPlugin,
+            GasBubblesPlugin,
+        ));
     }
 }
@@ -12,7 +12,8 @@ impl Plugin for MaterialPlugin {
             .add_observer(on_movable_solid_blueprint_added)
             .add_observer(on_liquid_blueprint_added)
             .add_observer(on_wall_added)
-            .add_observer(on_gas_blueprint_added);
+            .add_observer(on_gas_blueprint_added)
+            .add_observer(on_diagonal_mover_blueprint_added);
     }
 }
 
@@ -170,11 +171,42 @@ pub struct MovableSolidBlueprint(pub MovableSolid);
 )]
 pub struct Liquid {
     pub fluidity: usize,
+    /// The number of neighboring cells of the same liquid required before this liquid will spread
+    /// sideways. Liquids with fewer neighbors still fall, but won't thin out into sheets.
+    #[serde(default)]
+    pub min_neighbors_to_spread: u8,
+    /// How strongly the liquid prefers stacking to spreading sideways, in percent from 0 (spreads
+    /// freely) to 100. Particles at the surface of a pool hold still with this chance each tick,
+    /// while particles deeper in a column are pushed out more readily, so tall columns level out
+    /// slowly instead of collapsing into a film.
+    #[serde(default)]
+    pub stack_bias: u8,
 }
 
 impl Liquid {
     pub fn new(fluidity: usize) -> Liquid {
-        Liquid { fluidity }
+        Liquid {
+            fluidity,
+            min_neighbors_to_spread: 0,
+            stack_bias: 0,
+        }
+    }
+
+    pub fn with_min_neighbors_to_spread(mut self, min_neighbors_to_spread: u8) -> Liquid {
+        self.min_neighbors_to_spread = min_neighbors_to_spread;
+        self
+    }
+
+    /// Sets the stack bias from a fraction between 0 and 1, rounded to the nearest percent.
+    pub fn with_stack_bias(mut self, stack_bias: f32) -> Liquid {
+        self.stack_bias = (stack_bias.clamp(0., 1.) * 100.).round() as u8;
+        self
+    }
+
+    /// The chance a particle with `depth` cells of the same liquid above it holds still rather
+    /// than spreading sideways this tick.
+    pub fn rest_chance(&self, depth: usize) -> f64 {
+        f64::from(self.stack_bias) / 100. / (depth + 1) as f64
     }
 }
 
@@ -269,6 +301,53 @@ impl Material for Gas {
 )]
 pub struct GasBlueprint(pub Gas);
 
+/// A particle that only ever moves diagonally downward, such as water running down an angled
+/// surface. It slides along slopes and comes to rest on flat ground or when both diagonals are
+/// blocked.
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    Component,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+pub struct DiagonalMover;
+
+impl DiagonalMover {
+    pub fn new() -> DiagonalMover {
+        DiagonalMover
+    }
+}
+
+impl Material for DiagonalMover {
+    fn into_movement_priority(&self) -> MovementPriority {
+        MovementPriority::from(vec![vec![IVec2::NEG_ONE, IVec2::new(1, -1)]])
+    }
+}
+
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Default,
+    Component,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+pub struct DiagonalMoverBlueprint(pub DiagonalMover);
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Reflect, Serialize, Deserialize)]
 pub enum MaterialType {
     Solid,
@@ -342,3 +421,16 @@ pub fn on_wall_added(
             .insert(MovementPriorityBlueprint(gas.0.into_movement_priority()));
     }
 }
+
+pub fn on_diagonal_mover_blueprint_added(
+    trigger: Trigger<OnAdd, DiagonalMoverBlueprint>,
+    mut commands: Commands,
+    particle_query: Query<&DiagonalMoverBlueprint, With<ParticleType>>,
+) {
+    let entity = trigger.entity();
+    if let Ok(diagonal_mover) = particle_query.get(entity) {
+        commands.entity(entity).insert(MovementPriorityBlueprint(
+            diagonal_mover.0.into_movement_priority(),
+        ));
+    }
+}
@@ -1,21 +1,49 @@
 use bevy::prelude::*;
-use bfs_core::{Particle, ParticleRegistrationEvent, ParticleType};
+use bevy_turborand::{GlobalRng, RngComponent};
+use bfs_core::{
+    handle_new_particles, BlueprintComponent, Particle, ParticleRegistrationEvent,
+    ParticleSimulation, ParticleSimulationSet, ParticleType, ParticleTypeId, ParticleTypeMap,
+};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
+use std::collections::HashSet;
 use std::iter;
 use std::slice::Iter;
 
 use crate::rng::PhysicsRng;
+use crate::{
+    DiagonalMover, DiagonalMoverBlueprint, Gas, GasBlueprint, Liquid, LiquidBlueprint,
+    MovableSolid, MovableSolidBlueprint, Solid, SolidBlueprint, Wall, WallBlueprint,
+};
 
 pub struct ParticleDefinitionsPlugin;
 
 impl Plugin for ParticleDefinitionsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, handle_particle_registration)
-            .register_type::<Density>()
-            .register_type::<Velocity>()
-            .register_type::<Momentum>()
-            .register_type::<MovementPriority>();
+        // Registered particles get their components before the next simulation tick, which may
+        // run before `Update` when the simulation has a fixed tick rate.
+        app.add_systems(
+            PreUpdate,
+            handle_particle_registration.after(handle_new_particles),
+        );
+        app.add_systems(
+            ParticleSimulation,
+            resolve_particle_type_ids.before(ParticleSimulationSet),
+        )
+        .register_type::<Density>()
+        .register_type::<Velocity>()
+        .register_type::<Momentum>()
+        .register_type::<MovementPriority>()
+        .register_type::<PassesThrough>()
+        .register_type::<Erodes>()
+        .register_type::<Friction>()
+        .register_type::<Viscosity>()
+        .register_type::<LeavesTrail>()
+        .register_type::<InfectsOnContact>()
+        .register_type::<AllowDiagonal>()
+        .register_type::<Bounce>()
+        .register_type::<SpreadBudget>()
+        .register_type::<WindResistance>();
     }
 }
 
@@ -53,6 +81,14 @@ pub struct Density(pub u32);
 #[reflect(Component, Debug)]
 pub struct DensityBlueprint(pub Density);
 
+impl BlueprintComponent for Density {
+    type Blueprint = DensityBlueprint;
+
+    fn into_blueprint(self) -> DensityBlueprint {
+        DensityBlueprint(self)
+    }
+}
+
 #[derive(
     Copy,
     Clone,
@@ -110,12 +146,38 @@ impl Velocity {
 #[reflect(Component)]
 pub struct VelocityBlueprint(pub Velocity);
 
+impl BlueprintComponent for Velocity {
+    type Blueprint = VelocityBlueprint;
+
+    fn into_blueprint(self) -> VelocityBlueprint {
+        VelocityBlueprint(self)
+    }
+
+    /// Keeps the particle's current speed, capped to the new maximum.
+    fn updated(&self, existing: &Velocity) -> Velocity {
+        Velocity::new(existing.val.min(self.max).max(1), self.max)
+    }
+}
+
 #[derive(
     Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
 )]
 #[reflect(Component)]
 pub struct Momentum(pub IVec2);
 
+impl BlueprintComponent for Momentum {
+    type Blueprint = MomentumBlueprint;
+
+    fn into_blueprint(self) -> MomentumBlueprint {
+        MomentumBlueprint(self)
+    }
+
+    /// Momentum on a particle is its direction of travel, so an existing one is left untouched.
+    fn updated(&self, existing: &Momentum) -> Momentum {
+        *existing
+    }
+}
+
 impl Momentum {
     pub const ZERO: Self = Self(IVec2::splat(0));
 }
@@ -126,6 +188,348 @@ impl Momentum {
 #[reflect(Component)]
 pub struct MomentumBlueprint(pub Momentum);
 
+/// Particle types this particle may swap through regardless of density.
+///
+/// The particle types are named, and their ids are looked up by [`resolve_particle_type_ids`]
+/// whenever the component or the [`ParticleTypeMap`] changes, so movement compares ids rather
+/// than names.
+#[derive(Clone, Debug, Default, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct PassesThrough {
+    particle_types: HashSet<String>,
+    #[serde(skip)]
+    #[reflect(ignore)]
+    ids: HashSet<ParticleTypeId>,
+}
+
+impl PassesThrough {
+    pub fn new(particle_types: HashSet<String>) -> PassesThrough {
+        PassesThrough {
+            particle_types,
+            ids: HashSet::default(),
+        }
+    }
+
+    /// The names of the particle types this particle may swap through.
+    pub fn particle_types(&self) -> &HashSet<String> {
+        &self.particle_types
+    }
+
+    /// Looks up the ids of the named particle types. Types that aren't registered yet are
+    /// skipped until the next time this is called.
+    pub fn resolve(&mut self, type_map: &ParticleTypeMap) {
+        self.ids = self
+            .particle_types
+            .iter()
+            .filter_map(|name| type_map.id_of(name))
+            .collect();
+    }
+
+    pub fn contains(&self, particle_type: ParticleTypeId) -> bool {
+        self.ids.contains(&particle_type)
+    }
+}
+
+impl PartialEq for PassesThrough {
+    fn eq(&self, other: &PassesThrough) -> bool {
+        self.particle_types == other.particle_types
+    }
+}
+
+impl Eq for PassesThrough {}
+
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct PassesThroughBlueprint(pub PassesThrough);
+
+/// Allows a liquid to pick up adjacent `carries` particles into its flow while it moves at or
+/// above `threshold_velocity`. The faster the liquid moves, the more likely it is to erode.
+#[derive(Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct Erodes {
+    pub carries: Particle,
+    pub threshold_velocity: u8,
+    pub chance: f64,
+}
+
+impl Erodes {
+    pub fn new(carries: Particle, threshold_velocity: u8, chance: f64) -> Erodes {
+        Erodes {
+            carries,
+            threshold_velocity,
+            chance,
+        }
+    }
+
+    /// The chance to erode a neighbor at the given velocity, scaled linearly from `chance` at
+    /// `threshold_velocity` up to twice that at the velocity's max.
+    pub fn chance_at(&self, velocity: &Velocity) -> f64 {
+        if velocity.val < self.threshold_velocity {
+            return 0.;
+        }
+        let range = velocity.max.saturating_sub(self.threshold_velocity).max(1) as f64;
+        let excess = (velocity.val - self.threshold_velocity) as f64;
+        (self.chance * (1. + excess / range)).min(1.)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct ErodesBlueprint(pub Erodes);
+
+/// The chance (0.0 to 1.0) that a particle refuses to take a diagonal step on a given tick.
+///
+/// Low friction makes powders slide readily and form flat piles, while high friction makes
+/// steep piles. A friction of 1.0 prevents diagonal movement entirely.
+#[derive(
+    Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct Friction(pub f32);
+
+impl Friction {
+    pub fn new(friction: f32) -> Friction {
+        Friction(friction.clamp(0., 1.))
+    }
+}
+
+#[derive(
+    Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct FrictionBlueprint(pub Friction);
+
+/// How thick a liquid is, from 0 (runny) to 255 (barely flows).
+///
+/// Each tick, a liquid skips spreading sideways with a chance of `viscosity / 255`, so thick
+/// liquids spread more slowly but still level out as far as thin ones. Falling is unaffected.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct Viscosity(pub u8);
+
+impl Viscosity {
+    /// The chance (0.0 to 1.0) that the liquid holds still sideways on a given tick.
+    pub fn chance_to_hold(&self) -> f64 {
+        f64::from(self.0) / f64::from(u8::MAX)
+    }
+}
+
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct ViscosityBlueprint(pub Viscosity);
+
+/// Gives a moving particle a chance to leave a short-lived `particle` in each cell it vacates.
+/// Trail particles are removed after `lifetime` simulation ticks.
+#[derive(Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct LeavesTrail {
+    pub particle: Particle,
+    pub chance: f64,
+    pub lifetime: u64,
+}
+
+impl LeavesTrail {
+    pub fn new(particle: Particle, chance: f64, lifetime: u64) -> LeavesTrail {
+        LeavesTrail {
+            particle,
+            chance,
+            lifetime,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct LeavesTrailBlueprint(pub LeavesTrail);
+
+/// Gives a falling particle a chance to convert the particle it lands on into its own type, like
+/// a spreading infection or creeping ice.
+///
+/// The chance is rolled at most once per tick, when the particle is blocked directly below by a
+/// particle of another type. Particles of a type in `excludes` are never converted, and neither
+/// are walls. The infecting particle stays put for the tick, so it never passes through the
+/// particle it converts.
+///
+/// Like [`PassesThrough`], the excluded types are compared by the ids [`resolve_particle_type_ids`]
+/// looks up for them.
+#[derive(Clone, Debug, Default, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct InfectsOnContact {
+    pub chance: f64,
+    pub excludes: HashSet<String>,
+    #[serde(skip)]
+    #[reflect(ignore)]
+    excluded_ids: HashSet<ParticleTypeId>,
+}
+
+impl InfectsOnContact {
+    pub fn new(chance: f64, excludes: HashSet<String>) -> InfectsOnContact {
+        InfectsOnContact {
+            chance,
+            excludes,
+            excluded_ids: HashSet::default(),
+        }
+    }
+
+    /// Looks up the ids of the excluded particle types. Types that aren't registered yet are
+    /// skipped until the next time this is called.
+    pub fn resolve(&mut self, type_map: &ParticleTypeMap) {
+        self.excluded_ids = self
+            .excludes
+            .iter()
+            .filter_map(|name| type_map.id_of(name))
+            .collect();
+    }
+
+    /// Whether particles of `particle_type` may be converted.
+    pub fn infects(&self, particle_type: ParticleTypeId) -> bool {
+        !self.excluded_ids.contains(&particle_type)
+    }
+}
+
+impl PartialEq for InfectsOnContact {
+    fn eq(&self, other: &InfectsOnContact) -> bool {
+        self.chance == other.chance && self.excludes == other.excludes
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct InfectsOnContactBlueprint(pub InfectsOnContact);
+
+/// Whether a particle may move diagonally. Particles without this component move diagonally as
+/// their movement priority allows; `AllowDiagonal(false)` restricts them to cardinal moves, which
+/// makes for stepped rather than smooth piles.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
+pub struct AllowDiagonal(pub bool);
+
+impl Default for AllowDiagonal {
+    fn default() -> AllowDiagonal {
+        AllowDiagonal(true)
+    }
+}
+
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct AllowDiagonalBlueprint(pub AllowDiagonal);
+
+/// Makes a particle hop back up when it lands on something after falling quickly.
+///
+/// On impact, the particle hops `restitution` times its impact velocity (less one) diagonally
+/// upward, stopping early at the first occupied cell. Each landing starts from a lower hop than
+/// the last, so bouncing always dies out. A restitution of 0.0 never bounces.
+#[derive(
+    Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct Bounce {
+    pub restitution: f32,
+}
+
+impl Bounce {
+    pub fn new(restitution: f32) -> Bounce {
+        Bounce {
+            restitution: restitution.clamp(0., 1.),
+        }
+    }
+
+    /// The number of cells to hop after landing with `velocity`.
+    pub fn hop_distance(&self, velocity: &Velocity) -> u8 {
+        (f32::from(velocity.val.saturating_sub(1)) * self.restitution) as u8
+    }
+}
+
+#[derive(
+    Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct BounceBlueprint(pub Bounce);
+
+/// Limits how many cells a particle may move sideways before it has to fall again.
+///
+/// Each horizontal move spends one cell of the budget. Once it's spent, the particle can still
+/// fall but won't move sideways. Any downward move refills the budget, so liquids still pool and
+/// fill each new level they drop to.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct SpreadBudget {
+    pub budget: u16,
+    pub remaining: u16,
+}
+
+impl SpreadBudget {
+    pub fn new(budget: u16) -> SpreadBudget {
+        SpreadBudget {
+            budget,
+            remaining: budget,
+        }
+    }
+
+    pub fn is_spent(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Spends or refills the budget after a move by `relative_coordinates`.
+    pub fn record_move(&mut self, relative_coordinates: IVec2) {
+        if relative_coordinates.y < 0 {
+            self.remaining = self.budget;
+        } else if relative_coordinates.y == 0 {
+            self.remaining = self.remaining.saturating_sub(1);
+        }
+    }
+}
+
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct SpreadBudgetBlueprint(pub SpreadBudget);
+
+/// Particles at or below this density feel the full strength of wind and force fields. Heavier
+/// particles are pushed proportionally less.
+pub const WIND_REFERENCE_DENSITY: f32 = 250.;
+
+/// How strongly (0.0 to 1.0) a particle resists being pushed by wind and force fields, on top of
+/// the resistance it already gets from its density. A resistance of 1.0 makes a particle immune.
+#[derive(
+    Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct WindResistance(pub f32);
+
+impl WindResistance {
+    pub fn new(resistance: f32) -> WindResistance {
+        WindResistance(resistance.clamp(0., 1.))
+    }
+}
+
+#[derive(
+    Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect, Serialize, Deserialize,
+)]
+#[reflect(Component)]
+pub struct WindResistanceBlueprint(pub WindResistance);
+
+/// Returns the fraction (0.0 to 1.0) of a wind or force field's strength that applies to a
+/// particle.
+///
+/// Density alone gives intuitive results (gases blow around, sand barely moves), so most types
+/// never need a `WindResistance`. It's only for scaling a type down further.
+pub fn wind_factor(density: Option<&Density>, resistance: Option<&WindResistance>) -> f32 {
+    let density_factor = density.map_or(1., |density| {
+        (WIND_REFERENCE_DENSITY / density.0.max(1) as f32).min(1.)
+    });
+    density_factor * (1. - resistance.map_or(0., |resistance| resistance.0))
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect)]
 pub struct NeighborGroup {
     pub neighbor_group: SmallVec<[IVec2; 4]>,
@@ -304,6 +708,84 @@ impl MovementPriority {
             neighbor_groups: SmallVec::new_const(),
         }
     }
+
+    /// Falls straight down if it can, then diagonally down, then slides one cell sideways. Suits
+    /// thick, slow-spreading materials such as mud or wet sand.
+    pub fn downward_biased() -> MovementPriority {
+        MovementPriority::builder()
+            .group([IVec2::NEG_Y])
+            .mirrored_group(IVec2::new(1, -1))
+            .mirrored_group(IVec2::X)
+            .build()
+    }
+
+    /// Picks evenly between falling straight down and diagonally down, so piles spread out wide
+    /// and symmetric. Suits loose materials such as snow or ash.
+    pub fn symmetric_fall() -> MovementPriority {
+        MovementPriority::builder()
+            .group([IVec2::NEG_Y, IVec2::NEG_ONE, IVec2::new(1, -1)])
+            .build()
+    }
+
+    pub fn builder() -> MovementPriorityBuilder {
+        MovementPriorityBuilder::default()
+    }
+}
+
+/// Builds a [`MovementPriority`] one neighbor group at a time.
+///
+/// Groups are tried in the order they're added. Within a group, candidates are tried in a random
+/// order each tick.
+///
+/// ```ignore
+/// // Fall straight down, or else slide down to the left twice as often as to the right.
+/// let priority = MovementPriority::builder()
+///     .group([IVec2::NEG_Y])
+///     .weighted_group([(IVec2::NEG_ONE, 2), (IVec2::new(1, -1), 1)])
+///     .build();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MovementPriorityBuilder {
+    neighbor_groups: SmallVec<[NeighborGroup; 8]>,
+}
+
+impl MovementPriorityBuilder {
+    /// Adds a group of equally likely candidates.
+    pub fn group(mut self, neighbors: impl IntoIterator<Item = IVec2>) -> MovementPriorityBuilder {
+        self.neighbor_groups
+            .push(NeighborGroup::new(neighbors.into_iter().collect()));
+        self
+    }
+
+    /// Adds a group holding `neighbor` and its mirror image across the vertical axis, e.g. both
+    /// lower diagonals for `IVec2::new(1, -1)`.
+    pub fn mirrored_group(self, neighbor: IVec2) -> MovementPriorityBuilder {
+        let mirrored = IVec2::new(-neighbor.x, neighbor.y);
+        if mirrored == neighbor {
+            self.group([neighbor])
+        } else {
+            self.group([neighbor, mirrored])
+        }
+    }
+
+    /// Adds a group where each candidate is tried first with a chance proportional to its weight.
+    /// Candidates with a weight of 0 are left out.
+    pub fn weighted_group(
+        self,
+        neighbors: impl IntoIterator<Item = (IVec2, u8)>,
+    ) -> MovementPriorityBuilder {
+        // Candidates are shuffled each tick, so repeating one makes it proportionally more
+        // likely to come first.
+        self.group(
+            neighbors
+                .into_iter()
+                .flat_map(|(neighbor, weight)| iter::repeat_n(neighbor, usize::from(weight))),
+        )
+    }
+
+    pub fn build(self) -> MovementPriority {
+        MovementPriority::new(self.neighbor_groups)
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect)]
@@ -312,12 +794,33 @@ pub struct MovementPriorityBlueprint(pub MovementPriority);
 
 fn handle_particle_registration(
     mut commands: Commands,
+    mut global_rng: ResMut<GlobalRng>,
     parent_query: Query<
         (
             Option<&DensityBlueprint>,
             Option<&VelocityBlueprint>,
             Option<&MovementPriorityBlueprint>,
             Option<&MomentumBlueprint>,
+            (
+                Option<&PassesThroughBlueprint>,
+                Option<&ErodesBlueprint>,
+                Option<&FrictionBlueprint>,
+                Option<&ViscosityBlueprint>,
+                Option<&LeavesTrailBlueprint>,
+                Option<&AllowDiagonalBlueprint>,
+                Option<&BounceBlueprint>,
+                Option<&SpreadBudgetBlueprint>,
+                Option<&WindResistanceBlueprint>,
+                Option<&InfectsOnContactBlueprint>,
+            ),
+            (
+                Option<&WallBlueprint>,
+                Option<&SolidBlueprint>,
+                Option<&MovableSolidBlueprint>,
+                Option<&LiquidBlueprint>,
+                Option<&GasBlueprint>,
+                Option<&DiagonalMoverBlueprint>,
+            ),
         ),
         With<ParticleType>,
     >,
@@ -327,9 +830,28 @@ fn handle_particle_registration(
     ev_particle_registered.read().for_each(|ev| {
         ev.entities.iter().for_each(|entity| {
             if let Ok(parent) = particle_query.get(*entity) {
-                commands.entity(*entity).insert(PhysicsRng::default());
-                if let Ok((density, velocity, movement_priority, momentum)) =
-                    parent_query.get(parent.get())
+                commands
+                    .entity(*entity)
+                    .insert(PhysicsRng(RngComponent::from(&mut *global_rng)));
+                if let Ok((
+                    density,
+                    velocity,
+                    movement_priority,
+                    momentum,
+                    (
+                        passes_through,
+                        erodes,
+                        friction,
+                        viscosity,
+                        leaves_trail,
+                        allow_diagonal,
+                        bounce,
+                        spread_budget,
+                        wind_resistance,
+                        infects_on_contact,
+                    ),
+                    (wall, solid, movable_solid, liquid, gas, diagonal_mover),
+                )) = parent_query.get(parent.get())
                 {
                     if let Some(density) = density {
                         commands.entity(*entity).insert(density.0);
@@ -351,8 +873,168 @@ fn handle_particle_registration(
                     } else {
                         commands.entity(*entity).remove::<Momentum>();
                     }
+                    if let Some(passes_through) = passes_through {
+                        commands.entity(*entity).insert(passes_through.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<PassesThrough>();
+                    }
+                    if let Some(erodes) = erodes {
+                        commands.entity(*entity).insert(erodes.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<Erodes>();
+                    }
+                    if let Some(friction) = friction {
+                        commands.entity(*entity).insert(friction.0);
+                    } else {
+                        commands.entity(*entity).remove::<Friction>();
+                    }
+                    if let Some(viscosity) = viscosity {
+                        commands.entity(*entity).insert(viscosity.0);
+                    } else {
+                        commands.entity(*entity).remove::<Viscosity>();
+                    }
+                    if let Some(leaves_trail) = leaves_trail {
+                        commands.entity(*entity).insert(leaves_trail.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<LeavesTrail>();
+                    }
+                    if let Some(allow_diagonal) = allow_diagonal {
+                        commands.entity(*entity).insert(allow_diagonal.0);
+                    } else {
+                        commands.entity(*entity).remove::<AllowDiagonal>();
+                    }
+                    if let Some(bounce) = bounce {
+                        commands.entity(*entity).insert(bounce.0);
+                    } else {
+                        commands.entity(*entity).remove::<Bounce>();
+                    }
+                    if let Some(spread_budget) = spread_budget {
+                        commands.entity(*entity).insert(spread_budget.0);
+                    } else {
+                        commands.entity(*entity).remove::<SpreadBudget>();
+                    }
+                    if let Some(wind_resistance) = wind_resistance {
+                        commands.entity(*entity).insert(wind_resistance.0);
+                    } else {
+                        commands.entity(*entity).remove::<WindResistance>();
+                    }
+                    if let Some(infects_on_contact) = infects_on_contact {
+                        commands
+                            .entity(*entity)
+                            .insert(infects_on_contact.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<InfectsOnContact>();
+                    }
+                    if let Some(wall) = wall {
+                        commands.entity(*entity).insert(wall.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<Wall>();
+                    }
+                    if let Some(solid) = solid {
+                        commands.entity(*entity).insert(solid.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<Solid>();
+                    }
+                    if let Some(movable_solid) = movable_solid {
+                        commands.entity(*entity).insert(movable_solid.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<MovableSolid>();
+                    }
+                    if let Some(liquid) = liquid {
+                        commands.entity(*entity).insert(liquid.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<Liquid>();
+                    }
+                    if let Some(gas) = gas {
+                        commands.entity(*entity).insert(gas.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<Gas>();
+                    }
+                    if let Some(diagonal_mover) = diagonal_mover {
+                        commands.entity(*entity).insert(diagonal_mover.0.clone());
+                    } else {
+                        commands.entity(*entity).remove::<DiagonalMover>();
+                    }
                 }
             }
         });
     });
 }
+
+/// Looks up the particle type ids for [`PassesThrough`] and [`InfectsOnContact`] components that
+/// changed, or for all of them once the [`ParticleTypeMap`] changes.
+pub fn resolve_particle_type_ids(
+    mut passes_through_query: Query<&mut PassesThrough>,
+    mut infects_on_contact_query: Query<&mut InfectsOnContact>,
+    type_map: Res<ParticleTypeMap>,
+) {
+    // Resolving bypasses change detection, so a resolved component isn't resolved again next tick.
+    for mut passes_through in &mut passes_through_query {
+        if type_map.is_changed() || passes_through.is_changed() {
+            passes_through.bypass_change_detection().resolve(&type_map);
+        }
+    }
+    for mut infects_on_contact in &mut infects_on_contact_query {
+        if type_map.is_changed() || infects_on_contact.is_changed() {
+            infects_on_contact
+                .bypass_change_detection()
+                .resolve(&type_map);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downward_biased_falls_then_slides() {
+        assert_eq!(
+            MovementPriority::downward_biased(),
+            MovementPriority::from(vec![
+                vec![IVec2::NEG_Y],
+                vec![IVec2::new(1, -1), IVec2::NEG_ONE],
+                vec![IVec2::X, IVec2::NEG_X],
+            ])
+        );
+    }
+
+    #[test]
+    fn symmetric_fall_picks_evenly_below() {
+        assert_eq!(
+            MovementPriority::symmetric_fall(),
+            MovementPriority::from(vec![vec![IVec2::NEG_Y, IVec2::NEG_ONE, IVec2::new(1, -1)]])
+        );
+    }
+
+    #[test]
+    fn builder_adds_groups_in_order() {
+        let priority = MovementPriority::builder()
+            .group([IVec2::NEG_Y])
+            .mirrored_group(IVec2::new(1, -1))
+            // Straight up is its own mirror image.
+            .mirrored_group(IVec2::Y)
+            .weighted_group([(IVec2::NEG_ONE, 2), (IVec2::new(1, -1), 1), (IVec2::X, 0)])
+            .build();
+
+        assert_eq!(
+            priority,
+            MovementPriority::from(vec![
+                vec![IVec2::NEG_Y],
+                vec![IVec2::new(1, -1), IVec2::NEG_ONE],
+                vec![IVec2::Y],
+                vec![IVec2::NEG_ONE, IVec2::NEG_ONE, IVec2::new(1, -1)],
+            ])
+        );
+    }
+
+    #[test]
+    fn weighted_group_with_only_zero_weights_is_empty() {
+        let priority = MovementPriority::builder()
+            .weighted_group([(IVec2::NEG_Y, 0), (IVec2::X, 0)])
+            .build();
+
+        assert_eq!(priority.len(), 1);
+        assert_eq!(priority.iter().next(), Some(&NeighborGroup::empty()));
+    }
+}
@@ -23,5 +23,9 @@ impl PhysicsRng {
     pub fn index(&mut self, bound: impl RangeBounds<usize>) -> usize {
         self.0.index(bound)
     }
+
+    pub fn u64(&mut self, bound: impl RangeBounds<u64>) -> u64 {
+        self.0.u64(bound)
+    }
 }
 
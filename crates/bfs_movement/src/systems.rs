@@ -2,156 +2,832 @@ use crate::PhysicsRng;
 use crate::*;
 use std::mem;
 
+use bevy::ecs::query::QueryItem;
+use bevy::tasks::ComputeTaskPool;
 use bevy::utils::HashSet;
-use bfs_core::{ChunkMap, Coordinates, Particle, ParticleSimulationSet};
+use bfs_core::{
+    cached_neighbors, Chunk, ChunkMap, ChunkView, Coordinates, Frozen, GlobalModifiers, Lifetime,
+    MutateParticleEvent, NeighborCache, Particle, ParticleSimulation, ParticleSimulationSet,
+    ParticleTypeId, ParticleTypeMap, NEIGHBOR_OFFSETS,
+};
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+
+/// How far up a liquid column is searched when weighing its stack bias.
+const MAX_STACK_DEPTH: i32 = 16;
 
 pub struct SystemsPlugin;
 
 impl Plugin for SystemsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, handle_movement.in_set(ParticleSimulationSet));
+        app.add_systems(
+            ParticleSimulation,
+            (
+                handle_movement.run_if(not(moved_in_background)),
+                handle_erosion.after(handle_movement),
+                handle_buoyancy.after(handle_movement),
+                equalize_liquid_pressure
+                    .after(handle_movement)
+                    .run_if(resource_exists::<LiquidPressureEqualization>),
+            )
+                .in_set(ParticleSimulationSet)
+                .in_set(ParticleMovementSet),
+        )
+        .init_resource::<MovementConflictPolicy>()
+        .register_type::<LiquidPressureEqualization>()
+        .register_type::<MovementConflictPolicy>()
+        .register_type::<ParallelMovement>();
     }
 }
 
-#[allow(unused_mut)]
+/// The systems that move particles each tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParticleMovementSet;
+
+/// Decides which particle gets a cell when more than one particle could move into it in the same
+/// tick.
+///
+/// Particles move one at a time, so whichever particle moves first claims a contested cell and
+/// the rest find it occupied. The policy controls the order particles move in each tick.
+#[derive(Resource, Copy, Clone, Eq, PartialEq, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub enum MovementConflictPolicy {
+    /// Particles move in the order they're stored in. This is the cheapest policy, but the same
+    /// particles tend to win every tick, which can bias streams that meet.
+    #[default]
+    FirstWins,
+    /// Particles move in a random order drawn from their [`PhysicsRng`], so contested cells are
+    /// shared fairly while staying reproducible for a given seed.
+    RandomWins,
+    /// Denser particles move first, so heavier particles claim contested cells.
+    DensestWins,
+}
+
+/// Enables a pressure equalization pass for liquids when present.
+///
+/// Each tick, liquid particles sitting at an exposed surface may be moved to a lower exposed
+/// surface of the same liquid body, so connected vessels level out to the same height. Only
+/// surfaces with an empty cell above them can receive liquid, so sealed columns never rise.
+/// This pass is expensive and is disabled by default.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct LiquidPressureEqualization {
+    /// The maximum number of connected liquid cells to search from each surface particle.
+    pub max_search: usize,
+    /// The chance each surface particle attempts to equalize on a given tick.
+    pub rate: f64,
+}
+
+impl Default for LiquidPressureEqualization {
+    fn default() -> LiquidPressureEqualization {
+        LiquidPressureEqualization {
+            max_search: 512,
+            rate: 0.1,
+        }
+    }
+}
+
+/// Moves particles in parallel when present, using every thread of the `ComputeTaskPool`.
+///
+/// The map's chunks are split into a 2x2 checkerboard of phases (see
+/// [`ChunkMap::for_each_checkerboard_phase`]). The chunks of a phase never touch, so their
+/// particles are moved concurrently, one task per batch of chunks. A particle is only moved by its
+/// chunk's task if no cell it could reach this tick, given its velocity, movement priority and
+/// bounce, lies on or past the edge of its chunk. Every other particle, such as one about to cross
+/// into the next chunk, is deferred and moved once all phases are done, one at a time. Liquids
+/// that jump far sideways are deferred more often than not, so they gain the least from this.
+///
+/// For a given seed the outcome is the same however many threads there are and whatever
+/// `chunks_per_task` is set to. It's not the same as with serial movement, since particles claim
+/// contested cells in a different order. Serial movement remains the default.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct ParallelMovement {
+    /// How many chunks of a phase each task moves particles in, one after another. Larger batches
+    /// mean fewer, longer tasks.
+    pub chunks_per_task: usize,
+}
+
+impl Default for ParallelMovement {
+    fn default() -> ParallelMovement {
+        ParallelMovement { chunks_per_task: 4 }
+    }
+}
+
+pub(crate) type MovementData = (
+    Entity,
+    &'static Particle,
+    &'static mut Coordinates,
+    &'static mut Transform,
+    &'static mut PhysicsRng,
+    &'static mut Velocity,
+    Option<&'static mut Momentum>,
+    &'static Density,
+    &'static mut MovementPriority,
+    Option<&'static PassesThrough>,
+    &'static ParticleTypeId,
+    (
+        Option<&'static Friction>,
+        Option<&'static AllowDiagonal>,
+        Option<&'static Bounce>,
+        Option<&'static DiagonalMover>,
+        Option<&'static InfectsOnContact>,
+        Option<&'static Viscosity>,
+    ),
+    Option<&'static Liquid>,
+    Option<&'static LeavesTrail>,
+    Option<&'static mut SpreadBudget>,
+);
+
+type MovementQuery<'w, 's> = Query<'w, 's, MovementData, Without<Frozen>>;
+
+/// What a moving particle needs to know about the particles around it. This matches the same
+/// particles as [`MovementQuery`], but doesn't borrow anything mutably, so the tasks of a parallel
+/// phase can share it.
+type NeighborQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static ParticleTypeId,
+        &'static Density,
+        Option<&'static Liquid>,
+    ),
+    (
+        With<Particle>,
+        With<Coordinates>,
+        With<Transform>,
+        With<PhysicsRng>,
+        With<Velocity>,
+        With<MovementPriority>,
+        Without<Frozen>,
+    ),
+>;
+
+/// The cells a particle sees while it moves: the whole map, or one chunk of a parallel phase.
+trait Cells {
+    fn chunk(&self, coords: &IVec2) -> Option<&Chunk>;
+    fn entity(&self, coords: &IVec2) -> Option<Entity>;
+    fn contains(&self, coords: IVec2) -> bool;
+    fn is_occupied(&self, coords: IVec2) -> bool;
+    fn neighbors(&mut self, coords: IVec2) -> [Option<Entity>; 8];
+    fn swap(&mut self, first: IVec2, second: IVec2);
+    fn mark_dirty(&mut self, coords: IVec2);
+}
+
+struct WholeMap<'a> {
+    map: &'a mut ChunkMap,
+    neighbor_cache: Option<&'a mut NeighborCache>,
+}
+
+impl Cells for WholeMap<'_> {
+    fn chunk(&self, coords: &IVec2) -> Option<&Chunk> {
+        self.map.chunk(coords)
+    }
+
+    fn entity(&self, coords: &IVec2) -> Option<Entity> {
+        self.map.entity(coords).copied()
+    }
+
+    fn contains(&self, coords: IVec2) -> bool {
+        self.map.contains(coords)
+    }
+
+    fn is_occupied(&self, coords: IVec2) -> bool {
+        self.map.is_occupied(coords)
+    }
+
+    fn neighbors(&mut self, coords: IVec2) -> [Option<Entity>; 8] {
+        cached_neighbors(self.map, self.neighbor_cache.as_deref_mut(), coords)
+    }
+
+    fn swap(&mut self, first: IVec2, second: IVec2) {
+        self.map.swap(first, second);
+    }
+
+    fn mark_dirty(&mut self, coords: IVec2) {
+        self.map.mark_dirty(coords);
+    }
+}
+
+impl Cells for ChunkView<'_> {
+    fn chunk(&self, coords: &IVec2) -> Option<&Chunk> {
+        ChunkView::chunk(self, coords)
+    }
+
+    fn entity(&self, coords: &IVec2) -> Option<Entity> {
+        ChunkView::entity(self, coords).copied()
+    }
+
+    fn contains(&self, coords: IVec2) -> bool {
+        ChunkView::contains(self, coords)
+    }
+
+    fn is_occupied(&self, coords: IVec2) -> bool {
+        ChunkView::is_occupied(self, coords)
+    }
+
+    fn neighbors(&mut self, coords: IVec2) -> [Option<Entity>; 8] {
+        ChunkView::neighbors(self, coords)
+    }
+
+    fn swap(&mut self, first: IVec2, second: IVec2) {
+        ChunkView::swap(self, first, second);
+    }
+
+    fn mark_dirty(&mut self, coords: IVec2) {
+        ChunkView::mark_dirty(self, coords);
+    }
+}
+
+/// What moving particles leave to be dealt with once every particle has moved.
+#[derive(Default)]
+struct MovementOutcome {
+    /// Cells particles moved into this tick. Particles shouldn't move more than once.
+    visited: HashSet<IVec2>,
+    /// Cells vacated by particles that leave a trail. These are only filled once every particle
+    /// has moved, so a trail never lands on a cell that was refilled this tick.
+    trails: Vec<(IVec2, LeavesTrail)>,
+    /// Particles converted by an infecting particle landing on them. Conversions are applied after
+    /// movement, so an infecting particle can't swap into a cell it just converted.
+    infections: Vec<MutateParticleEvent>,
+}
+
+impl MovementOutcome {
+    fn extend(&mut self, other: MovementOutcome) {
+        self.visited.extend(other.visited);
+        self.trails.extend(other.trails);
+        self.infections.extend(other.infections);
+    }
+}
+
+/// How many cells a particle could get from its cell this tick, along either axis.
+fn reach(
+    velocity: &Velocity,
+    movement_priority: &MovementPriority,
+    bounce: Option<&Bounce>,
+    gravity_scale: f32,
+) -> i32 {
+    let steps = (f32::from(velocity.val) * gravity_scale).ceil() as i32;
+    let stride = movement_priority
+        .neighbor_groups
+        .iter()
+        .flat_map(|neighbor_group| neighbor_group.neighbor_group.iter())
+        .map(|offset| offset.abs().max_element())
+        .max()
+        .unwrap_or(0);
+    steps * stride + i32::from(bounce.map_or(0, |bounce| bounce.hop_distance(velocity)))
+}
+
+#[allow(unused_mut, clippy::too_many_arguments)]
 pub fn handle_movement(
-    mut particle_query: Query<(
-        Entity,
-        &Particle,
-        &mut Coordinates,
-        &mut Transform,
-        &mut PhysicsRng,
-        &mut Velocity,
-        Option<&mut Momentum>,
-        &Density,
-        &mut MovementPriority,
-    )>,
+    mut commands: Commands,
+    mut particle_query: MovementQuery,
+    neighbor_query: NeighborQuery,
     mut map: ResMut<ChunkMap>,
+    mut neighbor_cache: Option<ResMut<NeighborCache>>,
+    modifiers: Res<GlobalModifiers>,
+    policy: Res<MovementConflictPolicy>,
+    parallel: Option<Res<ParallelMovement>>,
+    mut ev_mutate_particle: EventWriter<MutateParticleEvent>,
 ) {
-    // Check visited before we perform logic on a particle (particles shouldn't move more than once)
-    let mut visited: HashSet<IVec2> = HashSet::default();
-    unsafe {
-        particle_query.iter_unsafe().for_each(
-            |(
-                _,
-                particle_type,
-                mut coordinates,
-                mut transform,
-                mut rng,
-                mut velocity,
-                mut momentum,
-                density,
-                mut movement_priority,
-            )| {
-                if let Some(chunk) = map.chunk(&coordinates.0) {
-                    let hibernating = chunk.hibernating();
-                    if let Some(dirty_rect) = chunk.prev_dirty_rect() {
-                        if hibernating {
-                            if rng.chance(0.95) {
-                                return;
+    let gravity_scale = modifiers.gravity_scale();
+    let order: Option<Vec<Entity>> = match *policy {
+        MovementConflictPolicy::FirstWins => None,
+        MovementConflictPolicy::RandomWins => {
+            let mut keyed: Vec<(u64, Entity)> = particle_query
+                .iter_mut()
+                .map(|mut particle| (particle.4.u64(..), particle.0))
+                .collect();
+            keyed.sort_unstable();
+            Some(keyed.into_iter().map(|(_, entity)| entity).collect())
+        }
+        MovementConflictPolicy::DensestWins => {
+            let mut keyed: Vec<(Reverse<u32>, Entity)> = particle_query
+                .iter()
+                .map(|particle| (Reverse(particle.7 .0), particle.0))
+                .collect();
+            keyed.sort_by_key(|(density, _)| *density);
+            Some(keyed.into_iter().map(|(_, entity)| entity).collect())
+        }
+    };
+    let mut outcome = MovementOutcome::default();
+    match parallel {
+        Some(parallel) => {
+            let order =
+                order.unwrap_or_else(|| particle_query.iter().map(|particle| particle.0).collect());
+            // Particles that could reach the edge of their chunk are moved after every phase.
+            let mut by_chunk: Vec<Vec<Entity>> = vec![Vec::new(); map.chunks.len()];
+            let mut deferred: Vec<Entity> = vec![];
+            for entity in order {
+                let Ok(particle) = particle_query.get(entity) else {
+                    continue;
+                };
+                let coordinates = particle.2 .0;
+                let margin =
+                    IVec2::splat(reach(particle.5, particle.8, particle.11 .2, gravity_scale) + 1);
+                let chunk_index = map.chunk_index(&coordinates).filter(|index| {
+                    let region = map.chunks[*index].region();
+                    map.entity(&coordinates) == Some(&entity)
+                        && coordinates.cmpge(region.min + margin).all()
+                        && coordinates.cmple(region.max - margin).all()
+                });
+                match chunk_index {
+                    Some(index) => by_chunk[index].push(entity),
+                    None => deferred.push(entity),
+                }
+            }
+
+            let chunks_per_task = parallel.chunks_per_task.max(1);
+            let (particles, neighbors, by_chunk) = (&particle_query, &neighbor_query, &by_chunk);
+            map.for_each_checkerboard_phase(|views| {
+                let mut batches: Vec<Vec<ChunkView>> = vec![];
+                for view in views {
+                    if by_chunk[view.index()].is_empty() {
+                        continue;
+                    }
+                    match batches.last_mut() {
+                        Some(batch) if batch.len() < chunks_per_task => batch.push(view),
+                        _ => batches.push(vec![view]),
+                    }
+                }
+                let outcomes = ComputeTaskPool::get().scope(|scope| {
+                    for batch in batches {
+                        scope.spawn(async move {
+                            let mut outcome = MovementOutcome::default();
+                            for mut view in batch {
+                                for entity in &by_chunk[view.index()] {
+                                    // SAFETY: Each particle is moved by its chunk's task only, and
+                                    // only swaps with particles in the cells its view can change,
+                                    // which belong to the same chunk.
+                                    unsafe {
+                                        if let Ok(particle) = particles.get_unchecked(*entity) {
+                                            move_particle(
+                                                particle,
+                                                &mut view,
+                                                particles,
+                                                neighbors,
+                                                gravity_scale,
+                                                &mut outcome,
+                                            );
+                                        }
+                                    }
+                                }
                             }
-                        } else if !dirty_rect.contains(coordinates.0) && rng.chance(0.7) {
-                            return;
-                        }
+                            outcome
+                        });
                     }
+                });
+                for phase_outcome in outcomes {
+                    outcome.extend(phase_outcome);
                 }
+            });
 
-                // Used to determine if we should add the particle to set of visited particles.
-                let mut moved = false;
-                'velocity_loop: for _ in 0..velocity.val {
-                    // If a particle is blocked on a certain vector, we shouldn't attempt to swap it with other particles along that
-                    // same vector.
-                    let mut obstructed: HashSet<IVec2> = HashSet::default();
+            let mut cells = WholeMap {
+                map: &mut map,
+                neighbor_cache: neighbor_cache.as_deref_mut(),
+            };
+            for entity in deferred {
+                // SAFETY: Particles are moved one at a time here.
+                unsafe {
+                    if let Ok(particle) = particle_query.get_unchecked(entity) {
+                        move_particle(
+                            particle,
+                            &mut cells,
+                            &particle_query,
+                            &neighbor_query,
+                            gravity_scale,
+                            &mut outcome,
+                        );
+                    }
+                }
+            }
+        }
+        None => {
+            let mut cells = WholeMap {
+                map: &mut map,
+                neighbor_cache: neighbor_cache.as_deref_mut(),
+            };
+            unsafe {
+                let particles: Box<dyn Iterator<Item = _>> = match order {
+                    Some(order) => Box::new(
+                        order
+                            .into_iter()
+                            .filter_map(|entity| particle_query.get_unchecked(entity).ok()),
+                    ),
+                    None => Box::new(particle_query.iter_unsafe()),
+                };
+                particles.for_each(|particle| {
+                    move_particle(
+                        particle,
+                        &mut cells,
+                        &particle_query,
+                        &neighbor_query,
+                        gravity_scale,
+                        &mut outcome,
+                    );
+                });
+            }
+        }
+    }
 
-                    for relative_coordinates in movement_priority
-                        .iter_candidates(&mut rng, momentum.as_deref().cloned().as_ref())
-                    {
-                        let neighbor_coordinates = coordinates.0 + *relative_coordinates;
+    ev_mutate_particle.send_batch(outcome.infections);
 
-                        if visited.contains(&neighbor_coordinates)
-                            || obstructed.contains(&relative_coordinates.signum())
-                        {
-                            continue;
-                        }
+    outcome
+        .trails
+        .into_iter()
+        .filter(|(coordinates, _)| !map.is_occupied(*coordinates))
+        .for_each(|(coordinates, leaves_trail)| {
+            commands.spawn((
+                leaves_trail.particle,
+                Transform::from_xyz(coordinates.x as f32, coordinates.y as f32, 0.),
+                Lifetime(leaves_trail.lifetime),
+            ));
+        });
+}
 
-                        match map.entity(&neighbor_coordinates) {
-                            Some(neighbor_entity) => {
-                                if let Ok((
-                                    _,
-                                    neighbor_particle_type,
-                                    mut neighbor_coordinates,
-                                    mut neighbor_transform,
-                                    _,
-                                    _,
-                                    _,
-                                    neighbor_density,
-                                    _,
-                                )) = particle_query.get_unchecked(*neighbor_entity)
-                                {
-                                    if *particle_type == *neighbor_particle_type {
-                                        continue;
-                                    }
-                                    if density > neighbor_density {
-                                        map.swap(neighbor_coordinates.0, coordinates.0);
-
-                                        swap_particle_positions(
-                                            &mut coordinates,
-                                            &mut transform,
-                                            &mut neighbor_coordinates,
-                                            &mut neighbor_transform,
-                                        );
-
-                                        if let Some(ref mut momentum) = momentum {
-                                            momentum.0 = IVec2::ZERO; // Reset momentum after a swap
-                                        }
+/// Moves a single particle for this tick.
+///
+/// # Safety
+///
+/// `particle` must come from `particle_query`, and nothing else may access it or the particles in
+/// the cells `cells` can change while it moves.
+unsafe fn move_particle(
+    (
+        _,
+        particle,
+        mut coordinates,
+        mut transform,
+        mut rng,
+        mut velocity,
+        mut momentum,
+        density,
+        mut movement_priority,
+        passes_through,
+        type_id,
+        (friction, allow_diagonal, bounce, diagonal_mover, infects_on_contact, viscosity),
+        liquid,
+        leaves_trail,
+        mut spread_budget,
+    ): QueryItem<'_, MovementData>,
+    cells: &mut impl Cells,
+    particle_query: &MovementQuery,
+    neighbor_query: &NeighborQuery,
+    gravity_scale: f32,
+    outcome: &mut MovementOutcome,
+) {
+    if let Some(chunk) = cells.chunk(&coordinates.0) {
+        // Hibernating chunks have settled, so skip them until something wakes them.
+        if chunk.hibernating() {
+            return;
+        }
+        if let Some(dirty_rect) = chunk.prev_dirty_rect() {
+            if !dirty_rect.contains(coordinates.0) && rng.chance(0.7) {
+                return;
+            }
+        }
+    }
 
-                                        velocity.decrement();
-                                        moved = true;
-                                        break 'velocity_loop;
-                                    } else {
-                                        obstructed.insert(relative_coordinates.signum());
-                                        continue;
-                                    }
-                                }
-                                // We've encountered an anchored particle
-                                else {
-                                    obstructed.insert(relative_coordinates.signum());
-                                    continue;
-                                }
-                            }
-                            // We've encountered a free slot for the target particle to move to
-                            None => {
-                                map.swap(coordinates.0, neighbor_coordinates);
-                                coordinates.0 = neighbor_coordinates;
+    // Friction is rolled once per tick and decides whether diagonal steps are allowed.
+    // Diagonal movers have no other way to move, so they always slide.
+    let slides = diagonal_mover.is_some()
+        || allow_diagonal.is_none_or(|allow_diagonal| allow_diagonal.0)
+            && friction.is_none_or(|friction| !rng.chance(f64::from(friction.0)));
 
-                                transform.translation.x = neighbor_coordinates.x as f32;
-                                transform.translation.y = neighbor_coordinates.y as f32;
+    let is_same_type = |entity: &Entity| {
+        neighbor_query
+            .get(*entity)
+            .is_ok_and(|(neighbor_type_id, _, _)| neighbor_type_id == type_id)
+    };
+    // Liquids without enough of the same liquid around them may still fall, but won't
+    // spread sideways.
+    let spreads = liquid.is_none_or(|liquid| {
+        liquid.min_neighbors_to_spread == 0
+            || cells
+                .neighbors(coordinates.0)
+                .iter()
+                .flatten()
+                .filter(|neighbor_entity| is_same_type(neighbor_entity))
+                .count()
+                >= liquid.min_neighbors_to_spread as usize
+    }) && liquid.is_none_or(|liquid| {
+        // Liquids biased toward stacking mostly hold still at the surface, and are
+        // pushed sideways more readily the deeper they sit in a column.
+        liquid.stack_bias == 0 || {
+            // The column ends at the first cell without the same liquid, which
+            // includes the first cell past the top of the map.
+            let depth = (1..=MAX_STACK_DEPTH)
+                .take_while(|offset| {
+                    cells
+                        .entity(&(coordinates.0 + IVec2::Y * *offset))
+                        .is_some_and(|above| is_same_type(&above))
+                })
+                .count();
+            !rng.chance(liquid.rest_chance(depth))
+        }
+    });
+    // Viscous liquids also hold still sideways on some ticks.
+    let holds_still =
+        spreads && viscosity.is_some_and(|viscosity| rng.chance(viscosity.chance_to_hold()));
+    let spreads = spreads && !holds_still;
 
-                                if let Some(ref mut momentum) = momentum {
-                                    momentum.0 = *relative_coordinates; // Set momentum relative to the current position
-                                }
+    // Trails are also rolled once per tick, and left in every cell vacated this tick.
+    let leaves_trail = leaves_trail.filter(|leaves_trail| rng.chance(leaves_trail.chance));
 
-                                velocity.increment();
+    // Infection is rolled once per tick too, and only applies the first time the
+    // particle is blocked from below.
+    let mut infects_on_contact =
+        infects_on_contact.filter(|infects_on_contact| rng.chance(infects_on_contact.chance));
+    // Used to determine if we should add the particle to set of visited particles.
+    let mut moved = false;
+    let hop_distance = bounce.map_or(0, |bounce| bounce.hop_distance(&velocity));
+    // Global gravity scales the number of steps taken this tick, with the fractional
+    // part rolled so that e.g. half gravity moves at half speed on average.
+    let steps = if gravity_scale == 1. {
+        velocity.val
+    } else {
+        let scaled = f32::from(velocity.val) * gravity_scale;
+        (scaled.floor() as u8).saturating_add(u8::from(rng.chance(f64::from(scaled.fract()))))
+    };
+    'velocity_loop: for _ in 0..steps {
+        // If a particle is blocked on a certain vector, we shouldn't attempt to swap it with other particles along that
+        // same vector.
+        let mut obstructed: HashSet<IVec2> = HashSet::default();
 
-                                moved = true;
+        for relative_coordinates in
+            movement_priority.iter_candidates(&mut rng, momentum.as_deref().cloned().as_ref())
+        {
+            let neighbor_coordinates = coordinates.0 + *relative_coordinates;
 
-                                continue 'velocity_loop;
+            if outcome.visited.contains(&neighbor_coordinates)
+                || obstructed.contains(&relative_coordinates.signum())
+            {
+                continue;
+            }
+
+            if !slides && relative_coordinates.x != 0 && relative_coordinates.y != 0 {
+                continue;
+            }
+
+            if relative_coordinates.y == 0
+                && (!spreads
+                    || spread_budget
+                        .as_ref()
+                        .is_some_and(|spread_budget| spread_budget.is_spent()))
+            {
+                continue;
+            }
+
+            // The edge of the map blocks particles like a wall would.
+            if !cells.contains(neighbor_coordinates) {
+                obstructed.insert(relative_coordinates.signum());
+                continue;
+            }
+
+            match cells.entity(&neighbor_coordinates) {
+                Some(neighbor_entity) => {
+                    if let Ok((neighbor_type_id, neighbor_density, neighbor_liquid)) =
+                        neighbor_query.get(neighbor_entity)
+                    {
+                        if type_id == neighbor_type_id {
+                            continue;
+                        }
+                        // A liquid only sinks through a lighter liquid rather than
+                        // pushing it sideways, so layered liquids settle instead of
+                        // churning. Liquids of equal density never displace each other.
+                        let displaces = density > neighbor_density
+                            && (liquid.is_none()
+                                || neighbor_liquid.is_none()
+                                || relative_coordinates.y < 0);
+                        // Passing through another particle is a regular swap, so the
+                        // visited set keeps two mutually passable types from swapping
+                        // back and forth within a single tick.
+                        if displaces
+                            || passes_through.is_some_and(|passes_through| {
+                                passes_through.contains(*neighbor_type_id)
+                            })
+                        {
+                            let Ok((_, _, mut neighbor_coordinates, mut neighbor_transform, ..)) =
+                                particle_query.get_unchecked(neighbor_entity)
+                            else {
+                                obstructed.insert(relative_coordinates.signum());
+                                continue;
+                            };
+                            cells.swap(neighbor_coordinates.0, coordinates.0);
+
+                            swap_particle_positions(
+                                &mut coordinates,
+                                &mut transform,
+                                &mut neighbor_coordinates,
+                                &mut neighbor_transform,
+                            );
+
+                            if let Some(ref mut momentum) = momentum {
+                                momentum.0 = IVec2::ZERO; // Reset momentum after a swap
+                            }
+                            if let Some(ref mut spread_budget) = spread_budget {
+                                spread_budget.record_move(*relative_coordinates);
+                            }
+
+                            velocity.decrement();
+                            moved = true;
+                            break 'velocity_loop;
+                        } else {
+                            if *relative_coordinates == IVec2::NEG_Y {
+                                if let Some(infects_on_contact) = infects_on_contact.take() {
+                                    if infects_on_contact.infects(*neighbor_type_id) {
+                                        outcome.infections.push(MutateParticleEvent {
+                                            entity: neighbor_entity,
+                                            particle: particle.clone(),
+                                        });
+                                    }
+                                }
                             }
-                        };
+                            obstructed.insert(relative_coordinates.signum());
+                            continue;
+                        }
+                    }
+                    // We've encountered an anchored particle
+                    else {
+                        obstructed.insert(relative_coordinates.signum());
+                        continue;
                     }
                 }
+                // We've encountered a free slot for the target particle to move to
+                None => {
+                    if let Some(leaves_trail) = leaves_trail {
+                        outcome.trails.push((coordinates.0, leaves_trail.clone()));
+                    }
+                    cells.swap(coordinates.0, neighbor_coordinates);
+                    coordinates.0 = neighbor_coordinates;
+
+                    transform.translation.x = neighbor_coordinates.x as f32;
+                    transform.translation.y = neighbor_coordinates.y as f32;
 
-                if moved {
-                    visited.insert(coordinates.0);
-                } else {
                     if let Some(ref mut momentum) = momentum {
-                        momentum.0 = IVec2::ZERO;
+                        momentum.0 = *relative_coordinates; // Set momentum relative to the current position
+                    }
+                    if let Some(ref mut spread_budget) = spread_budget {
+                        spread_budget.record_move(*relative_coordinates);
                     }
-                    velocity.decrement();
+
+                    velocity.increment();
+
+                    moved = true;
+
+                    continue 'velocity_loop;
                 }
-            },
-        );
+            };
+        }
     }
+
+    // Particles that bounce hop diagonally upward off whatever they landed on, one empty
+    // cell at a time. Velocity is reset so the next landing makes for a lower hop.
+    if hop_distance > 0 && cells.is_occupied(coordinates.0 + IVec2::NEG_Y) {
+        let direction = if rng.chance(0.5) {
+            IVec2::new(-1, 1)
+        } else {
+            IVec2::new(1, 1)
+        };
+        for _ in 0..hop_distance {
+            let hop_coordinates = coordinates.0 + direction;
+            if cells.chunk(&hop_coordinates).is_none()
+                || cells.is_occupied(hop_coordinates)
+                || outcome.visited.contains(&hop_coordinates)
+            {
+                break;
+            }
+            cells.swap(coordinates.0, hop_coordinates);
+            coordinates.0 = hop_coordinates;
+            transform.translation.x = hop_coordinates.x as f32;
+            transform.translation.y = hop_coordinates.y as f32;
+            moved = true;
+        }
+        velocity.val = 1;
+        if let Some(ref mut momentum) = momentum {
+            momentum.0 = IVec2::ZERO;
+        }
+    }
+
+    if moved {
+        outcome.visited.insert(coordinates.0);
+    } else {
+        if let Some(ref mut momentum) = momentum {
+            momentum.0 = IVec2::ZERO;
+        }
+        velocity.decrement();
+        // A viscous liquid that held still with room to spread keeps its chunk awake,
+        // so it still levels out instead of hibernating partway.
+        if holds_still
+            && [IVec2::X, IVec2::NEG_X].into_iter().any(|side| {
+                let side = coordinates.0 + side;
+                cells.contains(side) && !cells.is_occupied(side)
+            })
+        {
+            cells.mark_dirty(coordinates.0);
+        }
+    }
+}
+
+pub fn handle_erosion(
+    mut map: ResMut<ChunkMap>,
+    mut liquid_query: Query<
+        (
+            &Erodes,
+            &Velocity,
+            &mut Coordinates,
+            &mut Transform,
+            &mut PhysicsRng,
+        ),
+        (With<Liquid>, Without<Frozen>),
+    >,
+    mut carried_query: Query<
+        (&ParticleTypeId, &mut Coordinates, &mut Transform),
+        (Without<Erodes>, Without<Frozen>),
+    >,
+    type_map: Res<ParticleTypeMap>,
+    mut neighbor_cache: Option<ResMut<NeighborCache>>,
+) {
+    liquid_query.iter_mut().for_each(
+        |(erodes, velocity, mut coordinates, mut transform, mut rng)| {
+            let chance = erodes.chance_at(velocity);
+            if chance <= 0. || !rng.chance(chance) {
+                return;
+            }
+            let Some(carries) = type_map.id_of(&erodes.carries.name) else {
+                return;
+            };
+            let neighbors = cached_neighbors(&map, neighbor_cache.as_deref_mut(), coordinates.0);
+            let start = rng.index(0..neighbors.len());
+            for i in 0..neighbors.len() {
+                let index = (start + i) % neighbors.len();
+                let Some(neighbor_entity) = neighbors[index] else {
+                    continue;
+                };
+                let Ok((type_id, mut neighbor_coordinates, mut neighbor_transform)) =
+                    carried_query.get_mut(neighbor_entity)
+                else {
+                    continue;
+                };
+                if *type_id != carries {
+                    continue;
+                }
+                // Pull the grain into the liquid's cell. It's left to settle out of the flow on
+                // its own once the liquid slows down.
+                debug_assert_eq!(
+                    neighbor_coordinates.0,
+                    coordinates.0 + NEIGHBOR_OFFSETS[index]
+                );
+                map.swap(neighbor_coordinates.0, coordinates.0);
+                swap_particle_positions(
+                    &mut coordinates,
+                    &mut transform,
+                    &mut neighbor_coordinates,
+                    &mut neighbor_transform,
+                );
+                return;
+            }
+        },
+    );
+}
+
+/// Floats movable solids up through liquids denser than themselves.
+///
+/// Solids denser than the surrounding liquid already sink through regular movement, so this only
+/// handles the other direction. A solid only rises into a strictly denser liquid, so one of equal
+/// density rests wherever it is instead of bobbing up and down.
+pub fn handle_buoyancy(
+    mut map: ResMut<ChunkMap>,
+    mut solid_query: Query<
+        (&Density, &mut Coordinates, &mut Transform),
+        (With<MovableSolid>, Without<Frozen>),
+    >,
+    mut liquid_query: Query<
+        (&Density, &mut Coordinates, &mut Transform),
+        (With<Liquid>, Without<MovableSolid>, Without<Frozen>),
+    >,
+) {
+    solid_query
+        .iter_mut()
+        .for_each(|(density, mut coordinates, mut transform)| {
+            let Some(above) = map.entity(&(coordinates.0 + IVec2::Y)).copied() else {
+                return;
+            };
+            let Ok((liquid_density, mut liquid_coordinates, mut liquid_transform)) =
+                liquid_query.get_mut(above)
+            else {
+                return;
+            };
+            if liquid_density <= density {
+                return;
+            }
+            map.swap(coordinates.0, liquid_coordinates.0);
+            swap_particle_positions(
+                &mut coordinates,
+                &mut transform,
+                &mut liquid_coordinates,
+                &mut liquid_transform,
+            );
+        });
 }
 
 fn swap_particle_positions(
@@ -166,3 +842,692 @@ fn swap_particle_positions(
     );
     mem::swap(&mut first_coordinates.0, &mut second_coordinates.0);
 }
+
+pub fn equalize_liquid_pressure(
+    config: Res<LiquidPressureEqualization>,
+    mut map: ResMut<ChunkMap>,
+    mut liquid_query: Query<
+        (Entity, &mut Coordinates, &mut Transform, &mut PhysicsRng),
+        (With<Liquid>, Without<Frozen>),
+    >,
+    type_query: Query<&ParticleTypeId>,
+) {
+    let surfaces: Vec<(Entity, IVec2)> = liquid_query
+        .iter()
+        .filter(|(_, coordinates, _, _)| !map.is_occupied(coordinates.0 + IVec2::Y))
+        .map(|(entity, coordinates, _, _)| (entity, coordinates.0))
+        .collect();
+
+    let mut visited: HashSet<IVec2> = HashSet::default();
+    let mut queue: VecDeque<IVec2> = VecDeque::new();
+
+    for (entity, start) in surfaces {
+        // The surface may have been filled or drained by an earlier equalization this tick.
+        if map.entity(&start) != Some(&entity) || map.is_occupied(start + IVec2::Y) {
+            continue;
+        }
+        let Ok(particle_type) = type_query.get(entity) else {
+            continue;
+        };
+        let Ok((_, mut coordinates, mut transform, mut rng)) = liquid_query.get_mut(entity) else {
+            continue;
+        };
+        if !rng.chance(config.rate) {
+            continue;
+        }
+
+        // Search the connected body of the same liquid for the lowest exposed surface.
+        visited.clear();
+        queue.clear();
+        visited.insert(start);
+        queue.push_back(start);
+        let mut lowest: Option<IVec2> = None;
+        while let Some(position) = queue.pop_front() {
+            if visited.len() > config.max_search {
+                break;
+            }
+            if position != start
+                && !map.is_occupied(position + IVec2::Y)
+                && lowest.map_or(true, |lowest| position.y < lowest.y)
+            {
+                lowest = Some(position);
+            }
+            for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                let neighbor = position + offset;
+                if visited.contains(&neighbor) || !map.contains(neighbor) {
+                    continue;
+                }
+                if let Some(neighbor_entity) = map.entity(&neighbor) {
+                    if type_query
+                        .get(*neighbor_entity)
+                        .is_ok_and(|neighbor_type| neighbor_type == particle_type)
+                    {
+                        visited.insert(neighbor);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        // Moving a particle only reduces the disparity if the target surface sits at least two
+        // cells lower; anything less would just oscillate between the two surfaces.
+        if let Some(lowest) = lowest {
+            if start.y - lowest.y >= 2 {
+                let destination = lowest + IVec2::Y;
+                map.swap(start, destination);
+                coordinates.0 = destination;
+                transform.translation.x = destination.x as f32;
+                transform.translation.y = destination.y as f32;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use bevy::utils::Duration;
+    use bevy_turborand::{GlobalRng, RngPlugin};
+    use bfs_core::testing::{positions, spawn, step};
+    use bfs_core::{FallingSandCorePlugin, ParticleType, SimulationTick};
+
+    fn sim() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            RngPlugin::default(),
+            FallingSandCorePlugin::default(),
+            crate::FallingSandMovementPlugin,
+        ));
+        app.world_mut()
+            .spawn((ParticleType::new("Wall"), WallBlueprint(Wall)));
+        app
+    }
+
+    #[test]
+    fn fixed_tick_rate_falls_a_cell_per_tick() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            RngPlugin::default(),
+            FallingSandCorePlugin::default().with_tick_rate(50.),
+            crate::FallingSandMovementPlugin,
+        ));
+        // Each frame advances time by exactly two 20ms fixed ticks.
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            40,
+        )));
+        app.world_mut().spawn((
+            ParticleType::new("Sand"),
+            DensityBlueprint(Density(1250)),
+            VelocityBlueprint(Velocity::new(1, 1)),
+            MovableSolidBlueprint(MovableSolid::new()),
+        ));
+        app.update();
+        spawn(&mut app, "Sand", [IVec2::new(0, 400)]);
+        app.update();
+
+        let tick = |app: &App| app.world().resource::<SimulationTick>().get();
+        let (start, start_tick) = (positions(&mut app, "Sand")[0], tick(&app));
+        step(&mut app, 10);
+
+        // The sand falls one cell per tick, however the ticks line up with frames.
+        assert_eq!(tick(&app) - start_tick, 20);
+        assert_eq!(positions(&mut app, "Sand"), vec![start - IVec2::new(0, 20)]);
+    }
+
+    #[test]
+    fn diagonal_mover_slides_down_staircase() {
+        let mut app = sim();
+        app.world_mut().spawn((
+            ParticleType::new("Slider"),
+            DensityBlueprint(Density(1000)),
+            VelocityBlueprint(Velocity::new(1, 1)),
+            DiagonalMoverBlueprint(DiagonalMover::new()),
+        ));
+        app.update();
+
+        // Each step blocks the cell below and below-left of the slider, so the only way down is
+        // one cell to the right at a time. The bottom step is flat ground.
+        let steps = 6;
+        spawn(
+            &mut app,
+            "Wall",
+            (0..steps).flat_map(|k| [IVec2::new(k - 1, -k), IVec2::new(k, -k)]),
+        );
+        spawn(
+            &mut app,
+            "Wall",
+            (steps - 1..steps + 3).map(|x| IVec2::new(x, -steps)),
+        );
+        spawn(&mut app, "Slider", [IVec2::new(0, 1)]);
+        step(&mut app, 40);
+
+        assert_eq!(
+            positions(&mut app, "Slider"),
+            vec![IVec2::new(steps, 1 - steps)]
+        );
+    }
+
+    #[test]
+    fn full_stack_bias_keeps_surface_layer_in_place() {
+        let mut app = sim();
+        app.world_mut().spawn((
+            ParticleType::new("Syrup"),
+            DensityBlueprint(Density(750)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            LiquidBlueprint(Liquid::new(3).with_stack_bias(1.)),
+        ));
+        app.update();
+
+        spawn(&mut app, "Wall", (-10..=10).map(|x| IVec2::new(x, -1)));
+        let layer: Vec<IVec2> = (-1..=1).map(|x| IVec2::new(x, 0)).collect();
+        spawn(&mut app, "Syrup", layer.clone());
+        step(&mut app, 30);
+
+        assert_eq!(positions(&mut app, "Syrup"), layer);
+    }
+
+    #[test]
+    fn stack_bias_scan_stops_at_the_top_of_the_map() {
+        let mut app = sim();
+        app.insert_resource(ChunkMap::new(64));
+        app.world_mut().spawn((
+            ParticleType::new("Syrup"),
+            DensityBlueprint(Density(750)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            LiquidBlueprint(Liquid::new(3).with_stack_bias(0.5)),
+        ));
+        app.update();
+
+        // A basin as tall as the map, so the top of every column is in the map's top row.
+        let bounds = app.world().resource::<ChunkMap>().world_bounds_i();
+        spawn(
+            &mut app,
+            "Wall",
+            (bounds.min.y..=bounds.max.y).flat_map(|y| [IVec2::new(-3, y), IVec2::new(3, y)]),
+        );
+        let syrup: Vec<IVec2> = (bounds.min.y..=bounds.max.y)
+            .flat_map(|y| (-2..=2).map(move |x| IVec2::new(x, y)))
+            .collect();
+        spawn(&mut app, "Syrup", syrup.clone());
+        step(&mut app, 10);
+
+        assert_eq!(positions(&mut app, "Syrup"), syrup);
+    }
+
+    #[test]
+    fn liquid_spreads_only_with_enough_neighbors() {
+        let mut app = sim();
+        app.world_mut().spawn((
+            ParticleType::new("Mercury"),
+            DensityBlueprint(Density(1350)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            LiquidBlueprint(Liquid::new(0).with_min_neighbors_to_spread(1)),
+        ));
+        app.update();
+
+        spawn(&mut app, "Wall", (-20..=20).map(|x| IVec2::new(x, -1)));
+        // A lone drop still falls, but has no neighbors to push it sideways once it lands.
+        spawn(&mut app, "Mercury", [IVec2::new(-10, 8)]);
+        // A pair of drops each has one neighbor, which is enough to spread.
+        let pair = vec![IVec2::new(10, 0), IVec2::new(11, 0)];
+        spawn(&mut app, "Mercury", pair.clone());
+        step(&mut app, 30);
+
+        let positions = positions(&mut app, "Mercury");
+        assert!(positions.contains(&IVec2::new(-10, 0)));
+        let spread: Vec<IVec2> = positions
+            .into_iter()
+            .filter(|position| position.x > 0)
+            .collect();
+        assert_eq!(spread.len(), 2);
+        assert_ne!(spread, pair);
+    }
+
+    #[test]
+    fn viscous_liquid_spreads_slower_but_as_far() {
+        let mut app = sim();
+        app.insert_resource(GlobalRng::with_seed(7));
+        for (name, viscosity) in [("Water", 0), ("Honey", 220)] {
+            app.world_mut().spawn((
+                ParticleType::new(name),
+                DensityBlueprint(Density(1000)),
+                VelocityBlueprint(Velocity::new(1, 3)),
+                LiquidBlueprint(Liquid::new(3)),
+                ViscosityBlueprint(Viscosity(viscosity)),
+            ));
+        }
+        app.update();
+
+        // Each liquid drops as a column into its own basin, ten cells wide and with walls thick
+        // enough that neither can jump out.
+        for (name, left) in [("Water", -50), ("Honey", 50)] {
+            spawn(
+                &mut app,
+                "Wall",
+                (left - 6..left + 16).map(|x| IVec2::new(x, -1)),
+            );
+            spawn(
+                &mut app,
+                "Wall",
+                (0..12).flat_map(|y| {
+                    (left - 6..left)
+                        .chain(left + 10..left + 16)
+                        .map(move |x| IVec2::new(x, y))
+                }),
+            );
+            spawn(&mut app, name, (0..10).map(|y| IVec2::new(left, y)));
+        }
+        let on_floor = |app: &mut App, name: &str| -> usize {
+            positions(app, name)
+                .into_iter()
+                .filter(|position| position.y == 0)
+                .count()
+        };
+
+        step(&mut app, 10);
+        assert!(on_floor(&mut app, "Honey") < on_floor(&mut app, "Water"));
+        step(&mut app, 300);
+        assert_eq!(on_floor(&mut app, "Water"), 10);
+        assert_eq!(on_floor(&mut app, "Honey"), 10);
+    }
+
+    #[test]
+    fn particles_stay_inside_the_map() {
+        let mut app = sim();
+        app.insert_resource(ChunkMap::new(64));
+        app.world_mut().spawn((
+            ParticleType::new("Water"),
+            DensityBlueprint(Density(1000)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            LiquidBlueprint(Liquid::new(5)),
+        ));
+        app.update();
+
+        // A 64 cell map spans -32..=31 horizontally and -31..=32 vertically. The water drops from
+        // the top row into the bottom corners, and spreads up against the side edges.
+        let bounds = app.world().resource::<ChunkMap>().world_bounds_i();
+        spawn(
+            &mut app,
+            "Water",
+            (0..4).flat_map(|x| {
+                [
+                    IVec2::new(bounds.min.x + x, bounds.max.y),
+                    IVec2::new(bounds.max.x - x, bounds.max.y),
+                ]
+            }),
+        );
+        step(&mut app, 120);
+
+        let water = positions(&mut app, "Water");
+        assert_eq!(water.len(), 8);
+        assert!(water.iter().all(|position| bounds.contains(*position)));
+        let map = app.world().resource::<ChunkMap>();
+        assert_eq!(map.iter_particles().count(), 8);
+        assert!(water.iter().all(|position| map.is_occupied(*position)));
+    }
+
+    #[test]
+    fn light_solid_floats_to_the_top_of_the_map() {
+        let mut app = sim();
+        app.insert_resource(ChunkMap::new(64));
+        app.world_mut().spawn((
+            ParticleType::new("Water"),
+            DensityBlueprint(Density(1000)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            LiquidBlueprint(Liquid::new(3)),
+        ));
+        app.world_mut().spawn((
+            ParticleType::new("Wood"),
+            DensityBlueprint(Density(500)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            MovableSolidBlueprint(MovableSolid::new()),
+        ));
+        app.update();
+
+        // A one cell wide shaft running the full height of the map, filled with water and with
+        // the wood at the bottom. The wood keeps looking above itself once it reaches the top row.
+        let bounds = app.world().resource::<ChunkMap>().world_bounds_i();
+        spawn(
+            &mut app,
+            "Wall",
+            (bounds.min.y..=bounds.max.y).flat_map(|y| [IVec2::new(-1, y), IVec2::new(1, y)]),
+        );
+        spawn(&mut app, "Wood", [IVec2::new(0, bounds.min.y)]);
+        spawn(
+            &mut app,
+            "Water",
+            (bounds.min.y + 1..=bounds.max.y).map(|y| IVec2::new(0, y)),
+        );
+        step(&mut app, 150);
+
+        assert_eq!(
+            positions(&mut app, "Wood"),
+            vec![IVec2::new(0, bounds.max.y)]
+        );
+        assert_eq!(positions(&mut app, "Water").len(), 63);
+    }
+
+    /// Stacks a one cell wide column of `top` on one of `bottom` in a sealed shaft, and returns
+    /// each liquid's cells after `frames` frames.
+    fn layered_liquids(top: u32, bottom: u32, frames: usize) -> (App, Vec<IVec2>, Vec<IVec2>) {
+        let mut app = sim();
+        for (name, density) in [("Top", top), ("Bottom", bottom)] {
+            app.world_mut().spawn((
+                ParticleType::new(name),
+                DensityBlueprint(Density(density)),
+                VelocityBlueprint(Velocity::new(1, 3)),
+                LiquidBlueprint(Liquid::new(3)),
+            ));
+        }
+        app.update();
+
+        spawn(&mut app, "Wall", (-1..=1).map(|x| IVec2::new(x, -1)));
+        spawn(
+            &mut app,
+            "Wall",
+            (0..=8).flat_map(|y| [IVec2::new(-1, y), IVec2::new(1, y)]),
+        );
+        spawn(&mut app, "Bottom", (0..4).map(|y| IVec2::new(0, y)));
+        spawn(&mut app, "Top", (4..8).map(|y| IVec2::new(0, y)));
+        step(&mut app, frames);
+
+        let (top, bottom) = (positions(&mut app, "Top"), positions(&mut app, "Bottom"));
+        (app, top, bottom)
+    }
+
+    #[test]
+    fn denser_liquid_sinks_through_lighter_liquid_and_stays() {
+        let lower: Vec<IVec2> = (0..4).map(|y| IVec2::new(0, y)).collect();
+        let upper: Vec<IVec2> = (4..8).map(|y| IVec2::new(0, y)).collect();
+        let (mut app, top, bottom) = layered_liquids(1500, 1000, 60);
+        assert_eq!(top, lower);
+        assert_eq!(bottom, upper);
+
+        // Once the layers have swapped, they hold still.
+        step(&mut app, 30);
+        assert_eq!(positions(&mut app, "Top"), lower);
+        assert_eq!(positions(&mut app, "Bottom"), upper);
+    }
+
+    #[test]
+    fn liquids_of_equal_density_never_swap() {
+        let (_, top, bottom) = layered_liquids(1000, 1000, 60);
+        assert_eq!(top, (4..8).map(|y| IVec2::new(0, y)).collect::<Vec<_>>());
+        assert_eq!(bottom, (0..4).map(|y| IVec2::new(0, y)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn passes_through_named_types() {
+        let mut app = sim();
+        app.world_mut().spawn((
+            ParticleType::new("Stone"),
+            DensityBlueprint(Density(1000)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            SolidBlueprint(Solid),
+        ));
+        app.world_mut().spawn((
+            ParticleType::new("Ghost"),
+            DensityBlueprint(Density(1000)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            PassesThroughBlueprint(PassesThrough::new(["Stone".to_string()].into())),
+            SolidBlueprint(Solid),
+        ));
+        app.update();
+
+        // Neither type can displace the other, so the ghost only gets below the stones by
+        // passing through them.
+        spawn(&mut app, "Wall", (-5..=5).map(|x| IVec2::new(x, -1)));
+        spawn(&mut app, "Stone", (0..4).map(|y| IVec2::new(0, y)));
+        spawn(&mut app, "Ghost", [IVec2::new(0, 6)]);
+        step(&mut app, 30);
+
+        assert_eq!(positions(&mut app, "Ghost"), vec![IVec2::ZERO]);
+    }
+
+    #[test]
+    fn gas_drifts_with_the_wind() {
+        let mut app = sim();
+        app.insert_resource(GlobalRng::with_seed(7));
+        app.insert_resource(ForceField::new(Vec2::X, 0.5));
+        app.world_mut().spawn((
+            ParticleType::new("Steam"),
+            DensityBlueprint(Density(200)),
+            VelocityBlueprint(Velocity::new(1, 1)),
+            GasBlueprint(Gas::new(1)),
+        ));
+        app.update();
+
+        // Two sealed corridors, with end walls thick enough that gas can't jump over them. A fan
+        // blows the upper one against the global wind.
+        for floor in [0, 10] {
+            spawn(
+                &mut app,
+                "Wall",
+                (-32..=32).flat_map(|x| [IVec2::new(x, floor - 1), IVec2::new(x, floor + 3)]),
+            );
+            spawn(
+                &mut app,
+                "Wall",
+                (floor..floor + 3)
+                    .flat_map(|y| [-32, -31, -30, 30, 31, 32].map(|x| IVec2::new(x, y))),
+            );
+            spawn(
+                &mut app,
+                "Steam",
+                (floor..floor + 3).flat_map(|y| (-1..=1).map(move |x| IVec2::new(x, y))),
+            );
+        }
+        app.world_mut().spawn(LocalForceField::new(
+            IRect::new(-30, 10, 30, 12),
+            ForceField::new(Vec2::NEG_X, 0.5),
+        ));
+        step(&mut app, 40);
+
+        let steam = positions(&mut app, "Steam");
+        assert_eq!(steam.len(), 18);
+        let drift = |floor: i32| -> i32 {
+            steam
+                .iter()
+                .filter(|position| (floor..floor + 3).contains(&position.y))
+                .map(|position| position.x)
+                .sum()
+        };
+        assert!(drift(0) > 9 * 5, "{steam:?}");
+        assert!(drift(10) < -9 * 5, "{steam:?}");
+    }
+
+    /// Sets up contests for a single empty cell between two equal particles, and returns how
+    /// many of them the left particle won.
+    fn left_wins(policy: MovementConflictPolicy) -> usize {
+        let mut app = sim();
+        app.insert_resource(policy);
+        app.world_mut().spawn((
+            ParticleType::new("Sand"),
+            DensityBlueprint(Density(1250)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            MovableSolidBlueprint(MovableSolid::new()),
+        ));
+        app.update();
+
+        // Each contest is five cells wide: the particles sit on walls either side of an empty
+        // cell, and can only move by sliding into it.
+        let contests: Vec<i32> = (-50..50).map(|k| k * 5).collect();
+        spawn(&mut app, "Wall", (-260..=260).map(|x| IVec2::new(x, -1)));
+        spawn(
+            &mut app,
+            "Wall",
+            contests
+                .iter()
+                .flat_map(|x| [-2, -1, 1, 2].map(|offset| IVec2::new(x + offset, 0))),
+        );
+        // Every left particle is spawned before every right particle, so storage order favors
+        // the left.
+        spawn(
+            &mut app,
+            "Sand",
+            contests.iter().map(|x| IVec2::new(x - 1, 1)),
+        );
+        spawn(
+            &mut app,
+            "Sand",
+            contests.iter().map(|x| IVec2::new(x + 1, 1)),
+        );
+        step(&mut app, 10);
+
+        let sand = positions(&mut app, "Sand");
+        assert_eq!(sand.len(), 2 * contests.len());
+        contests
+            .iter()
+            .filter(|x| {
+                sand.contains(&IVec2::new(**x, 0)) && !sand.contains(&IVec2::new(*x - 1, 1))
+            })
+            .count()
+    }
+
+    #[test]
+    fn random_wins_shares_contested_cells_fairly() {
+        assert_eq!(left_wins(MovementConflictPolicy::FirstWins), 100);
+        let left = left_wins(MovementConflictPolicy::RandomWins);
+        assert!((25..=75).contains(&left), "left won {left} of 100 contests");
+    }
+
+    #[test]
+    fn pressure_equalization_levels_a_u_tube() {
+        let mut app = sim();
+        app.insert_resource(LiquidPressureEqualization {
+            rate: 1.,
+            ..default()
+        });
+        app.world_mut().spawn((
+            ParticleType::new("Water"),
+            DensityBlueprint(Density(1000)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            LiquidBlueprint(Liquid::new(3)),
+        ));
+        app.update();
+
+        // Two one-cell-wide arms at x = 0 and x = 4, joined along the bottom row.
+        let height = 10;
+        spawn(&mut app, "Wall", (-1..=5).map(|x| IVec2::new(x, -1)));
+        spawn(
+            &mut app,
+            "Wall",
+            (0..=height).flat_map(|y| [IVec2::new(-1, y), IVec2::new(5, y)]),
+        );
+        spawn(
+            &mut app,
+            "Wall",
+            (1..=height).flat_map(|y| (1..=3).map(move |x| IVec2::new(x, y))),
+        );
+        // The left arm holds 8 cells of water above the bottom row, the right arm none.
+        spawn(&mut app, "Water", (0..=4).map(|x| IVec2::new(x, 0)));
+        spawn(&mut app, "Water", (1..=8).map(|y| IVec2::new(0, y)));
+        step(&mut app, 30);
+
+        let water = positions(&mut app, "Water");
+        let arm = |x: i32| -> Vec<i32> {
+            water
+                .iter()
+                .filter(|position| position.x == x && position.y > 0)
+                .map(|position| position.y)
+                .collect()
+        };
+        assert_eq!(water.len(), 13);
+        assert_eq!(arm(0), vec![1, 2, 3, 4]);
+        assert_eq!(arm(4), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn pressure_equalization_along_the_map_edges() {
+        let mut app = sim();
+        app.insert_resource(ChunkMap::new(64));
+        app.insert_resource(LiquidPressureEqualization {
+            rate: 1.,
+            ..default()
+        });
+        app.world_mut().spawn((
+            ParticleType::new("Water"),
+            DensityBlueprint(Density(1000)),
+            VelocityBlueprint(Velocity::new(1, 3)),
+            LiquidBlueprint(Liquid::new(3)),
+        ));
+        app.update();
+
+        // The same U-tube as above, but pressed into the bottom-left corner of the map so the
+        // map's edges stand in for its left and bottom walls.
+        let min = app.world().resource::<ChunkMap>().world_bounds_i().min;
+        let height = 10;
+        spawn(
+            &mut app,
+            "Wall",
+            (0..=height).map(|y| min + IVec2::new(5, y)),
+        );
+        spawn(
+            &mut app,
+            "Wall",
+            (1..=height).flat_map(|y| (1..=3).map(move |x| min + IVec2::new(x, y))),
+        );
+        spawn(&mut app, "Water", (0..=4).map(|x| min + IVec2::new(x, 0)));
+        spawn(&mut app, "Water", (1..=8).map(|y| min + IVec2::new(0, y)));
+        step(&mut app, 30);
+
+        let water = positions(&mut app, "Water");
+        let arm = |x: i32| -> Vec<i32> {
+            water
+                .iter()
+                .filter(|position| position.x == min.x + x && position.y > min.y)
+                .map(|position| position.y - min.y)
+                .collect()
+        };
+        assert_eq!(water.len(), 13);
+        assert_eq!(arm(0), vec![1, 2, 3, 4]);
+        assert_eq!(arm(4), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn parallel_movement_is_deterministic() {
+        let run = |chunks_per_task: usize| {
+            let mut app = sim();
+            app.insert_resource(GlobalRng::with_seed(7))
+                .insert_resource(ParallelMovement { chunks_per_task });
+            app.world_mut().spawn((
+                ParticleType::new("Sand"),
+                DensityBlueprint(Density(1250)),
+                VelocityBlueprint(Velocity::new(1, 3)),
+                MovableSolidBlueprint(MovableSolid::new()),
+            ));
+            app.world_mut().spawn((
+                ParticleType::new("Water"),
+                DensityBlueprint(Density(750)),
+                VelocityBlueprint(Velocity::new(1, 3)),
+                LiquidBlueprint(Liquid::new(3)),
+            ));
+            app.update();
+
+            // Both piles straddle chunk edges (x = 0 and y = 0), so some particles are moved by
+            // their chunk's task and the rest after every phase.
+            spawn(&mut app, "Wall", (-48..48).map(|x| IVec2::new(x, -40)));
+            spawn(
+                &mut app,
+                "Sand",
+                (-20..20).flat_map(|x| (10..20).map(move |y| IVec2::new(x, y))),
+            );
+            spawn(
+                &mut app,
+                "Water",
+                (-30..30).flat_map(|x| (-10..5).map(move |y| IVec2::new(x, y))),
+            );
+            step(&mut app, 60);
+            (positions(&mut app, "Sand"), positions(&mut app, "Water"))
+        };
+
+        let (sand, water) = run(1);
+        assert_eq!(sand.len(), 400);
+        assert_eq!(water.len(), 900);
+        assert_eq!(run(usize::MAX), (sand.clone(), water.clone()));
+        assert_eq!(run(3), (sand, water));
+    }
+}
@@ -0,0 +1,92 @@
+//! Steps a simulation with liquid cohesion, heat conduction and evaporation all scanning the same
+//! neighborhoods, with and without the `NeighborCache`.
+
+use bevy::prelude::*;
+use bevy_turborand::RngPlugin;
+use bfs_color::ResetParticleColorEvent;
+use bfs_core::{FallingSandCorePlugin, NeighborCache, Particle, ParticleType};
+use bfs_movement::{
+    Density, DensityBlueprint, FallingSandMovementPlugin, Liquid, LiquidBlueprint, Velocity,
+    VelocityBlueprint, Wall, WallBlueprint,
+};
+use bfs_reactions::{
+    Evaporates, EvaporatesBlueprint, FallingSandReactionsPlugin, Temperature, TemperatureBlueprint,
+    ThermalConductivity, ThermalConductivityBlueprint,
+};
+use bfs_spatial::FallingSandSpatialPlugin;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Half the width of the basin the water sits in.
+const HALF_WIDTH: i32 = 48;
+/// How deep the water is.
+const DEPTH: i32 = 40;
+
+fn simulation(cached: bool) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        RngPlugin::default().with_rng_seed(0),
+        FallingSandCorePlugin::default(),
+        FallingSandMovementPlugin,
+        FallingSandSpatialPlugin,
+        FallingSandReactionsPlugin,
+    ))
+    .add_event::<ResetParticleColorEvent>();
+    if cached {
+        app.init_resource::<NeighborCache>();
+    }
+
+    // A hot basin of water. Nothing is hot enough to burn, so the water keeps checking its
+    // neighbors for heat every tick without ever evaporating.
+    app.world_mut().spawn((
+        ParticleType::new("Stone"),
+        WallBlueprint(Wall::new()),
+        TemperatureBlueprint(Temperature(90.)),
+        ThermalConductivityBlueprint(ThermalConductivity(0.5)),
+    ));
+    app.world_mut().spawn((
+        ParticleType::new("Water"),
+        DensityBlueprint(Density(1000)),
+        VelocityBlueprint(Velocity::new(1, 3)),
+        LiquidBlueprint(Liquid::new(3).with_min_neighbors_to_spread(2)),
+        TemperatureBlueprint(Temperature(20.)),
+        ThermalConductivityBlueprint(ThermalConductivity(0.2)),
+        EvaporatesBlueprint(Evaporates::new(1., Particle::new("Steam"), true)),
+    ));
+    app.update();
+
+    let stone = (-HALF_WIDTH - 1..=HALF_WIDTH + 1)
+        .map(|x| IVec2::new(x, -1))
+        .chain((0..=DEPTH).flat_map(|y| {
+            [
+                IVec2::new(-HALF_WIDTH - 1, y),
+                IVec2::new(HALF_WIDTH + 1, y),
+            ]
+        }));
+    let water = (0..DEPTH).flat_map(|y| (-HALF_WIDTH..=HALF_WIDTH).map(move |x| IVec2::new(x, y)));
+    for (name, cells) in [
+        ("Stone", stone.collect::<Vec<_>>()),
+        ("Water", water.collect()),
+    ] {
+        for cell in cells {
+            app.world_mut().spawn((
+                Particle::new(name),
+                Transform::from_xyz(cell.x as f32, cell.y as f32, 0.),
+            ));
+        }
+    }
+    app.update();
+    app
+}
+
+fn simulation_step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("simulation_step");
+    for (name, cached) in [("chunk_map", false), ("neighbor_cache", true)] {
+        let mut app = simulation(cached);
+        group.bench_function(name, |b| b.iter(|| app.update()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, simulation_step);
+criterion_main!(benches);
@@ -1,7 +1,10 @@
 use bevy::prelude::*;
 use bevy::utils::Duration;
+use bevy_turborand::{GlobalRng, RngComponent};
 use bfs_color::*;
-use bfs_core::{Coordinates, Particle, ParticleRegistrationEvent, ParticleType};
+use bfs_core::{
+    handle_new_particles, ChunkMap, Coordinates, Particle, ParticleRegistrationEvent, ParticleType,
+};
 
 use crate::ReactionRng;
 
@@ -9,26 +12,79 @@ pub struct ParticleDefinitionsPlugin;
 
 impl Plugin for ParticleDefinitionsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, handle_particle_registration);
+        app.add_systems(
+            PreUpdate,
+            handle_particle_registration.after(handle_new_particles),
+        );
         app.register_type::<Fire>()
             .register_type::<Burns>()
             .register_type::<Burning>()
-            .register_type::<Reacting>();
+            .register_type::<Reacting>()
+            .register_type::<Temperature>()
+            .register_type::<TemperatureBlueprint>()
+            .register_type::<ThermalConductivity>()
+            .register_type::<ThermalConductivityBlueprint>()
+            .register_type::<ReactionCooldown>()
+            .register_type::<ReactionCooldownBlueprint>()
+            .register_type::<Propagates>()
+            .register_type::<PropagatesBlueprint>()
+            .register_type::<Evaporates>()
+            .register_type::<EvaporatesBlueprint>()
+            .register_type::<Corrodes>()
+            .register_type::<CorrodesBlueprint>();
     }
 }
 
-#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect)]
+#[derive(Clone, PartialEq, Debug, Default, Component, Reflect)]
 #[reflect(Component)]
 pub struct Fire {
     pub burn_radius: f32,
     pub chance_to_spread: f64,
-    pub destroys_on_spread: bool,
+    pub on_spread: SpreadEffect,
 }
 
-#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect)]
+impl Fire {
+    /// Whether the fire particle is removed after igniting a neighbor.
+    #[deprecated(since = "0.4.0", note = "use `on_spread` instead")]
+    pub fn destroys_on_spread(&self) -> bool {
+        self.on_spread == SpreadEffect::Destroy
+    }
+
+    /// Removes the fire particle after it ignites a neighbor (`true`), or leaves it as is
+    /// (`false`).
+    #[deprecated(since = "0.4.0", note = "set `on_spread` instead")]
+    pub fn set_destroys_on_spread(&mut self, destroys_on_spread: bool) {
+        self.on_spread = SpreadEffect::from(destroys_on_spread);
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Component, Reflect)]
 #[reflect(Component)]
 pub struct FireBlueprint(pub Fire);
 
+/// What happens to a fire particle after it ignites a neighbor.
+#[derive(Clone, PartialEq, Debug, Default, Reflect)]
+pub enum SpreadEffect {
+    /// The fire particle is left as is.
+    #[default]
+    Keep,
+    /// The fire particle is removed.
+    Destroy,
+    /// The fire particle becomes another particle type, e.g. to leave scorch marks behind.
+    ConvertTo(Particle),
+}
+
+/// Maps the former `destroys_on_spread` flag to `Destroy` (true) or `Keep` (false).
+impl From<bool> for SpreadEffect {
+    fn from(destroys_on_spread: bool) -> SpreadEffect {
+        if destroys_on_spread {
+            SpreadEffect::Destroy
+        } else {
+            SpreadEffect::Keep
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Default, Component, Reflect)]
 #[reflect(Component)]
 pub struct Burns {
@@ -38,6 +94,14 @@ pub struct Burns {
     pub reaction: Option<Reacting>,
     pub color: Option<ParticleColor>,
     pub spreads: Option<Fire>,
+    /// Heat added to this particle's and its neighbors' `Temperature` each burn tick. Negative
+    /// values cool the surroundings instead.
+    pub heat_output: f32,
+    /// Particles continuously emitted while burning.
+    pub emits: Option<EmitsWhileBurning>,
+    /// The `Temperature` at which this particle catches fire on its own, without a `Fire`
+    /// particle nearby.
+    pub ignition_temperature: Option<f32>,
 }
 
 impl Burns {
@@ -57,9 +121,51 @@ impl Burns {
             reaction,
             color,
             spreads,
+            heat_output: 0.,
+            emits: None,
+            ignition_temperature: None,
         }
     }
 
+    /// How particles whose type doesn't burn behave when set alight while
+    /// [`GlobalModifier::all_flammable`](bfs_core::GlobalModifier::all_flammable) is active: they
+    /// burn for a second, spreading fire to their neighbors, and may be destroyed while burning.
+    pub fn fallback() -> Burns {
+        Burns::new(
+            Duration::from_secs(1),
+            Duration::from_millis(100),
+            Some(0.05),
+            None,
+            None,
+            Some(Fire {
+                burn_radius: 1.5,
+                chance_to_spread: 0.5,
+                on_spread: SpreadEffect::Keep,
+            }),
+        )
+    }
+
+    pub fn with_emits(mut self, emits: EmitsWhileBurning) -> Burns {
+        self.emits = Some(emits);
+        self
+    }
+
+    pub fn with_heat_output(mut self, heat_output: f32) -> Burns {
+        self.heat_output = heat_output;
+        self
+    }
+
+    pub fn with_ignition_temperature(mut self, ignition_temperature: f32) -> Burns {
+        self.ignition_temperature = Some(ignition_temperature);
+        self
+    }
+
+    /// Whether a particle at `temperature` has reached its ignition temperature.
+    pub fn ignites_at(&self, temperature: Temperature) -> bool {
+        self.ignition_temperature
+            .is_some_and(|ignition_temperature| temperature.0 >= ignition_temperature)
+    }
+
     pub fn to_burning(&self) -> Burning {
         Burning::new(self.duration, self.tick_rate)
     }
@@ -69,6 +175,104 @@ impl Burns {
 #[reflect(Component)]
 pub struct BurnsBlueprint(pub Burns);
 
+/// Where a reaction places the particles it produces.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Reflect)]
+pub enum ProductPlacement {
+    /// The cell directly above the reacting particle.
+    #[default]
+    Above,
+    /// The cell directly below the reacting particle.
+    Below,
+    /// The empty cell closest to the reacting particle, within the given radius.
+    NearestEmpty(i32),
+}
+
+impl ProductPlacement {
+    /// Returns the cell a product should be placed in, or `None` if there's no room for it.
+    pub fn resolve(&self, map: &ChunkMap, coordinates: IVec2) -> Option<IVec2> {
+        let cell = match self {
+            ProductPlacement::Above => coordinates + IVec2::Y,
+            ProductPlacement::Below => coordinates + IVec2::NEG_Y,
+            ProductPlacement::NearestEmpty(radius) => {
+                return map.nearest_empty(coordinates, *radius);
+            }
+        };
+        map.chunk(&cell)
+            .is_some_and(|chunk| chunk.region().contains(cell) && !chunk.is_occupied(cell))
+            .then_some(cell)
+    }
+}
+
+/// Continuously emits `particle` while a particle is burning, e.g. for steady smoke plumes.
+///
+/// `rate` is the number of particles emitted per simulation tick. Fractional rates emit on a
+/// matching share of ticks. Emission is skipped when there's no room for the product.
+#[derive(Clone, PartialEq, Debug, Reflect)]
+pub struct EmitsWhileBurning {
+    pub particle: Particle,
+    pub rate: f32,
+    pub at: ProductPlacement,
+}
+
+impl EmitsWhileBurning {
+    pub fn new(particle: Particle, rate: f32, at: ProductPlacement) -> EmitsWhileBurning {
+        EmitsWhileBurning { particle, rate, at }
+    }
+
+    pub fn emit(
+        &self,
+        commands: &mut Commands,
+        rng: &mut ReactionRng,
+        map: &ChunkMap,
+        coordinates: &Coordinates,
+    ) {
+        let rate = self.rate.max(0.);
+        let mut count = rate.trunc() as usize;
+        if rng.chance(f64::from(rate.fract())) {
+            count += 1;
+        }
+        let mut placed: Vec<IVec2> = Vec::with_capacity(count);
+        for _ in 0..count {
+            let Some(cell) = self.at.resolve(map, coordinates.0) else {
+                return;
+            };
+            // Products aren't in the map until next frame, so don't stack them in one cell.
+            if placed.contains(&cell) {
+                return;
+            }
+            placed.push(cell);
+            commands.spawn((
+                self.particle.clone(),
+                Transform::from_xyz(cell.x as f32, cell.y as f32, 0.),
+            ));
+        }
+    }
+}
+
+/// The heat held by a particle. Only particles with this component take part in heat exchange.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Temperature(pub f32);
+
+/// The initial `Temperature` of a particle type. A particle that changes into this type keeps
+/// the temperature it already had.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct TemperatureBlueprint(pub Temperature);
+
+/// How readily a particle's `Temperature` evens out with its orthogonal neighbors.
+///
+/// Each tick, the particle moves this fraction of the way towards the average temperature of its
+/// neighbors, so `0.` insulates completely and `1.` adopts the average immediately. Particles
+/// without this component keep their temperature, though conductive neighbors still draw from it.
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct ThermalConductivity(pub f32);
+
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct ThermalConductivityBlueprint(pub ThermalConductivity);
+
 #[derive(Clone, Eq, PartialEq, Debug, Default, Component, Reflect)]
 pub struct Burning {
     pub timer: Timer,
@@ -93,6 +297,22 @@ impl Burning {
         self.timer.reset();
         self.tick_timer.reset();
     }
+
+    /// How long the particle has been burning.
+    pub fn elapsed(&self) -> Duration {
+        self.timer.elapsed()
+    }
+
+    /// How far along the burn is, from `0.` at ignition to `1.` at burnout.
+    ///
+    /// Useful for ramping effects over the course of a burn, e.g. emitting the most smoke around
+    /// `0.5`.
+    pub fn progress(&self) -> f32 {
+        if self.timer.finished() {
+            return 1.;
+        }
+        self.timer.fraction()
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Default, Component, Reflect)]
@@ -113,18 +333,22 @@ impl Reacting {
         }
     }
 
+    /// Rolls the chance to produce and spawns the product above `coordinates` on success. Returns
+    /// whether anything was produced.
     pub fn produce(
         &self,
         commands: &mut Commands,
         rng: &mut ReactionRng,
         coordinates: &Coordinates,
-    ) {
-        if self.chance(rng) {
-            commands.spawn((
-                self.produces.clone(),
-                Transform::from_xyz(coordinates.0.x as f32, coordinates.0.y as f32 + 1., 0.),
-            ));
+    ) -> bool {
+        if !self.chance(rng) {
+            return false;
         }
+        commands.spawn((
+            self.produces.clone(),
+            Transform::from_xyz(coordinates.0.x as f32, coordinates.0.y as f32 + 1., 0.),
+        ));
+        true
     }
 
     pub fn chance(&self, rng: &mut ReactionRng) -> bool {
@@ -132,23 +356,207 @@ impl Reacting {
     }
 }
 
+/// Stops a particle from producing again for `ticks` simulation ticks after each successful
+/// reaction, so reactions come in bursts rather than every tick.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct ReactionCooldown {
+    pub ticks: u32,
+    pub remaining: u32,
+}
+
+impl ReactionCooldown {
+    pub fn new(ticks: u32) -> ReactionCooldown {
+        ReactionCooldown {
+            ticks,
+            remaining: 0,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.remaining == 0
+    }
+
+    pub fn start(&mut self) {
+        self.remaining = self.ticks;
+    }
+
+    pub fn tick(&mut self) {
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct ReactionCooldownBlueprint(pub ReactionCooldown);
+
+/// Passes a state change along a chain of particles at a fixed pace, like a lit fuse or a row of
+/// dominoes.
+///
+/// `speed_ticks` simulation ticks after a particle with this component is registered, every
+/// neighboring particle of type `along` is converted into `triggers`. If `triggers` propagates
+/// too, the change travels down the chain one cell every `speed_ticks` ticks, lighting every
+/// branch where the chain forks. Each particle propagates once.
+#[derive(Clone, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Propagates {
+    pub along: String,
+    pub speed_ticks: u32,
+    pub triggers: Particle,
+    pub remaining: u32,
+}
+
+impl Propagates {
+    pub fn new(along: &str, speed_ticks: u32, triggers: Particle) -> Propagates {
+        Propagates {
+            along: along.to_string(),
+            speed_ticks,
+            triggers,
+            remaining: speed_ticks,
+        }
+    }
+
+    /// How far the particle is towards propagating, from `0.` when registered to `1.` when it
+    /// converts its neighbors.
+    pub fn progress(&self) -> f32 {
+        if self.speed_ticks == 0 {
+            return 1.;
+        }
+        1. - self.remaining.min(self.speed_ticks) as f32 / self.speed_ticks as f32
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct PropagatesBlueprint(pub Propagates);
+
+/// Gives a particle, typically a liquid, a chance each tick to turn into `produces` in place,
+/// e.g. water boiling off into steam.
+///
+/// With `requires_heat`, the particle only evaporates while a burning or fire particle is next to
+/// it.
+#[derive(Clone, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Evaporates {
+    pub chance_per_tick: f64,
+    pub produces: Particle,
+    pub requires_heat: bool,
+}
+
+impl Evaporates {
+    pub fn new(chance_per_tick: f64, produces: Particle, requires_heat: bool) -> Evaporates {
+        Evaporates {
+            chance_per_tick,
+            produces,
+            requires_heat,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct EvaporatesBlueprint(pub Evaporates);
+
+/// The kinds of particles a [`Corrodes`] particle can dissolve.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, Reflect)]
+pub struct CorrosionMask {
+    pub walls: bool,
+    pub solids: bool,
+    pub movable_solids: bool,
+}
+
+impl CorrosionMask {
+    pub const ALL: CorrosionMask = CorrosionMask {
+        walls: true,
+        solids: true,
+        movable_solids: true,
+    };
+
+    /// Whether a particle that is a wall, solid or movable solid as given is dissolved.
+    pub fn matches(&self, wall: bool, solid: bool, movable_solid: bool) -> bool {
+        self.walls && wall || self.solids && solid || self.movable_solids && movable_solid
+    }
+}
+
+/// Gives a particle, such as acid, a chance each tick to dissolve one of its orthogonal
+/// neighbors.
+///
+/// A random neighbor is picked, and removed if it matches `targets`. After dissolving a neighbor,
+/// the particle is itself used up with a chance of `consumes_self_chance`.
+#[derive(Clone, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct Corrodes {
+    pub chance_per_tick: f64,
+    pub consumes_self_chance: f64,
+    pub targets: CorrosionMask,
+}
+
+impl Corrodes {
+    pub fn new(
+        chance_per_tick: f64,
+        consumes_self_chance: f64,
+        targets: CorrosionMask,
+    ) -> Corrodes {
+        Corrodes {
+            chance_per_tick,
+            consumes_self_chance,
+            targets,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct CorrodesBlueprint(pub Corrodes);
+
+/// Removes any in-flight reaction bookkeeping from a particle, such as an active burn and the fire
+/// it spreads while burning.
+///
+/// This runs automatically whenever a particle is (re)registered, e.g. after its type changes, so
+/// converted particles never keep a stale burn from their previous type. Components defined by
+/// the particle's type are reinserted by the registration that follows.
+pub fn clear_reaction_state(commands: &mut Commands, entity: Entity) {
+    commands.entity(entity).remove::<(Burning, Fire)>();
+}
+
 fn handle_particle_components(
     commands: &mut Commands,
+    rng: &mut ResMut<GlobalRng>,
     parent_query: &Query<
         (
             Option<&FireBlueprint>,
             Option<&BurnsBlueprint>,
             Option<&BurningBlueprint>,
+            Option<&TemperatureBlueprint>,
+            Option<&ThermalConductivityBlueprint>,
+            Option<&ReactionCooldownBlueprint>,
+            Option<&PropagatesBlueprint>,
+            Option<&EvaporatesBlueprint>,
+            Option<&CorrodesBlueprint>,
         ),
         With<ParticleType>,
     >,
-    particle_query: &Query<&Parent, With<Particle>>,
+    particle_query: &Query<(&Parent, Option<&ReactionCooldown>), With<Particle>>,
     entities: &Vec<Entity>,
 ) {
     entities.iter().for_each(|entity| {
-        if let Ok(parent) = particle_query.get(*entity) {
-            if let Ok((fire, burns, burning)) = parent_query.get(parent.get()) {
-                commands.entity(*entity).insert(ReactionRng::default());
+        if let Ok((parent, cooldown)) = particle_query.get(*entity) {
+            if let Ok((
+                fire,
+                burns,
+                burning,
+                temperature,
+                conductivity,
+                cooldown_blueprint,
+                propagates,
+                evaporates,
+                corrodes,
+            )) = parent_query.get(parent.get())
+            {
+                clear_reaction_state(commands, *entity);
+                commands
+                    .entity(*entity)
+                    .insert(ReactionRng(RngComponent::from(&mut **rng)));
                 if let Some(fire) = fire {
                     commands.entity(*entity).insert(fire.0.clone());
                 } else {
@@ -164,6 +572,45 @@ fn handle_particle_components(
                 } else {
                     commands.entity(*entity).remove::<Burning>();
                 }
+                if let Some(temperature) = temperature {
+                    commands.entity(*entity).insert_if_new(temperature.0);
+                } else {
+                    commands.entity(*entity).remove::<Temperature>();
+                }
+                if let Some(conductivity) = conductivity {
+                    commands.entity(*entity).insert(conductivity.0);
+                } else {
+                    commands.entity(*entity).remove::<ThermalConductivity>();
+                }
+                if let Some(propagates) = propagates {
+                    commands.entity(*entity).insert(propagates.0.clone());
+                } else {
+                    commands.entity(*entity).remove::<Propagates>();
+                }
+                if let Some(evaporates) = evaporates {
+                    commands.entity(*entity).insert(evaporates.0.clone());
+                } else {
+                    commands.entity(*entity).remove::<Evaporates>();
+                }
+                if let Some(corrodes) = corrodes {
+                    commands.entity(*entity).insert(corrodes.0.clone());
+                } else {
+                    commands.entity(*entity).remove::<Corrodes>();
+                }
+                // A running cooldown carries over to the new type only if that type also reacts.
+                let reacts = burns.is_some_and(|burns| burns.0.reaction.is_some());
+                match cooldown_blueprint {
+                    Some(cooldown_blueprint) if reacts => {
+                        let mut new_cooldown = cooldown_blueprint.0;
+                        if let Some(cooldown) = cooldown {
+                            new_cooldown.remaining = cooldown.remaining.min(new_cooldown.ticks);
+                        }
+                        commands.entity(*entity).insert(new_cooldown);
+                    }
+                    _ => {
+                        commands.entity(*entity).remove::<ReactionCooldown>();
+                    }
+                }
             }
         }
     });
@@ -171,18 +618,31 @@ fn handle_particle_components(
 
 fn handle_particle_registration(
     mut commands: Commands,
+    mut rng: ResMut<GlobalRng>,
     parent_query: Query<
         (
             Option<&FireBlueprint>,
             Option<&BurnsBlueprint>,
             Option<&BurningBlueprint>,
+            Option<&TemperatureBlueprint>,
+            Option<&ThermalConductivityBlueprint>,
+            Option<&ReactionCooldownBlueprint>,
+            Option<&PropagatesBlueprint>,
+            Option<&EvaporatesBlueprint>,
+            Option<&CorrodesBlueprint>,
         ),
         With<ParticleType>,
     >,
-    particle_query: Query<&Parent, With<Particle>>,
+    particle_query: Query<(&Parent, Option<&ReactionCooldown>), With<Particle>>,
     mut ev_particle_registered: EventReader<ParticleRegistrationEvent>,
 ) {
     ev_particle_registered.read().for_each(|ev| {
-        handle_particle_components(&mut commands, &parent_query, &particle_query, &ev.entities);
+        handle_particle_components(
+            &mut commands,
+            &mut rng,
+            &parent_query,
+            &particle_query,
+            &ev.entities,
+        );
     });
 }
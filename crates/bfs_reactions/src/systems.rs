@@ -2,18 +2,45 @@ use super::ReactionRng;
 use bevy::prelude::*;
 use bevy_spatial::SpatialAccess;
 use bfs_color::*;
-use bfs_core::{Coordinates, Particle, ParticleSimulationSet, RemoveParticleEvent};
+use bfs_core::{
+    cached_neighbors, ChunkMap, Coordinates, Frozen, GlobalModifiers, MutateParticleEvent,
+    NeighborCache, Particle, ParticleSimulation, ParticleSimulationSet, ParticleTypeId,
+    ParticleTypeMap, RemoveParticleEvent, NEIGHBOR_OFFSETS,
+};
+use bfs_movement::{MovableSolid, Solid, Wall};
 use bfs_spatial::ParticleTree;
 
-use crate::{Burning, Burns, Fire};
+use crate::{
+    Burning, Burns, Corrodes, Evaporates, Fire, Propagates, ReactionCooldown, SpreadEffect,
+    Temperature, ThermalConductivity,
+};
 
 pub struct SystemsPlugin;
 
 impl Plugin for SystemsPlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<ReactionEvent>().add_systems(
+            ParticleSimulation,
+            (
+                handle_fire,
+                handle_burning,
+                tick_reaction_cooldowns.before(handle_burning),
+                handle_propagation,
+                handle_evaporation,
+                handle_corrosion,
+                handle_heat_conduction,
+                handle_ignition.after(handle_heat_conduction),
+            )
+                .in_set(ParticleSimulationSet)
+                .in_set(ParticleReactionSet)
+                .run_if(reactions_active),
+        );
         app.add_systems(
-            Update,
-            (handle_fire, handle_burning).in_set(ParticleSimulationSet),
+            ParticleSimulation,
+            color_temperature_gradients
+                .after(handle_heat_conduction)
+                .in_set(ParticleSimulationSet)
+                .in_set(ParticleColorSet),
         );
     }
 }
@@ -22,23 +49,63 @@ pub struct BurningPlugin;
 
 impl Plugin for BurningPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (handle_fire, handle_burning).in_set(ParticleSimulationSet),
+        app.add_event::<ReactionEvent>().add_systems(
+            ParticleSimulation,
+            (handle_fire, handle_burning)
+                .in_set(ParticleSimulationSet)
+                .in_set(ParticleReactionSet),
         );
     }
 }
 
+/// The systems that run reactions, such as burning, corrosion and heat transfer, each tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParticleReactionSet;
+
+/// A reaction that changed or removed a particle.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Reaction {
+    /// A burning particle burned up.
+    Burn,
+    /// A particle was converted along a [`Propagates`] chain.
+    Propagate,
+    /// A particle was eaten away by a [`Corrodes`] neighbor.
+    Corrode,
+    /// A particle [`Evaporates`] into another type.
+    Evaporate,
+}
+
+/// Sent whenever a [`Reaction`] happens to the particle at `coordinates`.
+#[derive(Event, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ReactionEvent {
+    pub coordinates: IVec2,
+    pub reaction: Reaction,
+}
+
+/// Whether reactions should run this tick, i.e. no [`GlobalModifiers`] freeze them.
+pub fn reactions_active(modifiers: Option<Res<GlobalModifiers>>) -> bool {
+    modifiers.is_none_or(|modifiers| !modifiers.reactions_frozen())
+}
+
 pub fn handle_fire(
     mut commands: Commands,
-    mut fire_query: Query<(&Fire, &Coordinates, &mut ReactionRng)>,
-    burns_query: Query<(Entity, &Burns), (With<Particle>, Without<Burning>)>,
+    mut fire_query: Query<(Entity, &Fire, &Coordinates, &mut ReactionRng), Without<Frozen>>,
+    burns_query: Query<
+        (Entity, Option<&Burns>, Has<Fire>),
+        (With<Particle>, Without<Burning>, Without<Frozen>),
+    >,
     particle_tree: Res<ParticleTree>,
+    modifiers: Option<Res<GlobalModifiers>>,
+    mut ev_mutate_particle: EventWriter<MutateParticleEvent>,
 ) {
+    // Particles that don't burn on their own catch fire too while everything is flammable.
+    let fallback_burns = modifiers
+        .is_some_and(|modifiers| modifiers.all_flammable())
+        .then(Burns::fallback);
     fire_query
         .iter_mut()
-        .for_each(|(fire, coordinates, mut rng)| {
-            let mut destroy_fire: bool = false;
+        .for_each(|(fire_entity, fire, coordinates, mut rng)| {
+            let mut spread: bool = false;
             if !rng.chance(fire.chance_to_spread) {
                 return;
             }
@@ -46,51 +113,186 @@ pub fn handle_fire(
                 .within_distance(coordinates.0.as_vec2(), fire.burn_radius)
                 .iter()
                 .for_each(|(_, entity)| {
-                    if let Ok((entity, burns)) = burns_query.get(entity.unwrap()) {
-                        commands.entity(entity).insert(burns.to_burning());
-                        if let Some(colors) = &burns.color {
-                            commands.entity(entity).insert(colors.clone());
-                            commands.entity(entity).insert(FlowsColor::new(0.75));
+                    let Ok((entity, burns, is_fire)) = burns_query.get(entity.unwrap()) else {
+                        return;
+                    };
+                    let burns = match (burns, &fallback_burns) {
+                        (Some(burns), _) => burns,
+                        (None, Some(fallback_burns)) if !is_fire => {
+                            commands.entity(entity).insert(fallback_burns.clone());
+                            fallback_burns
                         }
-                        if let Some(fire) = &burns.spreads {
-                            commands.entity(entity).insert(fire.clone());
-                        }
-                        if fire.destroys_on_spread {
-                            destroy_fire = true;
-                        }
-                    }
-                });
-            if destroy_fire {
-                commands.trigger(RemoveParticleEvent {
-                    coordinates: coordinates.0,
-                    despawn: true,
+                        _ => return,
+                    };
+                    ignite(&mut commands, entity, burns);
+                    spread = true;
                 });
+            if !spread {
+                return;
+            }
+            match &fire.on_spread {
+                SpreadEffect::Keep => {}
+                SpreadEffect::Destroy => {
+                    commands.trigger(RemoveParticleEvent {
+                        coordinates: coordinates.0,
+                        despawn: true,
+                    });
+                }
+                SpreadEffect::ConvertTo(particle) => {
+                    ev_mutate_particle.send(MutateParticleEvent {
+                        entity: fire_entity,
+                        particle: particle.clone(),
+                    });
+                }
             }
         });
 }
 
-pub fn handle_burning(
-    mut commands: Commands,
-    mut burning_query: Query<(
+/// Sets `entity` alight as described by its `burns`.
+fn ignite(commands: &mut Commands, entity: Entity, burns: &Burns) {
+    commands.entity(entity).insert(burns.to_burning());
+    if let Some(colors) = &burns.color {
+        commands.entity(entity).insert(colors.clone());
+        commands.entity(entity).insert(FlowsColor::new(0.75));
+    }
+    if let Some(fire) = &burns.spreads {
+        commands.entity(entity).insert(fire.clone());
+    }
+}
+
+/// Evens out the temperature of particles with [`ThermalConductivity`] with their orthogonal
+/// neighbors.
+///
+/// Every particle reads its neighbors' temperatures from the start of the tick, so the result
+/// doesn't depend on iteration order.
+pub fn handle_heat_conduction(
+    mut temperature_query: Query<(
         Entity,
-        &mut Particle,
-        &mut Burns,
-        &mut Burning,
-        &mut ReactionRng,
+        &mut Temperature,
+        Option<&ThermalConductivity>,
         &Coordinates,
+        Has<Frozen>,
     )>,
+    map: Res<ChunkMap>,
+    mut neighbor_cache: Option<ResMut<NeighborCache>>,
+) {
+    let updates: Vec<(Entity, f32)> = temperature_query
+        .iter()
+        .filter_map(|(entity, temperature, conductivity, coordinates, frozen)| {
+            let conductivity = conductivity.filter(|_| !frozen)?;
+            let (total, count) =
+                cached_neighbors(&map, neighbor_cache.as_deref_mut(), coordinates.0)
+                    .into_iter()
+                    .zip(NEIGHBOR_OFFSETS)
+                    // Heat only flows between orthogonal neighbors.
+                    .filter_map(|(neighbor, offset)| {
+                        neighbor.filter(|_| offset.x == 0 || offset.y == 0)
+                    })
+                    .filter_map(|neighbor| temperature_query.get(neighbor).ok())
+                    .fold((0., 0), |(total, count), (_, neighbor, ..)| {
+                        (total + neighbor.0, count + 1)
+                    });
+            if count == 0 {
+                return None;
+            }
+            let average = total / count as f32;
+            let conductivity = conductivity.0.clamp(0., 1.);
+            Some((
+                entity,
+                temperature.0 + (average - temperature.0) * conductivity,
+            ))
+        })
+        .collect();
+    for (entity, temperature) in updates {
+        if let Ok((_, mut current, ..)) = temperature_query.get_mut(entity) {
+            current.0 = temperature;
+        }
+    }
+}
+
+/// Renders particles whose [`GradientColor`] reads from [`ColorSource::Temperature`].
+pub fn color_temperature_gradients(
+    mut particle_query: Query<
+        (&mut Sprite, &ParticleColor, &GradientColor, &Temperature),
+        (
+            Or<(
+                Changed<Temperature>,
+                Changed<ParticleColor>,
+                Changed<GradientColor>,
+            )>,
+            Without<ColorByVelocity>,
+        ),
+    >,
+) {
+    particle_query.iter_mut().for_each(
+        |(mut sprite, particle_color, gradient_color, temperature)| {
+            let ColorSource::Temperature { min, max } = gradient_color.source else {
+                return;
+            };
+            let t = if max > min {
+                (temperature.0 - min) / (max - min)
+            } else {
+                0.
+            };
+            sprite.color = particle_color.sample(t);
+        },
+    );
+}
+
+/// Sets particles alight once their [`Temperature`] reaches their
+/// [`Burns::ignition_temperature`].
+pub fn handle_ignition(
+    mut commands: Commands,
+    burns_query: Query<(Entity, &Burns, &Temperature), (Without<Burning>, Without<Frozen>)>,
+) {
+    burns_query
+        .iter()
+        .filter(|(_, burns, temperature)| burns.ignites_at(**temperature))
+        .for_each(|(entity, burns, _)| ignite(&mut commands, entity, burns));
+}
+
+pub fn tick_reaction_cooldowns(mut cooldown_query: Query<&mut ReactionCooldown, Without<Frozen>>) {
+    cooldown_query.iter_mut().for_each(|mut cooldown| {
+        if !cooldown.is_ready() {
+            cooldown.tick();
+        }
+    });
+}
+
+pub fn handle_burning(
+    mut commands: Commands,
+    mut burning_query: Query<
+        (
+            Entity,
+            &mut Particle,
+            &mut Burns,
+            &mut Burning,
+            &mut ReactionRng,
+            &Coordinates,
+            Option<&mut ReactionCooldown>,
+        ),
+        Without<Frozen>,
+    >,
+    mut temperature_query: Query<&mut Temperature>,
+    map: Res<ChunkMap>,
+    mut neighbor_cache: Option<ResMut<NeighborCache>>,
     time: Res<Time>,
     mut ev_reset_particle_color: EventWriter<ResetParticleColorEvent>,
+    mut ev_reaction: EventWriter<ReactionEvent>,
 ) {
     let mut entities: Vec<Entity> = vec![];
     burning_query.iter_mut().for_each(
-        |(entity, particle, mut burns, mut burning, mut rng, coordinates)| {
+        |(entity, particle, mut burns, mut burning, mut rng, coordinates, mut cooldown)| {
             if burning.timer.tick(time.delta()).finished() {
                 if burns.chance_destroy_per_tick.is_some() {
                     commands.trigger(RemoveParticleEvent {
                         coordinates: coordinates.0,
                         despawn: true,
-                    })
+                    });
+                    ev_reaction.send(ReactionEvent {
+                        coordinates: coordinates.0,
+                        reaction: Reaction::Burn,
+                    });
                 } else {
                     commands.entity(entity).remove::<Burning>();
                     entities.push(entity);
@@ -98,16 +300,43 @@ pub fn handle_burning(
                 }
                 return;
             }
+            if let Some(emits) = &burns.emits {
+                emits.emit(&mut commands, &mut rng, &map, coordinates);
+            }
             if burning.tick_timer.tick(time.delta()).finished() {
                 if let Some(ref mut reaction) = &mut burns.reaction {
-                    reaction.produce(&mut commands, &mut rng, coordinates);
+                    if cooldown.as_ref().is_none_or(|cooldown| cooldown.is_ready())
+                        && reaction.produce(&mut commands, &mut rng, coordinates)
+                    {
+                        if let Some(ref mut cooldown) = cooldown {
+                            cooldown.start();
+                        }
+                    }
+                }
+                if burns.heat_output != 0. {
+                    std::iter::once(Some(entity))
+                        .chain(cached_neighbors(
+                            &map,
+                            neighbor_cache.as_deref_mut(),
+                            coordinates.0,
+                        ))
+                        .flatten()
+                        .for_each(|entity| {
+                            if let Ok(mut temperature) = temperature_query.get_mut(entity) {
+                                temperature.0 += burns.heat_output;
+                            }
+                        });
                 }
                 if let Some(chance_destroy) = burns.chance_destroy_per_tick {
                     if rng.chance(chance_destroy) {
                         commands.trigger(RemoveParticleEvent {
                             coordinates: coordinates.0,
                             despawn: true,
-                        })
+                        });
+                        ev_reaction.send(ReactionEvent {
+                            coordinates: coordinates.0,
+                            reaction: Reaction::Burn,
+                        });
                     }
                 }
             }
@@ -115,3 +344,349 @@ pub fn handle_burning(
     );
     ev_reset_particle_color.send(ResetParticleColorEvent { entities });
 }
+
+pub fn handle_propagation(
+    mut commands: Commands,
+    mut propagates_query: Query<(Entity, &mut Propagates, &Coordinates), Without<Frozen>>,
+    particle_query: Query<&ParticleTypeId>,
+    map: Res<ChunkMap>,
+    mut neighbor_cache: Option<ResMut<NeighborCache>>,
+    type_map: Res<ParticleTypeMap>,
+    mut ev_mutate_particle: EventWriter<MutateParticleEvent>,
+    mut ev_reaction: EventWriter<ReactionEvent>,
+) {
+    propagates_query
+        .iter_mut()
+        .for_each(|(entity, mut propagates, coordinates)| {
+            if propagates.remaining > 0 {
+                propagates.remaining -= 1;
+                return;
+            }
+            // A chain of a type that was never registered has nothing to propagate along.
+            let along = type_map.id_of(&propagates.along);
+            cached_neighbors(&map, neighbor_cache.as_deref_mut(), coordinates.0)
+                .into_iter()
+                .zip(NEIGHBOR_OFFSETS)
+                .filter_map(|(neighbor, offset)| Some((neighbor?, coordinates.0 + offset)))
+                .filter(|(neighbor, _)| {
+                    particle_query
+                        .get(*neighbor)
+                        .is_ok_and(|type_id| Some(*type_id) == along)
+                })
+                .for_each(|(neighbor, neighbor_coordinates)| {
+                    ev_mutate_particle.send(MutateParticleEvent {
+                        entity: neighbor,
+                        particle: propagates.triggers.clone(),
+                    });
+                    ev_reaction.send(ReactionEvent {
+                        coordinates: neighbor_coordinates,
+                        reaction: Reaction::Propagate,
+                    });
+                });
+            commands.entity(entity).remove::<Propagates>();
+        });
+}
+
+pub fn handle_evaporation(
+    mut evaporates_query: Query<
+        (Entity, &Evaporates, &Coordinates, &mut ReactionRng),
+        Without<Frozen>,
+    >,
+    heat_query: Query<(), Or<(With<Burning>, With<Fire>)>>,
+    map: Res<ChunkMap>,
+    mut neighbor_cache: Option<ResMut<NeighborCache>>,
+    mut ev_mutate_particle: EventWriter<MutateParticleEvent>,
+    mut ev_reaction: EventWriter<ReactionEvent>,
+) {
+    evaporates_query
+        .iter_mut()
+        .for_each(|(entity, evaporates, coordinates, mut rng)| {
+            if !rng.chance(evaporates.chance_per_tick) {
+                return;
+            }
+            if evaporates.requires_heat
+                && !cached_neighbors(&map, neighbor_cache.as_deref_mut(), coordinates.0)
+                    .into_iter()
+                    .flatten()
+                    .any(|neighbor| heat_query.contains(neighbor))
+            {
+                return;
+            }
+            ev_mutate_particle.send(MutateParticleEvent {
+                entity,
+                particle: evaporates.produces.clone(),
+            });
+            ev_reaction.send(ReactionEvent {
+                coordinates: coordinates.0,
+                reaction: Reaction::Evaporate,
+            });
+        });
+}
+
+pub fn handle_corrosion(
+    mut commands: Commands,
+    mut corrodes_query: Query<(&Corrodes, &Coordinates, &mut ReactionRng), Without<Frozen>>,
+    target_query: Query<(Has<Wall>, Has<Solid>, Has<MovableSolid>), Without<Frozen>>,
+    map: Res<ChunkMap>,
+    mut ev_reaction: EventWriter<ReactionEvent>,
+) {
+    const ORTHOGONAL_OFFSETS: [IVec2; 4] = [IVec2::Y, IVec2::X, IVec2::NEG_Y, IVec2::NEG_X];
+    corrodes_query
+        .iter_mut()
+        .for_each(|(corrodes, coordinates, mut rng)| {
+            if !rng.chance(corrodes.chance_per_tick) {
+                return;
+            }
+            let target = coordinates.0 + ORTHOGONAL_OFFSETS[rng.index(0..4)];
+            let Some(target_entity) = map.chunk(&target).and_then(|chunk| chunk.get(&target))
+            else {
+                return;
+            };
+            let Ok((wall, solid, movable_solid)) = target_query.get(*target_entity) else {
+                return;
+            };
+            if !corrodes.targets.matches(wall, solid, movable_solid) {
+                return;
+            }
+            commands.trigger(RemoveParticleEvent {
+                coordinates: target,
+                despawn: true,
+            });
+            ev_reaction.send(ReactionEvent {
+                coordinates: target,
+                reaction: Reaction::Corrode,
+            });
+            if rng.chance(corrodes.consumes_self_chance) {
+                commands.trigger(RemoveParticleEvent {
+                    coordinates: coordinates.0,
+                    despawn: true,
+                });
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CorrosionMask;
+    use bevy::ecs::system::RunSystemOnce;
+    use bfs_core::on_remove_particle;
+    use std::time::Duration;
+
+    fn reaction_world() -> World {
+        let mut world = World::new();
+        world.insert_resource(ChunkMap::new(128));
+        world.init_resource::<Events<MutateParticleEvent>>();
+        world.init_resource::<Events<ReactionEvent>>();
+        world
+    }
+
+    fn propagation_world() -> World {
+        let mut world = reaction_world();
+        let mut type_map = ParticleTypeMap::default();
+        for name in ["Fuse", "Wire", "Spark"] {
+            type_map.insert(name.to_string(), Entity::PLACEHOLDER);
+        }
+        world.insert_resource(type_map);
+        world
+    }
+
+    fn occupy(world: &mut World, coordinates: IVec2, bundle: impl Bundle) -> Entity {
+        let entity = world.spawn((bundle, Coordinates(coordinates))).id();
+        world
+            .resource_mut::<ChunkMap>()
+            .insert_overwrite(coordinates, entity);
+        entity
+    }
+
+    fn place(world: &mut World, name: &str, coordinates: IVec2) -> Entity {
+        let type_id = world.resource::<ParticleTypeMap>().id_of(name).unwrap();
+        occupy(world, coordinates, type_id)
+    }
+
+    #[test]
+    fn propagation_converts_neighbors_of_the_chain_type() {
+        let mut world = propagation_world();
+        let spark = place(&mut world, "Spark", IVec2::ZERO);
+        world
+            .entity_mut(spark)
+            .insert(Propagates::new("Fuse", 0, Particle::new("Spark")));
+        let fuse = place(&mut world, "Fuse", IVec2::X);
+        place(&mut world, "Wire", IVec2::NEG_X);
+        place(&mut world, "Fuse", IVec2::new(2, 0));
+
+        world.run_system_once(handle_propagation).unwrap();
+
+        let events = world.resource::<Events<MutateParticleEvent>>();
+        let converted: Vec<Entity> = events
+            .iter_current_update_events()
+            .map(|ev| ev.entity)
+            .collect();
+        assert_eq!(converted, vec![fuse]);
+        assert!(world.get::<Propagates>(spark).is_none());
+    }
+
+    #[test]
+    fn heat_flows_between_conductive_neighbors() {
+        let mut world = reaction_world();
+        let hot = occupy(
+            &mut world,
+            IVec2::ZERO,
+            (Temperature(100.), ThermalConductivity(0.5)),
+        );
+        let cold = occupy(
+            &mut world,
+            IVec2::X,
+            (Temperature(0.), ThermalConductivity(0.5)),
+        );
+        let insulated = occupy(&mut world, IVec2::NEG_X, Temperature(40.));
+
+        world.run_system_once(handle_heat_conduction).unwrap();
+
+        // Halfway from 100 towards the average of its neighbors, (0 + 40) / 2.
+        assert_eq!(world.get::<Temperature>(hot), Some(&Temperature(60.)));
+        assert_eq!(world.get::<Temperature>(cold), Some(&Temperature(50.)));
+        assert_eq!(world.get::<Temperature>(insulated), Some(&Temperature(40.)));
+    }
+
+    #[test]
+    fn heat_conduction_shares_lookups_through_the_neighbor_cache() {
+        let mut world = reaction_world();
+        world.init_resource::<NeighborCache>();
+        let hot = occupy(
+            &mut world,
+            IVec2::ZERO,
+            (Temperature(100.), ThermalConductivity(0.5)),
+        );
+        let cold = occupy(
+            &mut world,
+            IVec2::X,
+            (Temperature(0.), ThermalConductivity(0.5)),
+        );
+        occupy(&mut world, IVec2::NEG_X, Temperature(40.));
+
+        world.run_system_once(handle_heat_conduction).unwrap();
+
+        assert_eq!(world.get::<Temperature>(hot), Some(&Temperature(60.)));
+        assert_eq!(world.get::<Temperature>(cold), Some(&Temperature(50.)));
+        // Only the conductive particles look up their neighbors.
+        assert_eq!(world.resource::<NeighborCache>().len(), 2);
+    }
+
+    #[test]
+    fn particles_ignite_at_their_ignition_temperature() {
+        let mut world = reaction_world();
+        let burns = Burns::new(
+            Duration::from_secs(1),
+            Duration::from_millis(100),
+            None,
+            None,
+            None,
+            None,
+        );
+        let flammable = burns.clone().with_ignition_temperature(50.);
+        let below = occupy(
+            &mut world,
+            IVec2::ZERO,
+            (flammable.clone(), Temperature(49.)),
+        );
+        let at = occupy(&mut world, IVec2::X, (flammable, Temperature(50.)));
+        let without_ignition = occupy(&mut world, IVec2::Y, (burns, Temperature(1000.)));
+
+        world.run_system_once(handle_ignition).unwrap();
+
+        assert!(world.get::<Burning>(below).is_none());
+        assert!(world.get::<Burning>(at).is_some());
+        assert!(world.get::<Burning>(without_ignition).is_none());
+    }
+
+    #[test]
+    fn evaporation_converts_particles_that_have_the_heat_they_need() {
+        let mut world = reaction_world();
+        let evaporates = |requires_heat| {
+            (
+                Evaporates::new(1., Particle::new("Steam"), requires_heat),
+                ReactionRng::default(),
+            )
+        };
+        let unheated = occupy(&mut world, IVec2::ZERO, evaporates(false));
+        let heated = occupy(&mut world, IVec2::new(10, 0), evaporates(true));
+        occupy(
+            &mut world,
+            IVec2::new(11, 0),
+            Burning::new(Duration::from_secs(1), Duration::from_millis(100)),
+        );
+        occupy(&mut world, IVec2::new(20, 0), evaporates(true));
+
+        world.run_system_once(handle_evaporation).unwrap();
+
+        let events = world.resource::<Events<MutateParticleEvent>>();
+        let mut evaporated: Vec<(Entity, String)> = events
+            .iter_current_update_events()
+            .map(|ev| (ev.entity, ev.particle.name.clone()))
+            .collect();
+        evaporated.sort();
+        let mut expected = vec![
+            (unheated, "Steam".to_string()),
+            (heated, "Steam".to_string()),
+        ];
+        expected.sort();
+        assert_eq!(evaporated, expected);
+    }
+
+    /// Surrounds a particle that corrodes `targets` with `neighbor`s, lets it react once and
+    /// returns how many of the neighbors are left.
+    fn neighbors_left_after_corrosion(
+        targets: CorrosionMask,
+        neighbor: impl Bundle + Clone,
+    ) -> usize {
+        let mut world = reaction_world();
+        world.add_observer(on_remove_particle);
+        occupy(
+            &mut world,
+            IVec2::ZERO,
+            (Corrodes::new(1., 0., targets), ReactionRng::default()),
+        );
+        let sides = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+        for side in sides {
+            occupy(&mut world, side, neighbor.clone());
+        }
+
+        world.run_system_once(handle_corrosion).unwrap();
+
+        let map = world.resource::<ChunkMap>();
+        sides
+            .into_iter()
+            .filter(|side| map.is_occupied(*side))
+            .count()
+    }
+
+    #[test]
+    fn corrosion_dissolves_targets_in_its_mask() {
+        assert_eq!(neighbors_left_after_corrosion(CorrosionMask::ALL, Wall), 3);
+        let movable_solids = CorrosionMask {
+            movable_solids: true,
+            ..default()
+        };
+        assert_eq!(
+            neighbors_left_after_corrosion(movable_solids, MovableSolid),
+            3
+        );
+    }
+
+    #[test]
+    fn corrosion_spares_targets_outside_its_mask() {
+        let all_but_walls = CorrosionMask {
+            walls: false,
+            ..CorrosionMask::ALL
+        };
+        assert_eq!(neighbors_left_after_corrosion(all_but_walls, Wall), 4);
+        let solids = CorrosionMask {
+            solids: true,
+            ..default()
+        };
+        assert_eq!(neighbors_left_after_corrosion(solids, MovableSolid), 4);
+        // Particles that are none of the kinds, such as liquids, are never dissolved.
+        assert_eq!(neighbors_left_after_corrosion(CorrosionMask::ALL, ()), 4);
+    }
+}
@@ -2,18 +2,113 @@ use std::path::PathBuf;
 use bevy::prelude::*;
 use bfs_core::MutateParticleEvent;
 
+use crate::SceneError;
+
 pub struct EventsPlugin;
 
 impl Plugin for EventsPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LoadSceneEvent>()
             .add_event::<SaveSceneEvent>()
+            .add_event::<SaveSceneRegionEvent>()
+            .add_event::<LoadSceneAtEvent>()
+            .add_event::<SceneSavedEvent>()
+            .add_event::<SceneLoadedEvent>()
             .add_event::<MutateParticleEvent>();
     }
 }
 
 #[derive(Event)]
-pub struct SaveSceneEvent(pub PathBuf);
+pub struct SaveSceneEvent {
+    pub path: PathBuf,
+    /// Also write a downscaled preview of the scene next to it as a `.png`. See
+    /// [`SceneThumbnailSettings`](crate::SceneThumbnailSettings).
+    pub thumbnail: bool,
+    /// Gzip-compress the scene file, which shrinks large scenes considerably. Loading detects
+    /// compressed scenes on its own.
+    pub compressed: bool,
+    pub encoding: SceneEncoding,
+}
+
+/// How particles are laid out in a saved scene. Loading handles every encoding.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub enum SceneEncoding {
+    /// One record per particle.
+    #[default]
+    PerParticle,
+    /// Horizontal runs of identical particles are stored as single records. See
+    /// [`ParticleScene::run_length_encoded`](crate::ParticleScene::run_length_encoded).
+    RunLength,
+}
+
+impl SaveSceneEvent {
+    pub fn new(path: impl Into<PathBuf>) -> SaveSceneEvent {
+        SaveSceneEvent {
+            path: path.into(),
+            thumbnail: false,
+            compressed: false,
+            encoding: SceneEncoding::PerParticle,
+        }
+    }
+
+    pub fn with_thumbnail(mut self) -> SaveSceneEvent {
+        self.thumbnail = true;
+        self
+    }
+
+    pub fn compressed(mut self) -> SaveSceneEvent {
+        self.compressed = true;
+        self
+    }
+
+    pub fn with_encoding(mut self, encoding: SceneEncoding) -> SaveSceneEvent {
+        self.encoding = encoding;
+        self
+    }
+}
+
+/// Saves the particles between the corners `min` and `max` (inclusive) as a scene.
+///
+/// Positions are stored relative to the rectangle's bottom-left corner, so the region can be
+/// pasted anywhere with a [`LoadSceneAtEvent`].
+#[derive(Event)]
+pub struct SaveSceneRegionEvent {
+    pub path: PathBuf,
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl SaveSceneRegionEvent {
+    pub fn new(path: impl Into<PathBuf>, min: IVec2, max: IVec2) -> SaveSceneRegionEvent {
+        SaveSceneRegionEvent {
+            path: path.into(),
+            min,
+            max,
+        }
+    }
+}
 
 #[derive(Event)]
 pub struct LoadSceneEvent(pub PathBuf);
+
+/// Loads a scene with every particle shifted by `offset`, e.g. to paste a region saved with
+/// [`SaveSceneRegionEvent`].
+#[derive(Event)]
+pub struct LoadSceneAtEvent {
+    pub path: PathBuf,
+    pub offset: IVec2,
+}
+
+/// Sent after a `SaveSceneEvent` or `SaveSceneRegionEvent` has been handled.
+#[derive(Event, Debug)]
+pub struct SceneSavedEvent {
+    pub path: PathBuf,
+    pub result: Result<(), SceneError>,
+}
+
+/// Sent after a `LoadSceneEvent` or `LoadSceneAtEvent` has been handled.
+#[derive(Event, Debug)]
+pub struct SceneLoadedEvent {
+    pub path: PathBuf,
+    pub result: Result<(), SceneError>,
+}
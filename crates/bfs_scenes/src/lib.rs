@@ -1,11 +1,18 @@
 mod events;
 
 use bevy::prelude::*;
-use bfs_core::{Coordinates, Particle};
-use ron::de::from_reader;
+use bfs_color::{render_map, FrameExportError};
+use bfs_core::{ChunkMap, Coordinates, Particle, ParticleId, ParticleTypeMap};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use image::imageops::FilterType;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
 
 pub use events::*;
 
@@ -14,62 +21,697 @@ pub struct FallingSandScenesPlugin;
 impl Plugin for FallingSandScenesPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(EventsPlugin);
+        app.init_resource::<ParticleIdMap>()
+            .init_resource::<SceneThumbnailSettings>();
         app.add_systems(
             Update,
             save_scene_system.run_if(on_event::<crate::events::SaveSceneEvent>),
         )
         .add_systems(
             Update,
-            load_scene_system.run_if(on_event::<crate::events::LoadSceneEvent>),
+            save_scene_region_system.run_if(on_event::<crate::events::SaveSceneRegionEvent>),
+        )
+        .add_systems(
+            Update,
+            load_scene_system.run_if(
+                on_event::<crate::events::LoadSceneEvent>
+                    .or(on_event::<crate::events::LoadSceneAtEvent>),
+            ),
         );
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SceneError {
+    #[error("Could not access scene file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not serialize scene: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("Could not deserialize scene: {0}")]
+    Deserialize(#[from] ron::error::SpannedError),
+    #[error("Scene contains unknown particle types: {}", .0.join(", "))]
+    UnknownType(Vec<String>),
+    #[error("Could not write scene thumbnail: {0}")]
+    Thumbnail(#[from] FrameExportError),
+    #[error(
+        "Scene format version {0} is not supported (versions 1 to {} are)",
+        SCENE_FORMAT_VERSION
+    )]
+    UnsupportedVersion(u32),
+}
+
+/// How thumbnails are rendered for scenes saved with [`SaveSceneEvent::with_thumbnail`].
+///
+/// Thumbnails cover the bounds of the saved particles and are scaled down to fit within
+/// `max_size` pixels on each side, keeping their aspect ratio. Smaller scenes are not scaled up.
+#[derive(Resource, Copy, Clone, PartialEq, Debug)]
+pub struct SceneThumbnailSettings {
+    pub max_size: u32,
+    /// The fill for empty cells. Empty cells are transparent if `None`.
+    pub background: Option<Color>,
+}
+
+impl Default for SceneThumbnailSettings {
+    fn default() -> SceneThumbnailSettings {
+        SceneThumbnailSettings {
+            max_size: 128,
+            background: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ParticleData {
     pub particle_type: Particle,
     pub coordinates: Coordinates,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<ParticleId>,
+}
+
+/// The version of the scene format written by [`ParticleScene::save`].
+///
+/// Scenes written by older versions are migrated when loaded. Version 1 scenes predate the
+/// version header.
+pub const SCENE_FORMAT_VERSION: u32 = 2;
+
+/// A horizontal line of `length` identical particles, starting at `start` and extending to the
+/// right.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ParticleRun {
+    pub particle: Particle,
+    pub start: IVec2,
+    pub length: u32,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct ParticleScene {
+    pub version: u32,
     pub particles: Vec<ParticleData>,
+    /// Particles stored as runs rather than one record each. See
+    /// [`ParticleScene::run_length_encoded`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runs: Vec<ParticleRun>,
+}
+
+/// The first bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Just enough of a scene file to tell which format version it was written in.
+#[derive(Deserialize)]
+struct SceneHeader {
+    #[serde(default = "SceneHeader::unversioned")]
+    version: u32,
+}
+
+impl SceneHeader {
+    fn unversioned() -> u32 {
+        1
+    }
+}
+
+/// The layout of version 1 scenes, which had no version header.
+#[derive(Deserialize)]
+struct ParticleSceneV1 {
+    particles: Vec<ParticleData>,
+}
+
+impl From<ParticleSceneV1> for ParticleScene {
+    fn from(scene: ParticleSceneV1) -> ParticleScene {
+        ParticleScene::new(scene.particles)
+    }
+}
+
+/// Maps the [`ParticleId`]s of the most recently loaded scene to the entities spawned for them.
+///
+/// The map is rebuilt each time a scene is loaded, so features that store ids can re-resolve
+/// their references once `SceneLoadedEvent` is received.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ParticleIdMap(HashMap<ParticleId, Entity>);
+
+impl ParticleIdMap {
+    pub fn get(&self, id: ParticleId) -> Option<Entity> {
+        self.0.get(&id).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&ParticleId, &Entity)> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl ParticleScene {
+    pub fn new(particles: Vec<ParticleData>) -> ParticleScene {
+        ParticleScene {
+            version: SCENE_FORMAT_VERSION,
+            particles,
+            runs: vec![],
+        }
+    }
+
+    /// Creates a scene that stores horizontal runs of identical particles as single records,
+    /// which shrinks scenes with large uniform areas such as walls or terrain considerably.
+    ///
+    /// Particles with a [`ParticleId`] are always stored individually.
+    pub fn run_length_encoded(particles: Vec<ParticleData>) -> ParticleScene {
+        let (mut runnable, particles): (Vec<ParticleData>, Vec<ParticleData>) = particles
+            .into_iter()
+            .partition(|particle_data| particle_data.id.is_none());
+        runnable.sort_by_key(|particle_data| {
+            let coordinates = particle_data.coordinates.0;
+            (coordinates.y, coordinates.x)
+        });
+
+        let mut runs: Vec<ParticleRun> = vec![];
+        for particle_data in runnable {
+            let coordinates = particle_data.coordinates.0;
+            if let Some(run) = runs.last_mut() {
+                if run.particle == particle_data.particle_type
+                    && run.start.y == coordinates.y
+                    && run.start.x + run.length as i32 == coordinates.x
+                {
+                    run.length += 1;
+                    continue;
+                }
+            }
+            runs.push(ParticleRun {
+                particle: particle_data.particle_type,
+                start: coordinates,
+                length: 1,
+            });
+        }
+
+        ParticleScene {
+            version: SCENE_FORMAT_VERSION,
+            particles,
+            runs,
+        }
+    }
+
+    /// Every particle in the scene, with runs expanded into individual particles.
+    pub fn into_particles(self) -> impl Iterator<Item = ParticleData> {
+        self.particles
+            .into_iter()
+            .chain(self.runs.into_iter().flat_map(|run| {
+                (0..run.length as i32).map(move |x| ParticleData {
+                    particle_type: run.particle.clone(),
+                    coordinates: Coordinates(run.start + IVec2::new(x, 0)),
+                    id: None,
+                })
+            }))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SceneError> {
+        let ron_string = ron::to_string(self)?;
+        File::create(path)?.write_all(ron_string.as_bytes())?;
+        Ok(())
+    }
+
+    /// Like [`ParticleScene::save`], but gzip-compresses the file. [`ParticleScene::load`] reads
+    /// compressed and uncompressed scenes alike.
+    pub fn save_compressed(&self, path: &Path) -> Result<(), SceneError> {
+        let ron_string = ron::to_string(self)?;
+        let mut encoder = GzEncoder::new(File::create(path)?, Compression::default());
+        encoder.write_all(ron_string.as_bytes())?;
+        encoder.finish()?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<ParticleScene, SceneError> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        let ron_string = if bytes.starts_with(&GZIP_MAGIC) {
+            let mut ron_string = String::new();
+            GzDecoder::new(bytes.as_slice()).read_to_string(&mut ron_string)?;
+            ron_string
+        } else {
+            String::from_utf8(bytes)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?
+        };
+        ParticleScene::from_ron(&ron_string)
+    }
+
+    /// Parses a scene written in any supported format version, migrating it to the current one.
+    pub fn from_ron(ron_string: &str) -> Result<ParticleScene, SceneError> {
+        let header: SceneHeader = ron::from_str(ron_string)?;
+        match header.version {
+            1 => Ok(ron::from_str::<ParticleSceneV1>(ron_string)?.into()),
+            SCENE_FORMAT_VERSION => Ok(ron::from_str(ron_string)?),
+            version => Err(SceneError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// Renders a thumbnail of `particles` and writes it next to the scene at `path`, with a `.png`
+/// extension.
+pub fn save_scene_thumbnail(
+    path: &Path,
+    particles: &[ParticleData],
+    map: &ChunkMap,
+    sprite_query: &Query<&Sprite>,
+    settings: &SceneThumbnailSettings,
+) -> Result<(), SceneError> {
+    let region = thumbnail_region(particles).unwrap_or_default();
+    let image = render_map(map, Some(region), settings.background, |entity| {
+        sprite_query.get(entity).ok().map(|sprite| sprite.color)
+    })?;
+
+    let max_size = settings.max_size.max(1);
+    let image = if image.width() > max_size || image.height() > max_size {
+        let scale = max_size as f32 / image.width().max(image.height()) as f32;
+        image::imageops::resize(
+            &image,
+            ((image.width() as f32 * scale) as u32).max(1),
+            ((image.height() as f32 * scale) as u32).max(1),
+            FilterType::Nearest,
+        )
+    } else {
+        image
+    };
+    image
+        .save_with_format(path.with_extension("png"), image::ImageFormat::Png)
+        .map_err(FrameExportError::from)?;
+    Ok(())
+}
+
+/// The inclusive bounds of `particles`, or `None` if there are none.
+fn thumbnail_region(particles: &[ParticleData]) -> Option<IRect> {
+    let mut coordinates = particles
+        .iter()
+        .map(|particle_data| particle_data.coordinates.0);
+    let first = coordinates.next()?;
+    let (min, max) = coordinates.fold((first, first), |(min, max), coordinates| {
+        (min.min(coordinates), max.max(coordinates))
+    });
+    Some(IRect { min, max })
 }
 
 pub fn save_scene_system(
-    particle_query: Query<(&Particle, &Coordinates)>,
+    particle_query: Query<(&Particle, &Coordinates, Option<&ParticleId>)>,
+    sprite_query: Query<&Sprite>,
+    map: Res<ChunkMap>,
+    thumbnail_settings: Res<SceneThumbnailSettings>,
     mut ev_save_scene: EventReader<SaveSceneEvent>,
+    mut ev_scene_saved: EventWriter<SceneSavedEvent>,
 ) {
     for ev in ev_save_scene.read() {
         let particles: Vec<ParticleData> = particle_query
             .iter()
-            .map(|(particle_type, coordinates)| ParticleData {
+            .map(|(particle_type, coordinates, id)| ParticleData {
                 particle_type: particle_type.clone(),
                 coordinates: *coordinates,
+                id: id.copied(),
+            })
+            .collect();
+        // Runs don't keep individual particles around, so thumbnails are rendered from a copy.
+        let thumbnail_particles = ev.thumbnail.then(|| particles.clone());
+
+        let particle_scene = match ev.encoding {
+            SceneEncoding::PerParticle => ParticleScene::new(particles),
+            SceneEncoding::RunLength => ParticleScene::run_length_encoded(particles),
+        };
+        // The scene itself is still written if its thumbnail fails.
+        let saved = if ev.compressed {
+            particle_scene.save_compressed(&ev.path)
+        } else {
+            particle_scene.save(&ev.path)
+        };
+        let result = saved.and_then(|()| {
+            let Some(particles) = thumbnail_particles else {
+                return Ok(());
+            };
+            save_scene_thumbnail(
+                &ev.path,
+                &particles,
+                &map,
+                &sprite_query,
+                &thumbnail_settings,
+            )
+        });
+        if let Err(err) = &result {
+            error!("Failed to save scene to {:?}: {}", ev.path, err);
+        }
+        ev_scene_saved.send(SceneSavedEvent {
+            path: ev.path.clone(),
+            result,
+        });
+    }
+}
+
+pub fn save_scene_region_system(
+    particle_query: Query<(&Particle, Option<&ParticleId>)>,
+    map: Res<ChunkMap>,
+    mut ev_save_scene_region: EventReader<SaveSceneRegionEvent>,
+    mut ev_scene_saved: EventWriter<SceneSavedEvent>,
+) {
+    for ev in ev_save_scene_region.read() {
+        let region = IRect::from_corners(ev.min, ev.max);
+        let particles: Vec<ParticleData> = map
+            .iter_region(region.min, region.max)
+            .filter_map(|(coordinates, entity)| {
+                let (particle_type, id) = particle_query.get(entity).ok()?;
+                Some(ParticleData {
+                    particle_type: particle_type.clone(),
+                    coordinates: Coordinates(coordinates - region.min),
+                    id: id.copied(),
+                })
             })
             .collect();
 
-        let particle_scene = ParticleScene { particles };
-        let ron_string = ron::to_string(&particle_scene).unwrap();
-        File::create(ev.0.clone())
-            .and_then(|mut file| file.write(ron_string.as_bytes()))
-            .expect("Error while writing scene to file");
+        let result = ParticleScene::new(particles).save(&ev.path);
+        if let Err(err) = &result {
+            error!("Failed to save scene region to {:?}: {}", ev.path, err);
+        }
+        ev_scene_saved.send(SceneSavedEvent {
+            path: ev.path.clone(),
+            result,
+        });
     }
 }
 
-pub fn load_scene_system(mut commands: Commands, mut ev_load_scene: EventReader<LoadSceneEvent>) {
-    for ev in ev_load_scene.read() {
-        let file = File::open(ev.0.clone()).expect("Failed to open RON file");
-        let particle_scene: ParticleScene = from_reader(file).expect("Failed to load RON file");
+pub fn load_scene_system(
+    mut commands: Commands,
+    mut ev_load_scene: EventReader<LoadSceneEvent>,
+    mut ev_load_scene_at: EventReader<LoadSceneAtEvent>,
+    mut ev_scene_loaded: EventWriter<SceneLoadedEvent>,
+    type_map: Res<ParticleTypeMap>,
+    mut id_map: ResMut<ParticleIdMap>,
+) {
+    let requests: Vec<(PathBuf, IVec2)> = ev_load_scene
+        .read()
+        .map(|ev| (ev.0.clone(), IVec2::ZERO))
+        .chain(
+            ev_load_scene_at
+                .read()
+                .map(|ev| (ev.path.clone(), ev.offset)),
+        )
+        .collect();
+    for (path, offset) in requests {
+        let particle_scene = match ParticleScene::load(&path) {
+            Ok(particle_scene) => particle_scene,
+            Err(err) => {
+                error!("Failed to load scene from {:?}: {}", path, err);
+                ev_scene_loaded.send(SceneLoadedEvent {
+                    path,
+                    result: Err(err),
+                });
+                continue;
+            }
+        };
 
-        for particle_data in particle_scene.particles {
-            let transform = Transform::from_xyz(
-                particle_data.coordinates.0.x as f32,
-                particle_data.coordinates.0.y as f32,
-                0.,
+        id_map.0.clear();
+        let mut unknown_types: Vec<String> = vec![];
+        for particle_data in particle_scene.into_particles() {
+            if type_map.get(&particle_data.particle_type.name).is_none() {
+                if !unknown_types.contains(&particle_data.particle_type.name) {
+                    unknown_types.push(particle_data.particle_type.name);
+                }
+                continue;
+            }
+
+            let coordinates = particle_data.coordinates.0 + offset;
+            let transform = Transform::from_xyz(coordinates.x as f32, coordinates.y as f32, 0.);
+
+            let mut entity = commands.spawn((particle_data.particle_type.clone(), transform));
+            if let Some(id) = particle_data.id {
+                entity.insert(id);
+                if id_map.0.insert(id, entity.id()).is_some() {
+                    warn!("Duplicate particle id {} in scene {:?}", id.0, path);
+                }
+            }
+        }
+
+        let result = if unknown_types.is_empty() {
+            Ok(())
+        } else {
+            let err = SceneError::UnknownType(unknown_types);
+            warn!(
+                "Skipped particles while loading scene from {:?}: {}",
+                path, err
             );
+            Err(err)
+        };
+        ev_scene_loaded.send(SceneLoadedEvent { path, result });
+    }
+}
 
-            commands.spawn((particle_data.particle_type.clone(), transform));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bfs_core::ParticleType;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("bfs_scenes_{}_{name}", std::process::id()))
+    }
+
+    fn scene_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(EventsPlugin)
+            .init_resource::<ParticleIdMap>()
+            .init_resource::<ParticleTypeMap>()
+            .insert_resource(ChunkMap::new(128))
+            .init_resource::<SceneThumbnailSettings>()
+            .add_systems(
+                Update,
+                (
+                    save_scene_system,
+                    save_scene_region_system,
+                    load_scene_system,
+                )
+                    .chain(),
+            );
+        let sand = app.world_mut().spawn(ParticleType::new("Sand")).id();
+        app.world_mut()
+            .resource_mut::<ParticleTypeMap>()
+            .insert("Sand".to_string(), sand);
+        app
+    }
+
+    fn place(app: &mut App, coordinates: IVec2) {
+        let entity = app
+            .world_mut()
+            .spawn((
+                Particle::new("Sand"),
+                Coordinates(coordinates),
+                Sprite::from_color(Color::WHITE, Vec2::ONE),
+            ))
+            .id();
+        app.world_mut()
+            .resource_mut::<ChunkMap>()
+            .insert_overwrite(coordinates, entity);
+    }
+
+    fn loaded_positions(app: &mut App) -> Vec<IVec2> {
+        let mut positions: Vec<IVec2> = app
+            .world_mut()
+            .query_filtered::<&Transform, (With<Particle>, Without<Coordinates>)>()
+            .iter(app.world())
+            .map(|transform| transform.translation.truncate().as_ivec2())
+            .collect();
+        positions.sort_by_key(|position| (position.x, position.y));
+        positions
+    }
+
+    #[test]
+    fn region_round_trip_keeps_boundary_cells() {
+        let mut app = scene_app();
+        // x = -1 and y = 0 are the last column and row of their chunks, so the region overlaps
+        // those chunks by a single cell.
+        let (min, max) = (IVec2::new(-1, 0), IVec2::new(4, 3));
+        let corners = [min, IVec2::new(4, 0), IVec2::new(-1, 3), max];
+        let outside = [
+            IVec2::new(-2, 0),
+            IVec2::new(-1, -1),
+            IVec2::new(5, 3),
+            IVec2::new(4, 4),
+        ];
+        for coordinates in corners.iter().chain(outside.iter()) {
+            place(&mut app, *coordinates);
+        }
+
+        let path = temp_path("region.scn.ron");
+        app.world_mut()
+            .send_event(SaveSceneRegionEvent::new(&path, max, min));
+        app.update();
+
+        let offset = IVec2::new(20, -10);
+        app.world_mut().send_event(LoadSceneAtEvent {
+            path: path.clone(),
+            offset,
+        });
+        app.update();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut expected: Vec<IVec2> = corners.iter().map(|c| *c - min + offset).collect();
+        expected.sort_by_key(|position| (position.x, position.y));
+        assert_eq!(loaded_positions(&mut app), expected);
+    }
+
+    #[test]
+    fn single_column_region_is_saved() {
+        let mut app = scene_app();
+        for y in 0..4 {
+            place(&mut app, IVec2::new(0, y));
+            place(&mut app, IVec2::new(1, y));
         }
+
+        let path = temp_path("column.scn.ron");
+        app.world_mut().send_event(SaveSceneRegionEvent::new(
+            &path,
+            IVec2::new(0, 0),
+            IVec2::new(0, 3),
+        ));
+        app.update();
+        let scene = ParticleScene::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut saved: Vec<IVec2> = scene
+            .into_particles()
+            .map(|particle_data| particle_data.coordinates.0)
+            .collect();
+        saved.sort_by_key(|position| position.y);
+        assert_eq!(saved, (0..4).map(|y| IVec2::new(0, y)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn thumbnail_of_a_single_row_covers_the_row() {
+        let mut app = scene_app();
+        for x in -2..=3 {
+            place(&mut app, IVec2::new(x, 0));
+        }
+
+        let path = temp_path("row.scn.ron");
+        app.world_mut()
+            .send_event(SaveSceneEvent::new(&path).with_thumbnail());
+        app.update();
+        let thumbnail_path = path.with_extension("png");
+        let thumbnail = image::open(&thumbnail_path).unwrap().to_rgba8();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&thumbnail_path).unwrap();
+
+        assert_eq!((thumbnail.width(), thumbnail.height()), (6, 1));
+        assert!(thumbnail
+            .pixels()
+            .all(|pixel| *pixel == image::Rgba([255, 255, 255, 255])));
+    }
+
+    #[test]
+    fn thumbnail_region_is_inclusive() {
+        let particle = |x, y| ParticleData {
+            particle_type: Particle::new("Sand"),
+            coordinates: Coordinates(IVec2::new(x, y)),
+            id: None,
+        };
+        assert_eq!(thumbnail_region(&[]), None);
+        assert_eq!(
+            thumbnail_region(&[particle(4, 7)]),
+            Some(IRect::new(4, 7, 4, 7))
+        );
+        assert_eq!(
+            thumbnail_region(&[particle(5, 1), particle(-2, 1), particle(0, 1)]),
+            Some(IRect::new(-2, 1, 5, 1))
+        );
+    }
+
+    #[test]
+    fn v1_scene_is_migrated() {
+        let scene = ParticleScene::from_ron(include_str!("../fixtures/scene_v1.ron")).unwrap();
+        assert_eq!(scene.version, SCENE_FORMAT_VERSION);
+        let particles: Vec<(String, IVec2)> = scene
+            .into_particles()
+            .map(|particle_data| {
+                (
+                    particle_data.particle_type.name,
+                    particle_data.coordinates.0,
+                )
+            })
+            .collect();
+        assert_eq!(
+            particles,
+            vec![
+                ("Sand".to_string(), IVec2::new(0, 0)),
+                ("Sand".to_string(), IVec2::new(1, 0)),
+                ("Water".to_string(), IVec2::new(-3, 12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let result = ParticleScene::from_ron("(version: 99, particles: [])");
+        assert!(matches!(result, Err(SceneError::UnsupportedVersion(99))));
+    }
+
+    #[test]
+    fn compressed_scene_round_trip() {
+        let mut app = scene_app();
+        let cells = [IVec2::new(-5, 3), IVec2::new(0, 0), IVec2::new(7, -2)];
+        for coordinates in cells {
+            place(&mut app, coordinates);
+        }
+
+        let path = temp_path("compressed.scn.ron");
+        app.world_mut()
+            .send_event(SaveSceneEvent::new(&path).compressed());
+        app.update();
+        assert!(std::fs::read(&path).unwrap().starts_with(&GZIP_MAGIC));
+
+        app.world_mut().send_event(LoadSceneEvent(path.clone()));
+        app.update();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut expected = cells.to_vec();
+        expected.sort_by_key(|position| (position.x, position.y));
+        assert_eq!(loaded_positions(&mut app), expected);
+    }
+
+    fn sorted_particles(particles: impl Iterator<Item = ParticleData>) -> Vec<(String, IVec2)> {
+        let mut particles: Vec<(String, IVec2)> = particles
+            .map(|particle_data| {
+                (
+                    particle_data.particle_type.name,
+                    particle_data.coordinates.0,
+                )
+            })
+            .collect();
+        particles.sort_by_key(|(_, coordinates)| (coordinates.y, coordinates.x));
+        particles
+    }
+
+    #[test]
+    fn run_length_encoding_round_trip() {
+        let particle = |name: &str, x, y| ParticleData {
+            particle_type: Particle::new(name),
+            coordinates: Coordinates(IVec2::new(x, y)),
+            id: None,
+        };
+        let mut particles: Vec<ParticleData> = vec![];
+        // A long run, split by a gap.
+        particles.extend((0..500).map(|x| particle("Wall", x, 0)));
+        particles.extend((501..600).map(|x| particle("Wall", x, 0)));
+        // Alternating cells can't be merged at all.
+        particles.extend((0..20).map(|x| particle(["Sand", "Water"][x as usize % 2], x, 1)));
+        // Particles with an id are never merged into a run.
+        let mut identified = particle("Wall", 600, 0);
+        identified.id = Some(ParticleId(7));
+        particles.push(identified);
+
+        let expected = sorted_particles(particles.clone().into_iter());
+        let scene = ParticleScene::run_length_encoded(particles);
+        assert_eq!(scene.particles.len(), 1);
+        assert_eq!(scene.runs.len(), 2 + 20);
+        assert_eq!(scene.runs[0].length, 500);
+        assert_eq!(scene.runs[1].length, 99);
+
+        let scene = ParticleScene::from_ron(&ron::to_string(&scene).unwrap()).unwrap();
+        assert_eq!(scene.particles[0].id, Some(ParticleId(7)));
+        assert_eq!(sorted_particles(scene.into_particles()), expected);
     }
 }
@@ -1,5 +1,6 @@
-use bevy::prelude::{App, Plugin};
-use bevy_spatial::{kdtree::KDTree2, AutomaticUpdate, SpatialStructure};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{App, Entity, Plugin, Query, Res, Vec2};
+use bevy_spatial::{kdtree::KDTree2, AutomaticUpdate, SpatialAccess, SpatialStructure};
 use bfs_core::Particle;
 use bevy::utils::Duration;
 
@@ -16,3 +17,189 @@ impl Plugin for FallingSandSpatialPlugin {
 }
 
 pub type ParticleTree = KDTree2<Particle>;
+
+/// Queries the [`ParticleTree`] and attaches each hit's `Particle` component.
+///
+/// The tree is only rebuilt every 200ms, so results can be stale: positions are those at the last
+/// rebuild, and particles despawned since then are left out of the results. Entities that changed
+/// type since the last rebuild are reported with their current type.
+#[derive(SystemParam)]
+pub struct ParticleTreeQuery<'w, 's> {
+    pub tree: Res<'w, ParticleTree>,
+    particle_query: Query<'w, 's, &'static Particle>,
+}
+
+impl ParticleTreeQuery<'_, '_> {
+    pub fn nearest(&self, loc: Vec2) -> Option<(Vec2, Entity, &Particle)> {
+        self.tree
+            .nearest_neighbour(loc)
+            .and_then(|hit| self.join(hit))
+    }
+
+    pub fn k_nearest(&self, loc: Vec2, k: usize) -> Vec<(Vec2, Entity, &Particle)> {
+        self.tree
+            .k_nearest_neighbour(loc, k)
+            .into_iter()
+            .filter_map(|hit| self.join(hit))
+            .collect()
+    }
+
+    pub fn within_distance(&self, loc: Vec2, distance: f32) -> Vec<(Vec2, Entity, &Particle)> {
+        self.tree
+            .within_distance(loc, distance)
+            .into_iter()
+            .filter_map(|hit| self.join(hit))
+            .collect()
+    }
+
+    fn join(
+        &self,
+        (position, entity): (Vec2, Option<Entity>),
+    ) -> Option<(Vec2, Entity, &Particle)> {
+        let entity = entity?;
+        self.particle_query
+            .get(entity)
+            .ok()
+            .map(|particle| (position, entity, particle))
+    }
+}
+
+/// Returns every particle in `tree` within `radius` of `center`, nearest first. Particles exactly
+/// `radius` away are included.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bfs_spatial::{particles_within, ParticleTree};
+/// fn ignite_nearby(tree: Res<ParticleTree>) {
+///     for (position, entity) in particles_within(&tree, Vec2::ZERO, 5.) {
+///         // ...
+///     }
+/// }
+/// # App::new().add_systems(Update, ignite_nearby);
+/// ```
+pub fn particles_within(
+    tree: &ParticleTree,
+    center: Vec2,
+    radius: f32,
+) -> Vec<(Vec2, Option<Entity>)> {
+    // Search slightly further out, so the boundary doesn't depend on the tree's own comparison.
+    let hits = tree.within_distance(center, radius.next_up());
+    nearest_first(hits, center, radius, usize::MAX)
+}
+
+/// Returns the `n` particles in `tree` nearest to `center`, nearest first. Fewer are returned if
+/// the tree holds fewer than `n` particles.
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bfs_spatial::{nearest_n, ParticleTree};
+/// fn seek_water(tree: Res<ParticleTree>) {
+///     if let Some((position, entity)) = nearest_n(&tree, Vec2::ZERO, 1).first() {
+///         // ...
+///     }
+/// }
+/// # App::new().add_systems(Update, seek_water);
+/// ```
+pub fn nearest_n(tree: &ParticleTree, center: Vec2, n: usize) -> Vec<(Vec2, Option<Entity>)> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let hits = tree.k_nearest_neighbour(center, n);
+    nearest_first(hits, center, f32::INFINITY, n)
+}
+
+/// Sorts `hits` by their distance to `center`, keeping the nearest `n` that are at most `radius`
+/// away.
+fn nearest_first(
+    mut hits: Vec<(Vec2, Option<Entity>)>,
+    center: Vec2,
+    radius: f32,
+    n: usize,
+) -> Vec<(Vec2, Option<Entity>)> {
+    hits.retain(|(position, _)| position.distance_squared(center) <= radius * radius);
+    hits.sort_by(|(a, _), (b, _)| {
+        a.distance_squared(center)
+            .total_cmp(&b.distance_squared(center))
+    });
+    hits.truncate(n);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits(positions: &[Vec2]) -> Vec<(Vec2, Option<Entity>)> {
+        positions
+            .iter()
+            .enumerate()
+            .map(|(i, position)| (*position, Some(Entity::from_raw(i as u32))))
+            .collect()
+    }
+
+    fn positions(hits: &[(Vec2, Option<Entity>)]) -> Vec<Vec2> {
+        hits.iter().map(|(position, _)| *position).collect()
+    }
+
+    #[test]
+    fn particles_within_an_empty_tree() {
+        assert!(nearest_first(Vec::new(), Vec2::ZERO, 5., usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn particles_within_include_the_boundary() {
+        let center = Vec2::new(1., 1.);
+        let found = nearest_first(
+            hits(&[
+                Vec2::new(4., 1.),
+                Vec2::new(1., 1.),
+                Vec2::new(1., -2.),
+                Vec2::new(3.2, 3.2),
+                Vec2::new(2., 2.),
+            ]),
+            center,
+            3.,
+            usize::MAX,
+        );
+        assert_eq!(
+            positions(&found)[..2],
+            [Vec2::new(1., 1.), Vec2::new(2., 2.)]
+        );
+        // Both boundary hits are exactly 3 away, and the one just beyond it is dropped.
+        assert_eq!(found.len(), 4);
+        assert!(positions(&found)[2..].contains(&Vec2::new(4., 1.)));
+        assert!(positions(&found)[2..].contains(&Vec2::new(1., -2.)));
+    }
+
+    #[test]
+    fn nearest_n_of_an_empty_tree() {
+        assert!(nearest_first(Vec::new(), Vec2::ZERO, f32::INFINITY, 3).is_empty());
+    }
+
+    #[test]
+    fn nearest_n_with_fewer_than_n_particles() {
+        let found = nearest_first(
+            hits(&[Vec2::new(5., 0.), Vec2::new(-1., 0.)]),
+            Vec2::ZERO,
+            f32::INFINITY,
+            3,
+        );
+        assert_eq!(positions(&found), [Vec2::new(-1., 0.), Vec2::new(5., 0.)]);
+    }
+
+    #[test]
+    fn nearest_n_with_more_than_n_particles() {
+        let found = nearest_first(
+            hits(&[
+                Vec2::new(0., 40.),
+                Vec2::new(3., 0.),
+                Vec2::new(0., -1.),
+                Vec2::new(-2., 0.),
+            ]),
+            Vec2::ZERO,
+            f32::INFINITY,
+            2,
+        );
+        assert_eq!(positions(&found), [Vec2::new(0., -1.), Vec2::new(-2., 0.)]);
+    }
+}
@@ -27,7 +27,7 @@ fn main() {
             ..default()
         }),
         EguiPlugin,
-        FallingSandPlugin,
+        FallingSandPlugin::default(),
         // Plugins provided by the modules defined in this example.
         CameraPlugin,
         ParticleSetupPlugin,
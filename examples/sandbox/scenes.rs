@@ -72,7 +72,8 @@ impl SceneManagementUI {
                                 file_name.push_str(".ron");
                             }
                             scene_path.0.set_file_name(file_name);
-                            ev_save_scene.send(SaveSceneEvent(scene_path.0.clone()));
+                            ev_save_scene
+                                .send(SaveSceneEvent::new(scene_path.0.clone()).with_thumbnail());
                             dialog_state.show_save_dialog = false; // Close after saving
                         }
                         if ui.button("Cancel").clicked() {
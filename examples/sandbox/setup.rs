@@ -221,7 +221,7 @@ pub fn setup_custom_particles(mut commands: Commands) {
             Some(Fire {
                 burn_radius: 2.,
                 chance_to_spread: 0.2,
-                destroys_on_spread: false,
+                on_spread: SpreadEffect::Keep,
             }),
         )),
         Name::new("Oil"),
@@ -358,7 +358,7 @@ pub fn setup_custom_particles(mut commands: Commands) {
             Some(Fire {
                 burn_radius: 2.,
                 chance_to_spread: 1.,
-                destroys_on_spread: true,
+                on_spread: SpreadEffect::Destroy,
             }),
         )),
         Name::new("Flammable Gas"),
@@ -384,7 +384,7 @@ pub fn setup_custom_particles(mut commands: Commands) {
         FireBlueprint(Fire {
             burn_radius: 1.5,
             chance_to_spread: 0.01,
-            destroys_on_spread: false,
+            on_spread: SpreadEffect::Keep,
         }),
         BurnsBlueprint(Burns::new(
             Duration::from_secs(1),
@@ -477,7 +477,7 @@ pub fn setup_custom_particles(mut commands: Commands) {
             Some(Fire {
                 burn_radius: 1.5,
                 chance_to_spread: 1.,
-                destroys_on_spread: false,
+                on_spread: SpreadEffect::Keep,
             }),
         )),
         Name::new("Grass Wall"),
@@ -509,7 +509,7 @@ pub fn setup_custom_particles(mut commands: Commands) {
             Some(Fire {
                 burn_radius: 1.5,
                 chance_to_spread: 0.005,
-                destroys_on_spread: false,
+                on_spread: SpreadEffect::Keep,
             }),
         )),
         Name::new("Wood Wall"),
@@ -539,6 +539,8 @@ fn load_particle_types(
 ) {
     for ev in ev_asset.read() {
         let asset = particle_types_asset.get(&ev.handle).unwrap();
-        asset.load_particle_types(&mut commands, &mut type_map);
+        if let Err(err) = asset.load_particle_types(&mut commands, &mut type_map) {
+            error!("Some particle types could not be loaded: {}", err);
+        }
     }
 }
@@ -11,14 +11,15 @@ use bevy::{
 };
 use bevy_egui::{egui, egui::Color32, EguiContexts};
 use bfs_internal::{
-    reactions::{BurnsBlueprint, Fire, Reacting},
+    reactions::{BurnsBlueprint, Fire, Reacting, SpreadEffect},
     ParticleBundle,
 };
 
 use bevy_falling_sand::color::*;
 use bevy_falling_sand::core::*;
 use bevy_falling_sand::debug::{
-    DebugDirtyRects, DebugHibernatingChunks, DebugParticleCount, TotalParticleCount,
+    DebugDirtyRects, DebugHibernatingChunks, DebugParticleCount, LogSimulationStatsEvent,
+    TotalParticleCount,
 };
 use bevy_falling_sand::movement::*;
 use bevy_falling_sand::scenes::{LoadSceneEvent, SaveSceneEvent};
@@ -41,6 +42,10 @@ impl bevy::prelude::Plugin for UIPlugin {
                 Update,
                 toggle_simulation.run_if(input_just_pressed(KeyCode::Space)),
             )
+            .add_systems(
+                Update,
+                step_simulation.run_if(input_just_pressed(KeyCode::Period)),
+            )
             .init_resource::<CursorCoords>()
             .init_resource::<ParticleList>()
             .init_resource::<ParticleTypeList>()
@@ -289,6 +294,10 @@ impl DebugUI {
         if show_particle_count {
             ui.label(format!("Total Particles: {}", total_particle_count));
         }
+
+        if ui.button("Log Stats").clicked() {
+            commands.trigger(LogSimulationStatsEvent);
+        }
     }
 }
 
@@ -470,22 +479,25 @@ pub fn update_particle_list(
 
 /// Stops or starts the simulation when scheduled.
 pub fn toggle_simulation(
-    mut commands: Commands,
-    simulation_pause: Option<Res<SimulationRun>>,
+    mut simulation_control: ResMut<SimulationControl>,
     app_state: Res<State<AppState>>,
 ) {
     match app_state.get() {
-        AppState::Canvas => {
-            if simulation_pause.is_some() {
-                commands.remove_resource::<SimulationRun>();
-            } else {
-                commands.init_resource::<SimulationRun>();
-            }
-        }
+        AppState::Canvas => simulation_control.toggle(),
         _ => {}
     }
 }
 
+/// Advances the simulation by one step while it's paused.
+pub fn step_simulation(
+    mut ev_step_simulation: EventWriter<StepSimulationEvent>,
+    app_state: Res<State<AppState>>,
+) {
+    if let AppState::Canvas = app_state.get() {
+        ev_step_simulation.send(StepSimulationEvent);
+    }
+}
+
 /// Listens for scroll events and performs the corresponding action
 pub fn ev_mouse_wheel(
     mut ev_scroll: EventReader<MouseWheel>,
@@ -706,6 +718,7 @@ pub fn update_particle_editor_fields(
             Option<&SolidBlueprint>,
             Option<&MovableSolidBlueprint>,
             Option<&GasBlueprint>,
+            Option<&ViscosityBlueprint>,
         ),
         With<ParticleType>,
     >,
@@ -714,6 +727,7 @@ pub fn update_particle_editor_fields(
     mut particle_momentum_field: ResMut<ParticleEditorMomentum>,
     mut particle_colors_field: ResMut<ParticleEditorColors>,
     mut particle_editor_burns_field: ResMut<ParticleEditorBurns>,
+    mut particle_editor_liquid_field: ResMut<ParticleEditorLiquid>,
     mut next_particle_category_field: ResMut<NextState<ParticleEditorCategoryState>>,
 ) {
     ev_particle_editor_update.read().for_each(|_| {
@@ -729,6 +743,7 @@ pub fn update_particle_editor_fields(
                 solid,
                 movable_solid,
                 gas,
+                viscosity,
             )) = particle_query.get(*entity)
             {
                 particle_editor_name.0 = particle_editor_selected_type.0.name.clone();
@@ -781,6 +796,7 @@ pub fn update_particle_editor_fields(
                 if let Some(_) = liquid {
                     next_particle_category_field.set(ParticleEditorCategoryState::Liquid)
                 }
+                particle_editor_liquid_field.viscosity = viscosity.copied().unwrap_or_default();
                 if let Some(_) = gas {
                     next_particle_category_field.set(ParticleEditorCategoryState::Gas)
                 }
@@ -934,6 +950,10 @@ pub fn render_particle_editor(
                                             &mut particle_editor_gas_field,
                                             &current_particle_category_field,
                                         );
+                                        render_viscosity_field(
+                                            ui,
+                                            &mut particle_editor_liquid_field,
+                                        );
                                         render_flows_color_field(
                                             ui,
                                             &mut particle_editor_flows_color_field,
@@ -1550,7 +1570,7 @@ fn render_burns_field(
                 particle_burns_field.blueprint.0.spreads = Some(Fire {
                     burn_radius: 2.,
                     chance_to_spread: 0.01,
-                    destroys_on_spread: false,
+                    on_spread: SpreadEffect::Keep,
                 });
             } else {
                 particle_burns_field.blueprint.0.spreads = None;
@@ -1583,16 +1603,17 @@ fn render_burns_field(
                     0.0..=1.0,
                 ));
             });
-            ui.add(egui::Checkbox::new(
-                &mut particle_burns_field
-                    .blueprint
-                    .0
-                    .spreads
-                    .as_mut()
-                    .unwrap()
-                    .destroys_on_spread,
-                "Destroys on spread",
-            ));
+            let spreads = particle_burns_field.blueprint.0.spreads.as_mut().unwrap();
+            let mut destroys_on_spread = spreads.on_spread == SpreadEffect::Destroy;
+            if ui
+                .add(egui::Checkbox::new(
+                    &mut destroys_on_spread,
+                    "Destroys on spread",
+                ))
+                .changed()
+            {
+                spreads.on_spread = SpreadEffect::from(destroys_on_spread);
+            }
             ui.add(egui::Checkbox::new(
                 &mut particle_burns_field.spawns_on_fire,
                 "Spawns on fire",
@@ -1628,6 +1649,19 @@ pub fn render_fluidity_field(
     });
 }
 
+pub fn render_viscosity_field(
+    ui: &mut egui::Ui,
+    particle_liquid_field: &mut ResMut<ParticleEditorLiquid>,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Viscosity: ");
+        ui.add(egui::Slider::new(
+            &mut particle_liquid_field.viscosity.0 .0,
+            0..=255,
+        ));
+    });
+}
+
 fn particle_editor_save(
     (mut commands, mut ev_particle_editor_save): (Commands, EventReader<ParticleEditorSave>),
     particle_type_map: Res<ParticleTypeMap>,
@@ -1739,6 +1773,11 @@ fn particle_editor_save(
                     particle_density_field.blueprint,
                     particle_max_velocity_field.blueprint,
                 ));
+                if particle_editor_liquid_field.viscosity.0 .0 > 0 {
+                    commands
+                        .entity(entity)
+                        .insert(particle_editor_liquid_field.viscosity);
+                }
                 if particle_editor_flows_color_field.enable {
                     commands
                         .entity(entity)
@@ -1968,6 +2007,7 @@ pub struct ParticleEditorMovableSolid {
 #[derive(Resource, Clone, Default, Debug)]
 pub struct ParticleEditorLiquid {
     blueprint: LiquidBlueprint,
+    viscosity: ViscosityBlueprint,
 }
 
 #[derive(Resource, Clone, Default, Debug)]